@@ -0,0 +1,95 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use shady_generator::node_operation::{FunctionLibrary, NativeOperation, NodeOperation};
+use shady_generator::{Connection, ConnectionAttempt, ConnectionTo, NativeType, Shader};
+
+/// Shader of `size` additions, each node adding the two previous ones
+fn init_ladder_shader(size: usize) -> (Shader, Vec<String>) {
+    let mut shader = Shader::new("Ladder".to_string());
+    let input = shader.new_input_property(NativeType::Float, false);
+    let input = shader.add_input_property(input).unwrap().reference.clone();
+    let output = shader.new_output_property(NativeType::Float);
+    let output = shader
+        .add_output_property(output)
+        .unwrap()
+        .reference
+        .clone();
+    let mut ids: Vec<String> = Vec::with_capacity(size);
+    for index in 0..size {
+        let node = shader.new_node(
+            "Add",
+            NodeOperation::NativeOperation(NativeOperation::Add(NativeType::Float)),
+        );
+        let id = shader.create_node(node).unwrap().unique_id().clone();
+        for (field_name, offset) in [("a", 1), ("b", 2)] {
+            let connection_from = match index.checked_sub(offset) {
+                Some(previous) => Connection::SingleOutputNode {
+                    id: ids[previous].clone(),
+                },
+                None => Connection::InputProperty { id: input.clone() },
+            };
+            shader
+                .connect(ConnectionAttempt {
+                    connection_from,
+                    connection_to: ConnectionTo::Node {
+                        id: id.clone(),
+                        field_name: field_name.to_string(),
+                    },
+                })
+                .unwrap();
+        }
+        ids.push(id);
+    }
+    shader
+        .connect(ConnectionAttempt {
+            connection_from: Connection::SingleOutputNode {
+                id: ids.last().unwrap().clone(),
+            },
+            connection_to: ConnectionTo::OutputProperty { id: output },
+        })
+        .unwrap();
+    (shader, ids)
+}
+
+fn node_generation(c: &mut Criterion) {
+    let functions = FunctionLibrary::new();
+    let mut group = c.benchmark_group("node_generation");
+    for size in [100, 1_000, 5_000] {
+        let (shader, ids) = init_ladder_shader(size);
+        group.bench_with_input(BenchmarkId::new("to_glsl", size), &shader, |b, shader| {
+            b.iter(|| shader.to_glsl(black_box(&functions)).unwrap());
+        });
+        group.bench_with_input(
+            BenchmarkId::new("upstream_nodes", size),
+            &shader,
+            |b, shader| {
+                b.iter(|| {
+                    shader
+                        .upstream_nodes(black_box(ids.last().unwrap()))
+                        .unwrap()
+                });
+            },
+        );
+        // Refused as it would close a loop, after a full dependency path search
+        let attempt = ConnectionAttempt {
+            connection_from: Connection::SingleOutputNode {
+                id: ids.last().unwrap().clone(),
+            },
+            connection_to: ConnectionTo::Node {
+                id: ids[0].clone(),
+                field_name: "a".to_string(),
+            },
+        };
+        group.bench_with_input(
+            BenchmarkId::new("cyclic_connect", size),
+            &attempt,
+            |b, attempt| {
+                let mut shader = shader.clone();
+                b.iter(|| shader.connect(black_box(attempt.clone())).unwrap_err());
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, node_generation);
+criterion_main!(benches);
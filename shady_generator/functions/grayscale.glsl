@@ -0,0 +1,4 @@
+// Gray color with the luminance of a linear RGB color
+vec3 grayscale(vec3 color) {
+    return vec3(luminance(color));
+}
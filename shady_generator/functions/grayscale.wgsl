@@ -0,0 +1,4 @@
+// Gray color with the luminance of a linear RGB color
+fn grayscale(color: vec3<f32>) -> vec3<f32> {
+    return vec3<f32>(luminance(color));
+}
@@ -0,0 +1,4 @@
+// Relative luminance of a linear RGB color (Rec. 709 coefficients)
+float luminance(vec3 color) {
+    return dot(color, vec3(0.2126, 0.7152, 0.0722));
+}
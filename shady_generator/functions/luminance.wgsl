@@ -0,0 +1,4 @@
+// Relative luminance of a linear RGB color (Rec. 709 coefficients)
+fn luminance(color: vec3<f32>) -> f32 {
+    return dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
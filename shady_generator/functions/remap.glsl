@@ -0,0 +1,4 @@
+// Maps a value from the `from_min`..`from_max` range to the `to_min`..`to_max` range
+float remap(float value, float from_min, float from_max, float to_min, float to_max) {
+    return to_min + (value - from_min) * (to_max - to_min) / (from_max - from_min);
+}
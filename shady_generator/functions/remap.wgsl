@@ -0,0 +1,4 @@
+// Maps a value from the `from_min`..`from_max` range to the `to_min`..`to_max` range
+fn remap(value: f32, from_min: f32, from_max: f32, to_min: f32, to_max: f32) -> f32 {
+    return to_min + (value - from_min) * (to_max - to_min) / (from_max - from_min);
+}
@@ -0,0 +1,142 @@
+use std::fmt::{self, Display, Write};
+
+/// Indentation of a nested code block
+const INDENTATION: &str = "    ";
+
+/// Shader code writer, streaming the generated code into any `fmt::Write` output.
+///
+/// Every line written, including empty ones, starts with the current indentation, and the written
+/// lines are counted
+pub struct CodeWriter<'a, W: Write> {
+    output: &'a mut W,
+    indentation: usize,
+    line_count: usize,
+    at_line_start: bool,
+}
+
+impl<'a, W: Write> CodeWriter<'a, W> {
+    /// Instantiates a writer with no indentation, writing into `output`
+    pub const fn new(output: &'a mut W) -> Self {
+        Self {
+            output,
+            indentation: 0,
+            line_count: 0,
+            at_line_start: true,
+        }
+    }
+
+    /// Current indentation level
+    pub const fn indentation(&self) -> usize {
+        self.indentation
+    }
+
+    /// Number of lines written, the last line counting only once terminated
+    pub const fn line_count(&self) -> usize {
+        self.line_count
+    }
+
+    /// Increases the indentation of the next lines
+    pub const fn indent(&mut self) {
+        self.indentation += 1;
+    }
+
+    /// Decreases the indentation of the next lines
+    pub fn dedent(&mut self) {
+        if self.indentation == 0 {
+            log::warn!("Code writer dedented below the top level");
+        }
+        self.indentation = self.indentation.saturating_sub(1);
+    }
+
+    /// Writes `code` and terminates the line
+    pub fn line(&mut self, code: impl Display) -> fmt::Result {
+        writeln!(self, "{}", code)
+    }
+
+    /// Writes an empty line
+    pub fn empty_line(&mut self) -> fmt::Result {
+        self.write_char('\n')
+    }
+
+    /// Writes a `// title` section comment line
+    pub fn section(&mut self, title: &str) -> fmt::Result {
+        writeln!(self, "// {}", title)
+    }
+
+    fn write_indentation(&mut self) -> fmt::Result {
+        for _ in 0..self.indentation {
+            self.output.write_str(INDENTATION)?;
+        }
+        self.at_line_start = false;
+        Ok(())
+    }
+
+    fn write_fragment(&mut self, fragment: &str) -> fmt::Result {
+        if fragment.is_empty() {
+            return Ok(());
+        }
+        if self.at_line_start {
+            self.write_indentation()?;
+        }
+        self.output.write_str(fragment)
+    }
+}
+
+impl<W: Write> Write for CodeWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut lines = s.split('\n');
+        if let Some(first) = lines.next() {
+            self.write_fragment(first)?;
+        }
+        for line in lines {
+            if self.at_line_start {
+                self.write_indentation()?;
+            }
+            self.output.write_char('\n')?;
+            self.line_count += 1;
+            self.at_line_start = true;
+            self.write_fragment(line)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_indented_lines() {
+        let mut output = String::new();
+        let mut writer = CodeWriter::new(&mut output);
+        writer.line("void main() {").unwrap();
+        writer.indent();
+        writer.line("float a = 1.0;").unwrap();
+        write!(writer, "float b = a;\nfloat c").unwrap();
+        writer.line(" = b;").unwrap();
+        writer.empty_line().unwrap();
+        writer.dedent();
+        writer.line("}").unwrap();
+        assert_eq!(writer.line_count(), 6);
+        assert_eq!(
+            output,
+            "void main() {\n    float a = 1.0;\n    float b = a;\n    float c = b;\n    \n}\n"
+        );
+    }
+
+    #[test]
+    fn writes_sections() {
+        let mut output = String::new();
+        let mut writer = CodeWriter::new(&mut output);
+        writer.section("Constants").unwrap();
+        writer
+            .line("const float A = 1.0;\nconst float B = 2.0;")
+            .unwrap();
+        writer.empty_line().unwrap();
+        assert_eq!(writer.line_count(), 4);
+        assert_eq!(
+            output,
+            "// Constants\nconst float A = 1.0;\nconst float B = 2.0;\n\n"
+        );
+    }
+}
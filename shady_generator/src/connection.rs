@@ -1,7 +1,7 @@
 use crate::NativeType;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, PartialOrd, Ord, Hash)]
 pub enum ConnectionTo {
     Node { id: String, field_name: String },
     OutputProperty { id: String },
@@ -21,7 +21,14 @@ pub struct ConnectionMessage {
 
 pub type ConnectionResponse = Option<Connection>;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, PartialOrd, Ord)]
+/// Connections severed by a removal, with the endpoint and the removed connection
+pub type SeveredConnections = Vec<(ConnectionTo, Connection)>;
+
+/// Source of a node input field or an output property connection.
+///
+/// Connections reference the constant, property or node by its id, handles are only used by
+/// the shader storage and traversals which resolve each id once
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, PartialOrd, Ord, Hash)]
 pub enum Connection {
     Constant { id: String },
     InputProperty { id: String },
@@ -39,11 +46,28 @@ impl ConnectionMessage {
 }
 
 impl Connection {
+    /// Id of the constant, property or node the connection comes from
+    pub const fn source_id(&self) -> &String {
+        match self {
+            Connection::Constant { id }
+            | Connection::InputProperty { id }
+            | Connection::ComplexOutputNode { id, .. }
+            | Connection::SingleOutputNode { id } => id,
+        }
+    }
+
     pub fn glsl_call(&self) -> String {
         match self {
             Connection::InputProperty { id }
             | Connection::SingleOutputNode { id }
             | Connection::Constant { id } => id.clone(),
+            // Fields named by index are matrix columns
+            Connection::ComplexOutputNode {
+                id: node_id,
+                field_name,
+            } if field_name.bytes().all(|b| b.is_ascii_digit()) => {
+                format!("{}[{}]", node_id, field_name)
+            }
             Connection::ComplexOutputNode {
                 id: node_id,
                 field_name,
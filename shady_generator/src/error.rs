@@ -1,4 +1,4 @@
-use crate::NativeType;
+use crate::{Diagnostic, GlslVersion, GraphicLibrary, IdentifierIssue, NativeType};
 use thiserror::Error;
 
 /// Shady Generator Error types
@@ -39,15 +39,115 @@ pub enum ShadyError {
     MissingOutputProperty(String),
     #[error("Could not create output property with duplicate id `{0}`")]
     DuplicateOutputProperty(String),
-    /// Node generation processing reached its max depth
-    #[error("Node processing reached depth {0}, check your nodes for potential loops")]
-    MaxDepthReached(usize),
-    /// Node generation detected a Node loop in the shader
-    #[error("Detected a loop for nodes {}", .0.join(", "))]
+    /// Node generation detected a Node loop in the shader, each node of the cycle path depends
+    /// on the next one
+    #[error("Detected a loop for nodes {}", .0.join(" -> "))]
     NodeLoopDetected(Vec<String>),
+    /// The connection would create a Node loop, each node of the cycle path depends on the next
+    /// one
+    #[error("Connection would create a loop for nodes {}", .0.join(" -> "))]
+    CyclicConnection(Vec<String>),
+    /// Missing sub-graph
+    #[error("Could not find sub-graph `{0}`")]
+    MissingSubGraph(String),
+    #[error("Could not create sub-graph with duplicate name `{0}`")]
+    DuplicateSubGraph(String),
+    /// The sub-graph has no output property and can't be generated as a function
+    #[error("Sub-graph `{0}` has no output property")]
+    EmptySubGraph(String),
+    /// The node input or output doesn't match the current ports of its sub-graph
+    #[error(
+        "Node {node} doesn't match the input and output properties of sub-graph `{sub_graph}`"
+    )]
+    SubGraphMismatch {
+        /// Node instantiating the sub-graph
+        node: String,
+        /// Instantiated sub-graph
+        sub_graph: String,
+    },
+    /// Sub-graphs instantiating each other, each sub-graph of the cycle path instantiates the next
+    /// one
+    #[error("Detected a loop for sub-graphs {}", .0.join(" -> "))]
+    SubGraphLoopDetected(Vec<String>),
+    /// The custom function is not available for the graphic library in the function library
+    #[error("Could not find function `{function}` for {library} in the function library")]
+    MissingFunctionSource {
+        /// Function name
+        function: String,
+        /// Targeted graphic library
+        library: GraphicLibrary,
+    },
+    /// Custom functions calling each other, each function of the cycle path calls the next one
+    #[error("Detected a loop for functions {}", .0.join(" -> "))]
+    FunctionLoopDetected(Vec<String>),
+    /// The custom function is not declared by its file
+    #[error("Could not find a declaration of function `{0}`")]
+    MissingFunction(String),
+    /// The custom function is declared with different parameters
+    #[error("Function `{0}` is overloaded")]
+    FunctionOverload(String),
+    /// The custom function signature can't be parsed or used by a node
+    #[error("Invalid signature for function `{function}`: {reason}")]
+    InvalidFunctionSignature {
+        /// Function name
+        function: String,
+        /// Signature issue
+        reason: String,
+    },
+    /// The save file was written by a newer version
+    #[error(
+        "Save format version {version} is not supported, latest supported version is {supported}"
+    )]
+    UnsupportedSaveVersion {
+        /// Save file format version
+        version: u32,
+        /// Latest supported format version
+        supported: u32,
+    },
+    /// The save file structure is invalid
+    #[error("Invalid save file format: {0}")]
+    InvalidSaveFormat(String),
+    /// The loaded save file has broken connections
+    #[error(
+        "Save file has broken connections: {}",
+        .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+    )]
+    BrokenSave(Vec<Diagnostic>),
     /// A Node can't be connected to itself
     #[error("Tried to connect Node {0} to itself")]
     SameNodeConnection(String),
+    /// Native type not supported by the target
+    #[error("Type {native_type} is not supported by {target}")]
+    UnsupportedNativeType {
+        /// Unsupported native type
+        native_type: NativeType,
+        /// Target graphic library or language version
+        target: String,
+    },
+    /// Native function not available for the target
+    #[error("Function `{function}` is not supported by {target}")]
+    UnsupportedNativeFunction {
+        /// Unsupported function name
+        function: String,
+        /// Target graphic library or language version
+        target: String,
+    },
+    /// Native operation not available for the target
+    #[error("Operation `{operation}` is not supported by {target}")]
+    UnsupportedNativeOperation {
+        /// Unsupported operation name
+        operation: String,
+        /// Target graphic library or language version
+        target: String,
+    },
+    /// The GLSL version doesn't match the graphic library profile
+    #[error("{version} is not available for {library}")]
+    IncompatibleGlslVersion {
+        /// Targeted GLSL version
+        version: GlslVersion,
+        /// Targeted graphic library
+        library: GraphicLibrary,
+    },
     /// I/O Error from `std::io::Error`
     #[error("I/O Error: {0}")]
     IOError(
@@ -71,4 +171,55 @@ pub enum ShadyError {
         #[source]
         serde_yaml::Error,
     ),
+    /// Serialization error for Shader from `serde_json::Error`
+    #[error("Failed to parse JSON Shader file: {0}")]
+    WrongJsonShaderSave(
+        #[from]
+        #[source]
+        serde_json::Error,
+    ),
+    /// Serialization error for Shader from `ron::Error`
+    #[error("Failed to parse RON Shader file: {0}")]
+    WrongRonShaderSave(
+        #[from]
+        #[source]
+        ron::Error,
+    ),
+    /// The save file extension matches no `SaveFormat`
+    #[error("Unknown save file format for `{0}`")]
+    UnknownSaveFormat(String),
+    /// A shader element identifier can't be used in the generated code
+    #[error("Invalid identifier `{identifier}`: {issue}")]
+    InvalidIdentifier {
+        /// The invalid identifier
+        identifier: String,
+        /// The identifier issue
+        issue: IdentifierIssue,
+    },
+    /// Non finite float values have no literal for the target
+    #[error("Value {value} has non finite components, unsupported by {target}")]
+    NonFiniteLiteral {
+        /// Constant value
+        value: String,
+        /// Target graphic library or language version
+        target: String,
+    },
+    /// A function argument required to be a constant expression isn't connected to a constant
+    #[error("Field `{field}` of node {node} must be connected to a constant")]
+    NonConstantInput {
+        /// Node id
+        node: String,
+        /// Input field name
+        field: String,
+    },
+    /// An identifier is used by two shader elements
+    #[error("Identifier `{0}` is already used by another shader element")]
+    IdentifierConflict(String),
+    /// The generated code couldn't be written to the output, from `std::fmt::Error`
+    #[error("Failed to write shader code: {0}")]
+    CodeWriting(
+        #[from]
+        #[source]
+        std::fmt::Error,
+    ),
 }
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 
 /// Supported graphics libraries
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub enum GraphicLibrary {
     /// OpenGL (GLSL code)
     OpenGl,
@@ -19,6 +19,16 @@ impl Default for GraphicLibrary {
     }
 }
 
+impl GraphicLibrary {
+    /// Source file extension for the library shading language
+    pub const fn code_extension(&self) -> &'static str {
+        match self {
+            GraphicLibrary::OpenGl | GraphicLibrary::OpenGlEs => "glsl",
+            GraphicLibrary::WebGPU => "wgsl",
+        }
+    }
+}
+
 impl Display for GraphicLibrary {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -0,0 +1,120 @@
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+const ID_PREFIX: &str = "v";
+const RANDOM_ID_LENGTH: usize = 10;
+const ALPHANUMERIC: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Unique id source of the shader elements, owned by the `Shader`.
+///
+/// The generator state is saved along the shader, so reloading a save keeps producing the same
+/// sequence of ids
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub enum IdGenerator {
+    /// Incrementing ids: `v0`, `v1`, `v2`...
+    Sequential {
+        /// Next index to generate
+        next: u64,
+    },
+    /// Reproducible pseudo random alphanumeric ids
+    Seeded {
+        /// Current state of the pseudo random sequence, initialized with the seed
+        state: u64,
+    },
+    /// Non reproducible random alphanumeric ids, from the thread rng
+    Random,
+}
+
+impl Default for IdGenerator {
+    fn default() -> Self {
+        Self::sequential()
+    }
+}
+
+impl IdGenerator {
+    /// Instantiates a sequential generator starting at `v0`
+    pub const fn sequential() -> Self {
+        Self::Sequential { next: 0 }
+    }
+
+    /// Instantiates a pseudo random generator from `seed`
+    pub const fn seeded(seed: u64) -> Self {
+        Self::Seeded { state: seed }
+    }
+
+    /// Generates the next id, without any collision check. See `Shader::generate_id`
+    pub fn next_id(&mut self) -> String {
+        match self {
+            Self::Sequential { next } => {
+                let id = format!("{}{}", ID_PREFIX, next);
+                *next += 1;
+                id
+            }
+            Self::Seeded { state } => {
+                let id = (0..RANDOM_ID_LENGTH)
+                    .map(|_| {
+                        let index = split_mix(state) % ALPHANUMERIC.len() as u64;
+                        char::from(ALPHANUMERIC[usize::try_from(index).unwrap_or_default()])
+                    })
+                    .collect::<String>();
+                format!("{}{}", ID_PREFIX, id)
+            }
+            Self::Random => format!(
+                "{}{}",
+                ID_PREFIX,
+                thread_rng()
+                    .sample_iter(&Alphanumeric)
+                    .take(RANDOM_ID_LENGTH)
+                    .map(char::from)
+                    .collect::<String>()
+            ),
+        }
+    }
+}
+
+impl Display for IdGenerator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sequential { .. } => write!(f, "Sequential"),
+            Self::Seeded { .. } => write!(f, "Seeded"),
+            Self::Random => write!(f, "Random"),
+        }
+    }
+}
+
+/// `SplitMix64` step, stable across platforms and dependency versions
+const fn split_mix(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sequential_ids() {
+        let mut generator = IdGenerator::sequential();
+        let ids: Vec<String> = (0..3).map(|_| generator.next_id()).collect();
+        assert_eq!(ids, vec!["v0", "v1", "v2"]);
+        assert_eq!(generator, IdGenerator::Sequential { next: 3 });
+    }
+
+    #[test]
+    fn seeded_ids_are_reproducible() {
+        let mut a = IdGenerator::seeded(42);
+        let mut b = IdGenerator::seeded(42);
+        let ids: Vec<String> = (0..10).map(|_| a.next_id()).collect();
+        assert_eq!(ids, (0..10).map(|_| b.next_id()).collect::<Vec<_>>());
+        assert_ne!(ids[0], ids[1]);
+        assert!(ids
+            .iter()
+            .all(|id| id.len() == RANDOM_ID_LENGTH + 1 && id.starts_with(ID_PREFIX)));
+        assert_ne!(IdGenerator::seeded(43).next_id(), ids[0]);
+    }
+}
@@ -0,0 +1,273 @@
+use crate::error::ShadyError;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::sync::LazyLock;
+
+/// Prefix of sanitized names which would be invalid or reserved identifiers
+const SAFE_PREFIX: &str = "v";
+
+/// GLSL keywords and reserved words which can't be used as identifiers
+const GLSL_RESERVED_WORDS: &str = "
+    attribute const uniform varying buffer shared coherent volatile restrict readonly writeonly
+    layout centroid flat smooth noperspective patch sample break continue do for while switch case
+    default if else subroutine in out inout float double int void bool true false invariant precise
+    discard return mat2 mat3 mat4 mat2x2 mat2x3 mat2x4 mat3x2 mat3x3 mat3x4 mat4x2 mat4x3 mat4x4
+    dmat2 dmat3 dmat4 dmat2x2 dmat2x3 dmat2x4 dmat3x2 dmat3x3 dmat3x4 dmat4x2 dmat4x3 dmat4x4 vec2
+    vec3 vec4 ivec2 ivec3 ivec4 bvec2 bvec3 bvec4 dvec2 dvec3 dvec4 uint uvec2 uvec3 uvec4 lowp
+    mediump highp precision sampler1D sampler2D sampler3D samplerCube sampler2DShadow struct
+    common partition active asm class union enum typedef template this resource goto inline
+    noinline public static extern external interface long short half fixed unsigned superp input
+    output hvec2 hvec3 hvec4 fvec2 fvec3 fvec4 filter sizeof cast namespace using main
+    sampler2DArray sampler2DArrayShadow samplerCubeShadow isampler2D isampler3D isamplerCube
+    isampler2DArray usampler2D usampler3D usamplerCube usampler2DArray
+";
+
+/// GLSL built-in functions of the desktop, ES and legacy profiles, a variable with the same name
+/// would shadow them
+const GLSL_BUILTIN_FUNCTIONS: &str = "
+    radians degrees sin cos tan asin acos atan sinh cosh tanh asinh acosh atanh pow exp log exp2
+    log2 sqrt inversesqrt abs sign floor trunc round roundEven ceil fract mod modf min max clamp
+    mix step smoothstep isnan isinf floatBitsToInt floatBitsToUint intBitsToFloat uintBitsToFloat
+    fma frexp ldexp packUnorm2x16 packSnorm2x16 packUnorm4x8 packSnorm4x8 unpackUnorm2x16
+    unpackSnorm2x16 unpackUnorm4x8 unpackSnorm4x8 packHalf2x16 unpackHalf2x16 packDouble2x32
+    unpackDouble2x32 length distance dot cross normalize ftransform faceforward reflect refract
+    matrixCompMult outerProduct transpose determinant inverse lessThan lessThanEqual greaterThan
+    greaterThanEqual equal notEqual any all not uaddCarry usubBorrow umulExtended imulExtended
+    bitfieldExtract bitfieldInsert bitfieldReverse bitCount findLSB findMSB textureSize
+    textureQueryLod textureQueryLevels textureSamples texture textureProj textureLod textureOffset
+    texelFetch texelFetchOffset textureProjOffset textureLodOffset textureProjLod
+    textureProjLodOffset textureGrad textureGradOffset textureProjGrad textureProjGradOffset
+    textureGather textureGatherOffset textureGatherOffsets texture1D texture1DProj texture1DLod
+    texture1DProjLod texture2D texture2DProj texture2DLod texture2DProjLod texture3D texture3DProj
+    texture3DLod texture3DProjLod textureCube textureCubeLod shadow1D shadow2D shadow1DProj
+    shadow2DProj shadow1DLod shadow2DLod shadow1DProjLod shadow2DProjLod atomicCounterIncrement
+    atomicCounterDecrement atomicCounter atomicAdd atomicMin atomicMax atomicAnd atomicOr atomicXor
+    atomicExchange atomicCompSwap imageSize imageSamples imageLoad imageStore imageAtomicAdd
+    imageAtomicMin imageAtomicMax imageAtomicAnd imageAtomicOr imageAtomicXor imageAtomicExchange
+    imageAtomicCompSwap dFdx dFdy dFdxFine dFdyFine dFdxCoarse dFdyCoarse fwidth fwidthFine
+    fwidthCoarse interpolateAtCentroid interpolateAtSample interpolateAtOffset noise1 noise2 noise3
+    noise4 EmitStreamVertex EndStreamPrimitive EmitVertex EndPrimitive barrier memoryBarrier
+    memoryBarrierAtomicCounter memoryBarrierBuffer memoryBarrierShared memoryBarrierImage
+    groupMemoryBarrier
+";
+
+/// WGSL keywords, reserved words and predeclared type names which can't be used as identifiers
+const WGSL_RESERVED_WORDS: &str = "
+    alias break case const const_assert continue continuing default diagnostic discard else
+    enable false fn for if let loop override requires return struct switch true var while
+    NULL Self abstract active alignas alignof as asm asm_fragment async attribute auto await
+    become binding_array cast catch class co_await co_return co_yield coherent column_major
+    common compile compile_fragment concept const_cast consteval constexpr constinit crate
+    debugger decltype delete demote demote_to_helper do dynamic_cast enum explicit export extends
+    extern external fallthrough filter final finally friend from fxgroup get goto groupshared
+    highp impl implements import inline instanceof interface layout lowp macro macro_rules match
+    mediump meta mod module move mut mutable namespace new nil noexcept noinline nointerpolation
+    noperspective null nullptr of operator package packoffset partition pass patch pixelfragment
+    precise precision premerge priv protected pub public readonly ref regardless register
+    reinterpret_cast require resource restrict self set shared sizeof smooth snorm static
+    static_assert static_cast std subroutine super target template this thread_local throw trait
+    try type typedef typeid typename typeof union unless unorm unsafe unsized use using varying
+    virtual volatile wgsl where with writeonly yield
+    array atomic bitcast bool f16 f32 function handle i32 mat2x2 mat2x3 mat2x4 mat3x2 mat3x3
+    mat3x4 mat4x2 mat4x3 mat4x4 private ptr sampler sampler_comparison storage texture_1d
+    texture_2d texture_2d_array texture_3d texture_cube texture_cube_array
+    texture_multisampled_2d texture_storage_1d texture_storage_2d texture_storage_2d_array
+    texture_storage_3d texture_depth_2d texture_depth_2d_array texture_depth_cube
+    texture_depth_cube_array texture_depth_multisampled_2d u32 uniform vec2 vec3 vec4 workgroup
+    read write read_write
+";
+
+/// WGSL built-in functions, a variable with the same name would shadow them
+const WGSL_BUILTIN_FUNCTIONS: &str = "
+    all any select arrayLength abs acos acosh asin asinh atan atanh atan2 ceil clamp cos cosh
+    countLeadingZeros countOneBits countTrailingZeros cross degrees determinant distance dot exp
+    exp2 extractBits faceForward firstLeadingBit firstTrailingBit floor fma fract frexp insertBits
+    inverseSqrt ldexp length log log2 max min mix modf normalize pow quantizeToF16 radians reflect
+    refract reverseBits round saturate sign sin sinh smoothstep sqrt step tan tanh transpose trunc
+    dpdx dpdxCoarse dpdxFine dpdy dpdyCoarse dpdyFine fwidth fwidthCoarse fwidthFine
+    textureDimensions textureGather textureGatherCompare textureLoad textureNumLayers
+    textureNumLevels textureNumSamples textureSample textureSampleBias textureSampleCompare
+    textureSampleCompareLevel textureSampleGrad textureSampleLevel textureSampleBaseClampToEdge
+    textureStore atomicLoad atomicStore atomicAdd atomicSub atomicMax atomicMin atomicAnd atomicOr
+    atomicXor atomicExchange atomicCompareExchangeWeak pack4x8snorm pack4x8unorm pack2x16snorm
+    pack2x16unorm pack2x16float unpack4x8snorm unpack4x8unorm unpack2x16snorm unpack2x16unorm
+    unpack2x16float storageBarrier workgroupBarrier workgroupUniformLoad
+";
+
+static RESERVED_WORDS: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
+    GLSL_RESERVED_WORDS
+        .split_whitespace()
+        .chain(WGSL_RESERVED_WORDS.split_whitespace())
+        .chain(builtin_function_names())
+        .collect()
+});
+
+/// Reason an identifier can't be used in the generated shader code
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum IdentifierIssue {
+    /// The identifier is empty
+    Empty,
+    /// The identifier contains a character other than ascii alphanumeric characters and `_`
+    InvalidCharacter(char),
+    /// The identifier is a single `_`, which WGSL doesn't accept as an identifier
+    LoneUnderscore,
+    /// The identifier starts with a digit
+    LeadingDigit,
+    /// The identifier starts with the `gl_` or `GL_` prefixes reserved by OpenGL
+    ReservedPrefix,
+    /// The identifier contains two consecutive underscores, reserved by GLSL and WGSL
+    DoubleUnderscore,
+    /// The identifier is a GLSL or WGSL keyword, reserved word or built-in function name
+    ReservedWord,
+}
+
+impl Display for IdentifierIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "empty identifier"),
+            Self::InvalidCharacter(c) => write!(f, "invalid character {:?}", c),
+            Self::LoneUnderscore => write!(f, "lone underscore"),
+            Self::LeadingDigit => write!(f, "starts with a digit"),
+            Self::ReservedPrefix => write!(f, "starts with a reserved prefix"),
+            Self::DoubleUnderscore => write!(f, "contains a double underscore"),
+            Self::ReservedWord => write!(f, "reserved GLSL or WGSL word"),
+        }
+    }
+}
+
+/// Names of the GLSL and WGSL built-in functions
+pub fn builtin_function_names() -> impl Iterator<Item = &'static str> {
+    GLSL_BUILTIN_FUNCTIONS
+        .split_whitespace()
+        .chain(WGSL_BUILTIN_FUNCTIONS.split_whitespace())
+}
+
+/// Is `word` a GLSL or WGSL keyword, reserved word or built-in function name
+pub fn is_reserved_word(word: &str) -> bool {
+    RESERVED_WORDS.contains(word)
+}
+
+fn identifier_issue(identifier: &str) -> Option<IdentifierIssue> {
+    let Some(first) = identifier.chars().next() else {
+        return Some(IdentifierIssue::Empty);
+    };
+    if let Some(c) = identifier
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && *c != '_')
+    {
+        return Some(IdentifierIssue::InvalidCharacter(c));
+    }
+    if identifier == "_" {
+        return Some(IdentifierIssue::LoneUnderscore);
+    }
+    if first.is_ascii_digit() {
+        return Some(IdentifierIssue::LeadingDigit);
+    }
+    if identifier.starts_with("gl_") || identifier.starts_with("GL_") {
+        return Some(IdentifierIssue::ReservedPrefix);
+    }
+    if identifier.contains("__") {
+        return Some(IdentifierIssue::DoubleUnderscore);
+    }
+    if is_reserved_word(identifier) {
+        return Some(IdentifierIssue::ReservedWord);
+    }
+    None
+}
+
+/// Checks that `identifier` can be used as a variable name in both GLSL and WGSL code
+pub fn check_identifier(identifier: &str) -> Result<(), ShadyError> {
+    identifier_issue(identifier).map_or(Ok(()), |issue| {
+        Err(ShadyError::InvalidIdentifier {
+            identifier: identifier.to_string(),
+            issue,
+        })
+    })
+}
+
+/// Converts `name` to a valid identifier, passing `check_identifier`.
+///
+/// Invalid characters are replaced by single underscores, and names starting with a digit or a
+/// reserved prefix, or matching a reserved word, are prefixed with `v_`
+pub fn sanitize_identifier(name: &str) -> String {
+    let mut res = String::with_capacity(name.len());
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            res.push(c);
+        } else if !res.is_empty() && !res.ends_with('_') {
+            res.push('_');
+        }
+    }
+    let res = res.trim_end_matches('_');
+    if res.is_empty() {
+        SAFE_PREFIX.to_string()
+    } else if identifier_issue(res).is_some() {
+        format!("{}_{}", SAFE_PREFIX, res)
+    } else {
+        res.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_operation::NativeFunction;
+    use crate::GlslVersion;
+
+    fn issue(identifier: &str) -> Option<IdentifierIssue> {
+        match check_identifier(identifier) {
+            Ok(()) => None,
+            Err(ShadyError::InvalidIdentifier { issue, .. }) => Some(issue),
+            Err(e) => panic!("Unexpected error {}", e),
+        }
+    }
+
+    #[test]
+    fn checks_identifiers() {
+        assert_eq!(issue("my_var2"), None);
+        assert_eq!(issue("Gl_Pos123"), None);
+        assert_eq!(issue(""), Some(IdentifierIssue::Empty));
+        assert_eq!(
+            issue("my var"),
+            Some(IdentifierIssue::InvalidCharacter(' '))
+        );
+        assert_eq!(issue("_"), Some(IdentifierIssue::LoneUnderscore));
+        assert_eq!(issue("_a"), None);
+        assert_eq!(issue("1st"), Some(IdentifierIssue::LeadingDigit));
+        assert_eq!(issue("gl_Position"), Some(IdentifierIssue::ReservedPrefix));
+        assert_eq!(issue("GL_ES"), Some(IdentifierIssue::ReservedPrefix));
+        assert_eq!(issue("my__var"), Some(IdentifierIssue::DoubleUnderscore));
+        assert_eq!(issue("float"), Some(IdentifierIssue::ReservedWord));
+        assert_eq!(issue("let"), Some(IdentifierIssue::ReservedWord));
+        for word in [
+            "null", "nullptr", "new", "ref", "module", "wgsl", "demote", "unless",
+        ] {
+            assert_eq!(issue(word), Some(IdentifierIssue::ReservedWord));
+        }
+        assert_eq!(issue("mix"), Some(IdentifierIssue::ReservedWord));
+        assert_eq!(issue("textureSample"), Some(IdentifierIssue::ReservedWord));
+    }
+
+    #[test]
+    fn reserves_every_native_function() {
+        for function in NativeFunction::VARIANTS {
+            for version in GlslVersion::VARIANTS {
+                assert!(is_reserved_word(function.glsl_function_name(*version)));
+            }
+            assert!(is_reserved_word(function.wgsl_function_name()));
+        }
+    }
+
+    #[test]
+    fn sanitizes_identifiers() {
+        assert_eq!(sanitize_identifier("MY VALUE"), "MY_VALUE");
+        assert_eq!(sanitize_identifier(" my -- value! "), "my_value");
+        assert_eq!(sanitize_identifier("1ST VALUE"), "v_1ST_VALUE");
+        assert_eq!(sanitize_identifier("gl_Position"), "v_gl_Position");
+        assert_eq!(sanitize_identifier("float"), "v_float");
+        assert_eq!(sanitize_identifier("__"), "v");
+        for name in ["MY VALUE", "1ST VALUE", "float", "__", "été", "a  b"] {
+            assert!(check_identifier(&sanitize_identifier(name)).is_ok());
+        }
+    }
+}
@@ -28,6 +28,15 @@ impl InputField {
         self.glsl_type
     }
 
+    /// Types accepted as a connection, tolerant fields accept the `glsl_type` tolerated types
+    pub fn accepted_types(&self) -> Vec<NativeType> {
+        if self.tolerant {
+            self.glsl_type.tolerated_input_types().to_vec()
+        } else {
+            vec![self.glsl_type]
+        }
+    }
+
     pub const fn new(glsl_type: NativeType) -> Self {
         Self {
             glsl_type,
@@ -0,0 +1,169 @@
+use crate::{GlslVersion, NativeType, ShadyError};
+
+/// Target language of the emitted literals
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LiteralTarget {
+    /// GLSL code for the given version
+    Glsl(GlslVersion),
+    /// WGSL code
+    Wgsl,
+}
+
+/// Values emitted as type correct shader code literals, parsed back to the same value
+pub trait Literal {
+    /// Shader code literal of the value for `target`
+    fn literal(&self, target: LiteralTarget) -> String;
+}
+
+impl Literal for bool {
+    fn literal(&self, _target: LiteralTarget) -> String {
+        self.to_string()
+    }
+}
+
+impl Literal for i32 {
+    fn literal(&self, target: LiteralTarget) -> String {
+        match target {
+            // `2147483648` overflows a GLSL `int` before being negated
+            LiteralTarget::Glsl(_) if *self == Self::MIN => format!("({} - 1)", Self::MIN + 1),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl Literal for u32 {
+    fn literal(&self, _target: LiteralTarget) -> String {
+        format!("{}u", self)
+    }
+}
+
+impl Literal for f32 {
+    fn literal(&self, target: LiteralTarget) -> String {
+        if self.is_finite() {
+            // The `Debug` representation is the shortest one parsed back to the same value, and
+            // always has a decimal point or an exponent
+            return format!("{:?}", self);
+        }
+        non_finite_literal(f64::from(*self), target)
+    }
+}
+
+impl Literal for f64 {
+    fn literal(&self, target: LiteralTarget) -> String {
+        match target {
+            LiteralTarget::Glsl(_) if self.is_finite() => format!("{:?}lf", self),
+            LiteralTarget::Glsl(_) => format!("double({})", non_finite_literal(*self, target)),
+            LiteralTarget::Wgsl => {
+                log::warn!("WGSL has no double type, emitting {} as a float", self);
+                format!("{:?}", self)
+            }
+        }
+    }
+}
+
+/// Infinite and NaN values have no literal, they are emitted from their bit pattern when the
+/// target allows it.
+///
+/// Legacy GLSL targets fall back to a division by zero, of unspecified result. WGSL rejects non
+/// finite constant expressions, `ConstantValue::literal` fails with
+/// `ShadyError::NonFiniteLiteral` instead of emitting them
+fn non_finite_literal(value: f64, target: LiteralTarget) -> String {
+    let (bits, division) = if value.is_nan() {
+        ("0x7FC00000u", "(0.0 / 0.0)")
+    } else if value.is_sign_positive() {
+        ("0x7F800000u", "(1.0 / 0.0)")
+    } else {
+        ("0xFF800000u", "(-1.0 / 0.0)")
+    };
+    log::warn!("Emitting non finite value {} for {:?}", value, target);
+    match target {
+        LiteralTarget::Glsl(version) if version.is_legacy() => division.to_string(),
+        LiteralTarget::Glsl(_) => format!("uintBitsToFloat({})", bits),
+        LiteralTarget::Wgsl => format!("bitcast<f32>({})", bits),
+    }
+}
+
+/// Constructor literal of a `native_type` vector or matrix with `components`, matrix components
+/// being listed column by column
+pub fn vector_literal<T: Literal>(
+    native_type: NativeType,
+    components: &[T],
+    target: LiteralTarget,
+) -> Result<String, ShadyError> {
+    let components: Vec<String> = components.iter().map(|c| c.literal(target)).collect();
+    Ok(format!(
+        "{}({})",
+        match target {
+            LiteralTarget::Glsl(_) => native_type.get_glsl_type(),
+            LiteralTarget::Wgsl => native_type.get_wgsl_type()?,
+        },
+        components.join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLSL: LiteralTarget = LiteralTarget::Glsl(GlslVersion::Glsl450);
+
+    #[test]
+    fn float_literals_are_typed() {
+        assert_eq!(1.0_f32.literal(GLSL), "1.0");
+        assert_eq!((-0.5_f32).literal(GLSL), "-0.5");
+        assert_eq!(0.1_f32.literal(GLSL), "0.1");
+        assert_eq!(1e20_f32.literal(GLSL), "1e20");
+        assert_eq!(1.5e-7_f32.literal(LiteralTarget::Wgsl), "1.5e-7");
+        assert_eq!(f32::MAX.literal(GLSL), "3.4028235e38");
+        assert_eq!(1.0_f64.literal(GLSL), "1.0lf");
+        assert_eq!(3_u32.literal(GLSL), "3u");
+        assert_eq!(i32::MIN.literal(GLSL), "(-2147483647 - 1)");
+        assert_eq!(i32::MIN.literal(LiteralTarget::Wgsl), "-2147483648");
+    }
+
+    #[test]
+    fn float_literals_round_trip() {
+        for value in [
+            0.1_f32,
+            1.0 / 3.0,
+            16_777_217.0,
+            f32::MIN_POSITIVE,
+            f32::EPSILON,
+        ] {
+            let literal = value.literal(GLSL);
+            assert_eq!(literal.parse::<f32>().unwrap().to_bits(), value.to_bits());
+        }
+        let literal = (1.0_f64 / 3.0).literal(GLSL);
+        let parsed: f64 = literal.trim_end_matches("lf").parse().unwrap();
+        assert_eq!(parsed.to_bits(), (1.0_f64 / 3.0).to_bits());
+    }
+
+    #[test]
+    fn non_finite_literals() {
+        assert_eq!(f32::INFINITY.literal(GLSL), "uintBitsToFloat(0x7F800000u)");
+        assert_eq!(
+            f32::NEG_INFINITY.literal(LiteralTarget::Glsl(GlslVersion::GlslEs100)),
+            "(-1.0 / 0.0)"
+        );
+        assert_eq!(
+            f32::NAN.literal(LiteralTarget::Wgsl),
+            "bitcast<f32>(0x7FC00000u)"
+        );
+        assert_eq!(
+            f64::NAN.literal(GLSL),
+            "double(uintBitsToFloat(0x7FC00000u))"
+        );
+    }
+
+    #[test]
+    fn vector_literals() {
+        assert_eq!(
+            vector_literal(NativeType::Vec2, &[1.0_f32, 2.5], GLSL).unwrap(),
+            "vec2(1.0, 2.5)"
+        );
+        assert_eq!(
+            vector_literal(NativeType::IVec3, &[1, -2, 3], LiteralTarget::Wgsl).unwrap(),
+            "vec3<i32>(1, -2, 3)"
+        );
+    }
+}
@@ -0,0 +1,18 @@
+use crate::{make_native_type_enum, NativeType};
+
+make_native_type_enum!(IntegerNativeType {
+    Int,
+    IVec2,
+    IVec3,
+    IVec4,
+    UInt,
+    UVec2,
+    UVec3,
+    UVec4,
+});
+
+impl Default for IntegerNativeType {
+    fn default() -> Self {
+        Self::Int
+    }
+}
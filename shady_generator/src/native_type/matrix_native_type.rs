@@ -0,0 +1,29 @@
+use crate::{make_native_type_enum, NativeType};
+
+make_native_type_enum!(MatrixNativeType { Mat2, Mat3, Mat4 });
+
+impl MatrixNativeType {
+    /// Number of columns and rows of the square matrix
+    pub const fn size(self) -> usize {
+        match self {
+            Self::Mat2 => 2,
+            Self::Mat3 => 3,
+            Self::Mat4 => 4,
+        }
+    }
+
+    /// Type of the matrix columns, also the type of the vectors it can be multiplied with
+    pub const fn column_type(self) -> NativeType {
+        match self {
+            Self::Mat2 => NativeType::Vec2,
+            Self::Mat3 => NativeType::Vec3,
+            Self::Mat4 => NativeType::Vec4,
+        }
+    }
+}
+
+impl Default for MatrixNativeType {
+    fn default() -> Self {
+        Self::Mat4
+    }
+}
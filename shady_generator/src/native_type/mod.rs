@@ -1,14 +1,22 @@
 mod floating_native_type;
+mod integer_native_type;
+mod matrix_native_type;
 mod non_scalar_native_type;
+mod sampler_native_type;
 mod scalar_native_type;
+mod vector_native_type;
 
-pub use {floating_native_type::*, non_scalar_native_type::*, scalar_native_type::*};
+pub use {
+    floating_native_type::*, integer_native_type::*, matrix_native_type::*,
+    non_scalar_native_type::*, sampler_native_type::*, scalar_native_type::*,
+    vector_native_type::*,
+};
 
 #[macro_export]
 macro_rules! make_native_type_enum {
     (
         $name:ident {
-            $( $variant:ident, )*
+            $( $variant:ident ),* $(,)?
         }
     ) => {
         #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -20,13 +28,18 @@ macro_rules! make_native_type_enum {
             pub const VARIANTS: &'static [$name] = &[
                 $( $name::$variant, )*
             ];
+
+            /// Matching native type, usable in const contexts unlike `From`
+            pub const fn native_type(self) -> NativeType {
+                match self {
+                     $( $name::$variant => NativeType::$variant, )*
+                }
+            }
         }
 
         impl From<$name> for NativeType {
             fn from(t: $name) -> Self {
-                match t {
-                     $( $name::$variant => Self::$variant, )*
-                }
+                t.native_type()
             }
         }
 
@@ -38,6 +51,7 @@ macro_rules! make_native_type_enum {
     }
 }
 
+use crate::{GlslVersion, GraphicLibrary, Literal, LiteralTarget, ShadyError};
 use serde::{Deserialize, Serialize};
 use std::fmt::{self, Display, Formatter};
 
@@ -66,10 +80,60 @@ pub enum NativeType {
     Vec4,
     /// 4D Integer Vector (x, y, z, w)
     IVec4,
-    /// 4D Integer Vector (x, y, z, w)
+    /// 2D Boolean vector (x, y)
+    BVec2,
+    /// 3D Boolean vector (x, y, z)
+    BVec3,
+    /// 4D Boolean vector (x, y, z, w)
+    BVec4,
+    /// 2D Unsigned Integer vector (x, y)
+    UVec2,
+    /// 3D Unsigned Integer vector (x, y, z)
+    UVec3,
+    /// 4D Unsigned Integer vector (x, y, z, w)
+    UVec4,
+    /// 2D Double vector (x, y)
+    DVec2,
+    /// 3D Double vector (x, y, z)
+    DVec3,
+    /// 4D Double vector (x, y, z, w)
+    DVec4,
+    /// 2x2 Float matrix, column major
+    Mat2,
+    /// 3x3 Float matrix, column major
+    Mat3,
+    /// 4x4 Float matrix, column major
+    Mat4,
+    /// 2D float texture sampler
     Sampler2d,
-    /// 4D Integer Vector (x, y, z, w)
+    /// Cube float texture sampler
     SamplerCube,
+    /// 3D float texture sampler
+    Sampler3d,
+    /// 2D float texture array sampler
+    Sampler2dArray,
+    /// 2D depth texture comparison sampler
+    Sampler2dShadow,
+    /// Cube depth texture comparison sampler
+    SamplerCubeShadow,
+    /// 2D depth texture array comparison sampler
+    Sampler2dArrayShadow,
+    /// 2D signed integer texture sampler
+    ISampler2d,
+    /// 3D signed integer texture sampler
+    ISampler3d,
+    /// Cube signed integer texture sampler
+    ISamplerCube,
+    /// 2D signed integer texture array sampler
+    ISampler2dArray,
+    /// 2D unsigned integer texture sampler
+    USampler2d,
+    /// 3D unsigned integer texture sampler
+    USampler3d,
+    /// Cube unsigned integer texture sampler
+    USamplerCube,
+    /// 2D unsigned integer texture array sampler
+    USampler2dArray,
 }
 
 impl Display for NativeType {
@@ -93,30 +157,234 @@ impl NativeType {
             Self::IVec3 => "ivec3",
             Self::Vec4 => "vec4",
             Self::IVec4 => "ivec4",
+            Self::BVec2 => "bvec2",
+            Self::BVec3 => "bvec3",
+            Self::BVec4 => "bvec4",
+            Self::UVec2 => "uvec2",
+            Self::UVec3 => "uvec3",
+            Self::UVec4 => "uvec4",
+            Self::DVec2 => "dvec2",
+            Self::DVec3 => "dvec3",
+            Self::DVec4 => "dvec4",
+            Self::Mat2 => "mat2",
+            Self::Mat3 => "mat3",
+            Self::Mat4 => "mat4",
             Self::Sampler2d => "sampler2D",
             Self::SamplerCube => "samplerCube",
+            Self::Sampler3d => "sampler3D",
+            Self::Sampler2dArray => "sampler2DArray",
+            Self::Sampler2dShadow => "sampler2DShadow",
+            Self::SamplerCubeShadow => "samplerCubeShadow",
+            Self::Sampler2dArrayShadow => "sampler2DArrayShadow",
+            Self::ISampler2d => "isampler2D",
+            Self::ISampler3d => "isampler3D",
+            Self::ISamplerCube => "isamplerCube",
+            Self::ISampler2dArray => "isampler2DArray",
+            Self::USampler2d => "usampler2D",
+            Self::USampler3d => "usampler3D",
+            Self::USamplerCube => "usamplerCube",
+            Self::USampler2dArray => "usampler2DArray",
         }
     }
 
-    /// Default GLSL value
-    pub fn default_glsl_value(&self) -> &'static str {
-        match self {
-            Self::Bool => "false",
-            Self::Int | Self::UInt => "0",
-            Self::Float | Self::Double => "0.0",
-            Self::Vec2 => "vec2(0.0, 0.0)",
-            Self::IVec2 => "ivec2(0, 0)",
-            Self::Vec3 => "vec3(0.0, 0.0, 0.0)",
-            Self::IVec3 => "ivec3(0, 0, 0)",
-            Self::Vec4 => "vec4(0.0, 0.0, 0.0, 0.0)",
-            Self::IVec4 => "ivec4(0, 0, 0, 0)",
+    /// Parses a GLSL type declaration, see `get_glsl_type`
+    pub fn from_glsl_type(glsl_type: &str) -> Option<Self> {
+        Self::VARIANTS
+            .iter()
+            .copied()
+            .find(|t| t.get_glsl_type() == glsl_type)
+    }
+
+    /// Returns the WGSL type declaration
+    pub fn get_wgsl_type(&self) -> Result<&'static str, ShadyError> {
+        Ok(match self {
+            Self::Bool => "bool",
+            Self::Int => "i32",
+            Self::UInt => "u32",
+            Self::Float => "f32",
+            Self::Vec2 => "vec2<f32>",
+            Self::IVec2 => "vec2<i32>",
+            Self::Vec3 => "vec3<f32>",
+            Self::IVec3 => "vec3<i32>",
+            Self::Vec4 => "vec4<f32>",
+            Self::IVec4 => "vec4<i32>",
+            Self::BVec2 => "vec2<bool>",
+            Self::BVec3 => "vec3<bool>",
+            Self::BVec4 => "vec4<bool>",
+            Self::UVec2 => "vec2<u32>",
+            Self::UVec3 => "vec3<u32>",
+            Self::UVec4 => "vec4<u32>",
+            Self::Mat2 => "mat2x2<f32>",
+            Self::Mat3 => "mat3x3<f32>",
+            Self::Mat4 => "mat4x4<f32>",
+            Self::Sampler2d => "texture_2d<f32>",
+            Self::SamplerCube => "texture_cube<f32>",
+            Self::Sampler3d => "texture_3d<f32>",
+            Self::Sampler2dArray => "texture_2d_array<f32>",
+            Self::Sampler2dShadow => "texture_depth_2d",
+            Self::SamplerCubeShadow => "texture_depth_cube",
+            Self::Sampler2dArrayShadow => "texture_depth_2d_array",
+            Self::ISampler2d => "texture_2d<i32>",
+            Self::ISampler3d => "texture_3d<i32>",
+            Self::ISamplerCube => "texture_cube<i32>",
+            Self::ISampler2dArray => "texture_2d_array<i32>",
+            Self::USampler2d => "texture_2d<u32>",
+            Self::USampler3d => "texture_3d<u32>",
+            Self::USamplerCube => "texture_cube<u32>",
+            Self::USampler2dArray => "texture_2d_array<u32>",
+            Self::Double | Self::DVec2 | Self::DVec3 | Self::DVec4 => {
+                return Err(ShadyError::UnsupportedNativeType {
+                    native_type: *self,
+                    target: GraphicLibrary::WebGPU.to_string(),
+                })
+            }
+        })
+    }
+
+    /// Default value literal for `target`: zero numbers, vectors and matrices, or `false`
+    pub fn default_literal(&self, target: LiteralTarget) -> String {
+        let (component, size) = match self {
+            Self::Bool => return false.literal(target),
+            Self::Int => return 0_i32.literal(target),
+            Self::UInt => return 0_u32.literal(target),
+            Self::Float => return 0_f32.literal(target),
+            Self::Double => return 0_f64.literal(target),
+            Self::Vec2 => (0_f32.literal(target), 2),
+            Self::IVec2 => (0_i32.literal(target), 2),
+            Self::Vec3 => (0_f32.literal(target), 3),
+            Self::IVec3 => (0_i32.literal(target), 3),
+            Self::Vec4 => (0_f32.literal(target), 4),
+            Self::IVec4 => (0_i32.literal(target), 4),
+            Self::BVec2 => (false.literal(target), 2),
+            Self::BVec3 => (false.literal(target), 3),
+            Self::BVec4 => (false.literal(target), 4),
+            Self::UVec2 => (0_u32.literal(target), 2),
+            Self::UVec3 => (0_u32.literal(target), 3),
+            Self::UVec4 => (0_u32.literal(target), 4),
+            Self::DVec2 => (0_f64.literal(target), 2),
+            Self::DVec3 => (0_f64.literal(target), 3),
+            Self::DVec4 => (0_f64.literal(target), 4),
+            Self::Mat2 | Self::Mat3 | Self::Mat4 => {
+                // A GLSL matrix built from a single scalar has it on its diagonal, WGSL has a
+                // zero value constructor
+                return match self.get_wgsl_type() {
+                    Ok(wgsl_type) if target == LiteralTarget::Wgsl => format!("{}()", wgsl_type),
+                    _ => format!("{}({})", self.get_glsl_type(), 0_f32.literal(target)),
+                };
+            }
+            // Samplers
             _ => {
                 log::warn!("There is no default value available for {}", self);
-                "__UNSET__"
+                return "__UNSET__".to_string();
+            }
+        };
+        match self.get_wgsl_type() {
+            // WGSL vector constructors splat a single component
+            Ok(wgsl_type) if target == LiteralTarget::Wgsl => {
+                format!("{}({})", wgsl_type, component)
+            }
+            _ => format!(
+                "{}({})",
+                self.get_glsl_type(),
+                vec![component; size].join(", ")
+            ),
+        }
+    }
+
+    /// Default GLSL value for the targeted `version`
+    pub fn default_glsl_value(&self, version: GlslVersion) -> String {
+        self.default_literal(LiteralTarget::Glsl(version))
+    }
+
+    /// Default WGSL value
+    pub fn default_wgsl_value(&self) -> String {
+        self.default_literal(LiteralTarget::Wgsl)
+    }
+
+    /// Is the type a handle to a texture (sampler)
+    pub const fn is_sampler(&self) -> bool {
+        self.sampler_type().is_some()
+    }
+
+    /// Sampler type of a texture handle, see `is_sampler`
+    pub const fn sampler_type(&self) -> Option<SamplerNativeType> {
+        Some(match self {
+            Self::Sampler2d => SamplerNativeType::Sampler2d,
+            Self::SamplerCube => SamplerNativeType::SamplerCube,
+            Self::Sampler3d => SamplerNativeType::Sampler3d,
+            Self::Sampler2dArray => SamplerNativeType::Sampler2dArray,
+            Self::Sampler2dShadow => SamplerNativeType::Sampler2dShadow,
+            Self::SamplerCubeShadow => SamplerNativeType::SamplerCubeShadow,
+            Self::Sampler2dArrayShadow => SamplerNativeType::Sampler2dArrayShadow,
+            Self::ISampler2d => SamplerNativeType::ISampler2d,
+            Self::ISampler3d => SamplerNativeType::ISampler3d,
+            Self::ISamplerCube => SamplerNativeType::ISamplerCube,
+            Self::ISampler2dArray => SamplerNativeType::ISampler2dArray,
+            Self::USampler2d => SamplerNativeType::USampler2d,
+            Self::USampler3d => SamplerNativeType::USampler3d,
+            Self::USamplerCube => SamplerNativeType::USamplerCube,
+            Self::USampler2dArray => SamplerNativeType::USampler2dArray,
+            _ => return None,
+        })
+    }
+
+    /// Is the type a non scalar vector type
+    pub const fn is_vector(&self) -> bool {
+        self.vector_size().is_some()
+    }
+
+    /// Number of components of a vector type
+    pub const fn vector_size(&self) -> Option<usize> {
+        match self {
+            Self::Vec2 | Self::IVec2 | Self::BVec2 | Self::UVec2 | Self::DVec2 => Some(2),
+            Self::Vec3 | Self::IVec3 | Self::BVec3 | Self::UVec3 | Self::DVec3 => Some(3),
+            Self::Vec4 | Self::IVec4 | Self::BVec4 | Self::UVec4 | Self::DVec4 => Some(4),
+            _ => None,
+        }
+    }
+
+    /// Scalar type of the components of a scalar or vector type
+    pub const fn component_type(&self) -> Option<ScalarNativeType> {
+        match self {
+            Self::Bool | Self::BVec2 | Self::BVec3 | Self::BVec4 => Some(ScalarNativeType::Bool),
+            Self::Int | Self::IVec2 | Self::IVec3 | Self::IVec4 => Some(ScalarNativeType::Int),
+            Self::UInt | Self::UVec2 | Self::UVec3 | Self::UVec4 => Some(ScalarNativeType::UInt),
+            Self::Float | Self::Vec2 | Self::Vec3 | Self::Vec4 => Some(ScalarNativeType::Float),
+            Self::Double | Self::DVec2 | Self::DVec3 | Self::DVec4 => {
+                Some(ScalarNativeType::Double)
             }
+            _ => None,
         }
     }
 
+    /// Is the type a square float matrix
+    pub const fn is_matrix(&self) -> bool {
+        matches!(self, Self::Mat2 | Self::Mat3 | Self::Mat4)
+    }
+
+    /// Matrix type of a square float matrix, see `is_matrix`
+    pub const fn matrix_type(&self) -> Option<MatrixNativeType> {
+        match self {
+            Self::Mat2 => Some(MatrixNativeType::Mat2),
+            Self::Mat3 => Some(MatrixNativeType::Mat3),
+            Self::Mat4 => Some(MatrixNativeType::Mat4),
+            _ => None,
+        }
+    }
+
+    /// Is the type made of integer components
+    pub const fn is_integer(&self) -> bool {
+        matches!(
+            self.component_type(),
+            Some(ScalarNativeType::Int | ScalarNativeType::UInt)
+        )
+    }
+
+    /// Is the type made of double precision components
+    pub const fn is_double(&self) -> bool {
+        matches!(self.component_type(), Some(ScalarNativeType::Double))
+    }
+
     pub const fn tolerated_input_types(&self) -> &'static [Self] {
         match self {
             Self::Bool => &[Self::Bool],
@@ -130,8 +398,33 @@ impl NativeType {
             Self::IVec3 => &[Self::Int, Self::IVec3],
             Self::Vec4 => &[Self::Float, Self::Vec4],
             Self::IVec4 => &[Self::Int, Self::IVec4],
+            Self::BVec2 => &[Self::Bool, Self::BVec2],
+            Self::BVec3 => &[Self::Bool, Self::BVec3],
+            Self::BVec4 => &[Self::Bool, Self::BVec4],
+            Self::UVec2 => &[Self::UInt, Self::UVec2],
+            Self::UVec3 => &[Self::UInt, Self::UVec3],
+            Self::UVec4 => &[Self::UInt, Self::UVec4],
+            Self::DVec2 => &[Self::Double, Self::DVec2],
+            Self::DVec3 => &[Self::Double, Self::DVec3],
+            Self::DVec4 => &[Self::Double, Self::DVec4],
+            Self::Mat2 => &[Self::Float, Self::Mat2],
+            Self::Mat3 => &[Self::Float, Self::Mat3],
+            Self::Mat4 => &[Self::Float, Self::Mat4],
             Self::Sampler2d => &[Self::Sampler2d],
             Self::SamplerCube => &[Self::SamplerCube],
+            Self::Sampler3d => &[Self::Sampler3d],
+            Self::Sampler2dArray => &[Self::Sampler2dArray],
+            Self::Sampler2dShadow => &[Self::Sampler2dShadow],
+            Self::SamplerCubeShadow => &[Self::SamplerCubeShadow],
+            Self::Sampler2dArrayShadow => &[Self::Sampler2dArrayShadow],
+            Self::ISampler2d => &[Self::ISampler2d],
+            Self::ISampler3d => &[Self::ISampler3d],
+            Self::ISamplerCube => &[Self::ISamplerCube],
+            Self::ISampler2dArray => &[Self::ISampler2dArray],
+            Self::USampler2d => &[Self::USampler2d],
+            Self::USampler3d => &[Self::USampler3d],
+            Self::USamplerCube => &[Self::USamplerCube],
+            Self::USampler2dArray => &[Self::USampler2dArray],
         }
     }
 
@@ -148,8 +441,33 @@ impl NativeType {
         Self::IVec3,
         Self::Vec4,
         Self::IVec4,
+        Self::BVec2,
+        Self::BVec3,
+        Self::BVec4,
+        Self::UVec2,
+        Self::UVec3,
+        Self::UVec4,
+        Self::DVec2,
+        Self::DVec3,
+        Self::DVec4,
+        Self::Mat2,
+        Self::Mat3,
+        Self::Mat4,
         Self::Sampler2d,
         Self::SamplerCube,
+        Self::Sampler3d,
+        Self::Sampler2dArray,
+        Self::Sampler2dShadow,
+        Self::SamplerCubeShadow,
+        Self::Sampler2dArrayShadow,
+        Self::ISampler2d,
+        Self::ISampler3d,
+        Self::ISamplerCube,
+        Self::ISampler2dArray,
+        Self::USampler2d,
+        Self::USampler3d,
+        Self::USamplerCube,
+        Self::USampler2dArray,
     ];
 }
 
@@ -7,44 +7,59 @@ make_native_type_enum!(NonScalarNativeType {
     IVec3,
     Vec4,
     IVec4,
+    BVec2,
+    BVec3,
+    BVec4,
+    UVec2,
+    UVec3,
+    UVec4,
+    DVec2,
+    DVec3,
+    DVec4,
+    Mat2,
+    Mat3,
+    Mat4,
 });
 
 impl NonScalarNativeType {
     pub(crate) fn type_construction_fields(self) -> Vec<(String, NativeType)> {
         match self {
-            Self::Vec2 => vec![
-                ("x".to_string(), ScalarNativeType::Float.into()),
-                ("y".to_string(), ScalarNativeType::Float.into()),
-            ],
-            Self::IVec2 => vec![
-                ("x".to_string(), ScalarNativeType::Int.into()),
-                ("y".to_string(), ScalarNativeType::Int.into()),
-            ],
-            Self::Vec3 => vec![
-                ("x".to_string(), ScalarNativeType::Float.into()),
-                ("y".to_string(), ScalarNativeType::Float.into()),
-                ("z".to_string(), ScalarNativeType::Float.into()),
-            ],
-            Self::IVec3 => vec![
-                ("x".to_string(), ScalarNativeType::Int.into()),
-                ("y".to_string(), ScalarNativeType::Int.into()),
-                ("z".to_string(), ScalarNativeType::Int.into()),
-            ],
-            Self::Vec4 => vec![
-                ("x".to_string(), ScalarNativeType::Float.into()),
-                ("y".to_string(), ScalarNativeType::Float.into()),
-                ("z".to_string(), ScalarNativeType::Float.into()),
-                ("w".to_string(), ScalarNativeType::Float.into()),
-            ],
-            Self::IVec4 => vec![
-                ("x".to_string(), ScalarNativeType::Int.into()),
-                ("y".to_string(), ScalarNativeType::Int.into()),
-                ("z".to_string(), ScalarNativeType::Int.into()),
-                ("w".to_string(), ScalarNativeType::Int.into()),
-            ],
+            Self::Vec2 => Self::components(ScalarNativeType::Float, 2),
+            Self::IVec2 => Self::components(ScalarNativeType::Int, 2),
+            Self::Vec3 => Self::components(ScalarNativeType::Float, 3),
+            Self::IVec3 => Self::components(ScalarNativeType::Int, 3),
+            Self::Vec4 => Self::components(ScalarNativeType::Float, 4),
+            Self::IVec4 => Self::components(ScalarNativeType::Int, 4),
+            Self::BVec2 => Self::components(ScalarNativeType::Bool, 2),
+            Self::BVec3 => Self::components(ScalarNativeType::Bool, 3),
+            Self::BVec4 => Self::components(ScalarNativeType::Bool, 4),
+            Self::UVec2 => Self::components(ScalarNativeType::UInt, 2),
+            Self::UVec3 => Self::components(ScalarNativeType::UInt, 3),
+            Self::UVec4 => Self::components(ScalarNativeType::UInt, 4),
+            Self::DVec2 => Self::components(ScalarNativeType::Double, 2),
+            Self::DVec3 => Self::components(ScalarNativeType::Double, 3),
+            Self::DVec4 => Self::components(ScalarNativeType::Double, 4),
+            // Matrix columns are named by index, see `Connection::glsl_call`
+            Self::Mat2 => Self::columns(Self::Vec2, 2),
+            Self::Mat3 => Self::columns(Self::Vec3, 3),
+            Self::Mat4 => Self::columns(Self::Vec4, 4),
         }
     }
 
+    fn components(component_type: ScalarNativeType, count: usize) -> Vec<(String, NativeType)> {
+        ["x", "y", "z", "w"]
+            .iter()
+            .take(count)
+            .map(|c| (c.to_string(), component_type.into()))
+            .collect()
+    }
+
+    fn columns(column_type: Self, count: usize) -> Vec<(String, NativeType)> {
+        (0..count)
+            .map(|i| (i.to_string(), column_type.into()))
+            .collect()
+    }
+
     pub(crate) fn type_construction_input(self) -> Input {
         Input {
             fields: self
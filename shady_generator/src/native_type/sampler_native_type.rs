@@ -0,0 +1,197 @@
+use crate::{make_native_type_enum, NativeType};
+
+make_native_type_enum!(SamplerNativeType {
+    Sampler2d,
+    SamplerCube,
+    Sampler3d,
+    Sampler2dArray,
+    Sampler2dShadow,
+    SamplerCubeShadow,
+    Sampler2dArrayShadow,
+    ISampler2d,
+    ISampler3d,
+    ISamplerCube,
+    ISampler2dArray,
+    USampler2d,
+    USampler3d,
+    USamplerCube,
+    USampler2dArray,
+});
+
+impl Default for SamplerNativeType {
+    fn default() -> Self {
+        Self::Sampler2d
+    }
+}
+
+impl SamplerNativeType {
+    /// Is the sampler a depth comparison sampler
+    pub const fn is_shadow(self) -> bool {
+        matches!(
+            self,
+            Self::Sampler2dShadow | Self::SamplerCubeShadow | Self::Sampler2dArrayShadow
+        )
+    }
+
+    /// Is the sampler an integer (`isampler*` or `usampler*`) sampler
+    pub const fn is_integer(self) -> bool {
+        matches!(self.texel_type(), NativeType::IVec4 | NativeType::UVec4)
+    }
+
+    /// Is the sampler a cube map sampler
+    pub const fn is_cube(self) -> bool {
+        matches!(
+            self,
+            Self::SamplerCube | Self::SamplerCubeShadow | Self::ISamplerCube | Self::USamplerCube
+        )
+    }
+
+    /// Is the sampler a texture array sampler
+    pub const fn is_array(self) -> bool {
+        matches!(
+            self,
+            Self::Sampler2dArray
+                | Self::Sampler2dArrayShadow
+                | Self::ISampler2dArray
+                | Self::USampler2dArray
+        )
+    }
+
+    /// Is the sampler a 3D texture sampler
+    pub const fn is_3d(self) -> bool {
+        matches!(self, Self::Sampler3d | Self::ISampler3d | Self::USampler3d)
+    }
+
+    /// Type of a sampled texel, shadow samplers return the comparison result
+    pub const fn texel_type(self) -> NativeType {
+        match self {
+            Self::Sampler2d | Self::SamplerCube | Self::Sampler3d | Self::Sampler2dArray => {
+                NativeType::Vec4
+            }
+            Self::Sampler2dShadow | Self::SamplerCubeShadow | Self::Sampler2dArrayShadow => {
+                NativeType::Float
+            }
+            Self::ISampler2d | Self::ISampler3d | Self::ISamplerCube | Self::ISampler2dArray => {
+                NativeType::IVec4
+            }
+            Self::USampler2d | Self::USampler3d | Self::USamplerCube | Self::USampler2dArray => {
+                NativeType::UVec4
+            }
+        }
+    }
+
+    /// Type of the sampling coordinates, the array layer and the shadow comparison reference
+    /// are packed as the last components
+    pub const fn coordinates_type(self) -> NativeType {
+        match self {
+            Self::Sampler2d | Self::ISampler2d | Self::USampler2d => NativeType::Vec2,
+            Self::SamplerCubeShadow | Self::Sampler2dArrayShadow => NativeType::Vec4,
+            _ => NativeType::Vec3,
+        }
+    }
+
+    /// Type of the sampling coordinates without the shadow comparison reference, as used
+    /// by `textureGather`
+    pub const fn unpacked_coordinates_type(self) -> NativeType {
+        match self {
+            Self::Sampler2dShadow => NativeType::Vec2,
+            Self::SamplerCubeShadow | Self::Sampler2dArrayShadow => NativeType::Vec3,
+            _ => self.coordinates_type(),
+        }
+    }
+
+    /// Type of the coordinates partial derivatives, as used by `textureGrad`
+    pub const fn gradient_type(self) -> NativeType {
+        if self.is_3d() || self.is_cube() {
+            NativeType::Vec3
+        } else {
+            NativeType::Vec2
+        }
+    }
+
+    /// Type of the texel offset, cube maps can't be offset
+    pub const fn offset_type(self) -> Option<NativeType> {
+        if self.is_cube() {
+            None
+        } else if self.is_3d() {
+            Some(NativeType::IVec3)
+        } else {
+            Some(NativeType::IVec2)
+        }
+    }
+
+    /// Type of the integer texel coordinates, as used by `texelFetch`
+    pub const fn fetch_coordinates_type(self) -> Option<NativeType> {
+        if self.is_cube() || self.is_shadow() {
+            None
+        } else if self.is_3d() || self.is_array() {
+            Some(NativeType::IVec3)
+        } else {
+            Some(NativeType::IVec2)
+        }
+    }
+
+    /// Type of the homogeneous coordinates of projective lookups, only 2D and 3D textures
+    /// support them
+    pub const fn projective_coordinates_type(self) -> Option<NativeType> {
+        match self {
+            Self::Sampler2d | Self::ISampler2d | Self::USampler2d => Some(NativeType::Vec3),
+            Self::Sampler3d | Self::ISampler3d | Self::USampler3d | Self::Sampler2dShadow => {
+                Some(NativeType::Vec4)
+            }
+            _ => None,
+        }
+    }
+
+    /// Type of the texture dimensions, as returned by `textureSize`
+    pub const fn size_type(self) -> NativeType {
+        if self.is_3d() || self.is_array() {
+            NativeType::IVec3
+        } else {
+            NativeType::IVec2
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_sampler_is_a_native_sampler() {
+        for sampler in SamplerNativeType::VARIANTS {
+            let native_type = NativeType::from(*sampler);
+            assert!(native_type.is_sampler());
+            assert_eq!(native_type.sampler_type(), Some(*sampler));
+        }
+    }
+
+    #[test]
+    fn types_texture_lookups() {
+        assert_eq!(
+            SamplerNativeType::ISampler2d.texel_type(),
+            NativeType::IVec4
+        );
+        assert_eq!(
+            SamplerNativeType::Sampler2dArrayShadow.coordinates_type(),
+            NativeType::Vec4
+        );
+        assert_eq!(
+            SamplerNativeType::Sampler2dArrayShadow.unpacked_coordinates_type(),
+            NativeType::Vec3
+        );
+        assert_eq!(
+            SamplerNativeType::SamplerCube.size_type(),
+            NativeType::IVec2
+        );
+        assert_eq!(
+            SamplerNativeType::USampler2dArray.size_type(),
+            NativeType::IVec3
+        );
+        assert_eq!(SamplerNativeType::SamplerCube.offset_type(), None);
+        assert_eq!(
+            SamplerNativeType::Sampler2dShadow.fetch_coordinates_type(),
+            None
+        );
+    }
+}
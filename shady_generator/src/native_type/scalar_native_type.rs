@@ -15,6 +15,30 @@ make_native_type_enum!(NumericScalarNativeType {
     Double,
 });
 
+impl ScalarNativeType {
+    /// Vector type of `size` components of this type, if `size` is 2, 3 or 4
+    pub const fn vector_type(self, size: usize) -> Option<NativeType> {
+        Some(match (self, size) {
+            (Self::Bool, 2) => NativeType::BVec2,
+            (Self::Bool, 3) => NativeType::BVec3,
+            (Self::Bool, 4) => NativeType::BVec4,
+            (Self::Int, 2) => NativeType::IVec2,
+            (Self::Int, 3) => NativeType::IVec3,
+            (Self::Int, 4) => NativeType::IVec4,
+            (Self::UInt, 2) => NativeType::UVec2,
+            (Self::UInt, 3) => NativeType::UVec3,
+            (Self::UInt, 4) => NativeType::UVec4,
+            (Self::Float, 2) => NativeType::Vec2,
+            (Self::Float, 3) => NativeType::Vec3,
+            (Self::Float, 4) => NativeType::Vec4,
+            (Self::Double, 2) => NativeType::DVec2,
+            (Self::Double, 3) => NativeType::DVec3,
+            (Self::Double, 4) => NativeType::DVec4,
+            _ => return None,
+        })
+    }
+}
+
 impl Default for ScalarNativeType {
     fn default() -> Self {
         Self::Float
@@ -0,0 +1,98 @@
+use crate::{make_native_type_enum, NativeType, ScalarNativeType};
+
+make_native_type_enum!(VectorNativeType {
+    Vec2,
+    Vec3,
+    Vec4,
+    IVec2,
+    IVec3,
+    IVec4,
+    UVec2,
+    UVec3,
+    UVec4,
+    DVec2,
+    DVec3,
+    DVec4,
+    BVec2,
+    BVec3,
+    BVec4,
+});
+
+make_native_type_enum!(NumericVectorNativeType {
+    Vec2,
+    Vec3,
+    Vec4,
+    IVec2,
+    IVec3,
+    IVec4,
+    UVec2,
+    UVec3,
+    UVec4,
+    DVec2,
+    DVec3,
+    DVec4,
+});
+
+make_native_type_enum!(BooleanVectorNativeType {
+    BVec2,
+    BVec3,
+    BVec4,
+});
+
+/// Boolean vector of `size` components, the result of component wise comparisons
+const fn boolean_vector(size: usize) -> NativeType {
+    match ScalarNativeType::Bool.vector_type(size) {
+        Some(native_type) => native_type,
+        None => NativeType::Bool,
+    }
+}
+
+impl VectorNativeType {
+    /// Number of components of the vector
+    pub const fn size(self) -> usize {
+        match self {
+            Self::Vec2 | Self::IVec2 | Self::UVec2 | Self::DVec2 | Self::BVec2 => 2,
+            Self::Vec3 | Self::IVec3 | Self::UVec3 | Self::DVec3 | Self::BVec3 => 3,
+            Self::Vec4 | Self::IVec4 | Self::UVec4 | Self::DVec4 | Self::BVec4 => 4,
+        }
+    }
+
+    /// Boolean vector of the same size
+    pub const fn boolean_vector(self) -> NativeType {
+        boolean_vector(self.size())
+    }
+}
+
+impl NumericVectorNativeType {
+    /// Number of components of the vector
+    pub const fn size(self) -> usize {
+        match self {
+            Self::Vec2 | Self::IVec2 | Self::UVec2 | Self::DVec2 => 2,
+            Self::Vec3 | Self::IVec3 | Self::UVec3 | Self::DVec3 => 3,
+            Self::Vec4 | Self::IVec4 | Self::UVec4 | Self::DVec4 => 4,
+        }
+    }
+
+    /// Boolean vector of the same size
+    pub const fn boolean_vector(self) -> NativeType {
+        boolean_vector(self.size())
+    }
+}
+
+impl Default for VectorNativeType {
+    fn default() -> Self {
+        Self::Vec4
+    }
+}
+
+impl Default for NumericVectorNativeType {
+    fn default() -> Self {
+        Self::Vec4
+    }
+}
+
+impl Default for BooleanVectorNativeType {
+    fn default() -> Self {
+        Self::BVec4
+    }
+}
@@ -1,8 +1,11 @@
 use crate::error::ShadyError;
-use crate::node_operation::{InternalNodeOperation, NodeOperation};
+use crate::node_operation::{
+    InternalNodeOperation, NativeFunction, NativeOperation, NodeOperation,
+};
+use crate::shader::glsl_precision_qualifier;
 use crate::{
-    generate_unique_id, Connection, ConnectionMessage, ConnectionResponse, Input, InputField,
-    NativeType, Output, OutputFields,
+    Connection, ConnectionMessage, ConnectionResponse, GlslVersion, Input, InputField, NativeType,
+    Output, OutputFields, ShaderPrecision, ShaderType,
 };
 use serde::{Deserialize, Serialize};
 
@@ -15,22 +18,14 @@ pub struct Node {
     input: Input,
     output: Output,
     operation: InternalNodeOperation,
+    /// Precision qualifier of the node variable, only applied to OpenGL ES targets
+    #[serde(default)]
+    precision: Option<ShaderPrecision>,
 }
 
 impl Node {
-    /// Instantiates a shader node with the given `name` and `operation`
-    pub fn new(name: &str, operation: NodeOperation) -> Self {
-        Self {
-            name: name.to_string(),
-            id: generate_unique_id(),
-            input: operation.input(),
-            output: operation.output(),
-            operation: operation.into(),
-        }
-    }
-
     /// Instantiates a shader node with the given `name` and `operation` and with a custom unique id
-    /// as `custom_id`
+    /// as `custom_id`. Use `Shader::new_node` to get an id from the shader `IdGenerator`
     pub fn new_with_custom_id(name: &str, custom_id: &str, operation: NodeOperation) -> Self {
         Self {
             name: name.to_string(),
@@ -38,6 +33,7 @@ impl Node {
             input: operation.input(),
             output: operation.output(),
             operation: operation.into(),
+            precision: None,
         }
     }
 
@@ -51,6 +47,57 @@ impl Node {
         &self.id
     }
 
+    /// Retrieves the node output
+    pub const fn output(&self) -> &Output {
+        &self.output
+    }
+
+    /// Name of the custom function called by the node, if any
+    pub const fn custom_function_name(&self) -> Option<&String> {
+        if let InternalNodeOperation::CustomOperation(name) = &self.operation {
+            Some(name)
+        } else {
+            None
+        }
+    }
+
+    /// Native function called by the node, if any
+    pub const fn native_function(&self) -> Option<&NativeFunction> {
+        if let InternalNodeOperation::NativeFunction(function) = &self.operation {
+            Some(function)
+        } else {
+            None
+        }
+    }
+
+    /// Native operation computed by the node, if any
+    pub const fn native_operation(&self) -> Option<&NativeOperation> {
+        if let InternalNodeOperation::NativeOperation(operation) = &self.operation {
+            Some(operation)
+        } else {
+            None
+        }
+    }
+
+    /// Name of the sub-graph instantiated by the node, if any
+    pub const fn sub_graph_name(&self) -> Option<&String> {
+        if let InternalNodeOperation::SubGraph(name) = &self.operation {
+            Some(name)
+        } else {
+            None
+        }
+    }
+
+    /// Retrieves the node precision qualifier
+    pub const fn precision(&self) -> Option<ShaderPrecision> {
+        self.precision
+    }
+
+    /// Sets the node precision qualifier, only applied to OpenGL ES targets
+    pub const fn set_precision(&mut self, precision: Option<ShaderPrecision>) {
+        self.precision = precision;
+    }
+
     /// Retrieves the name and unique id of the Node formatted together
     pub fn unique_name(&self) -> String {
         format!("{}_{}", self.name, self.id)
@@ -77,7 +124,15 @@ impl Node {
         Ok(field_pos)
     }
 
-    fn input_field_glsl_values(&self) -> Vec<String> {
+    fn input_field_glsl_values(&self, version: GlslVersion) -> Vec<String> {
+        self.input_field_values(|t| t.default_glsl_value(version))
+    }
+
+    fn input_field_wgsl_values(&self) -> Vec<String> {
+        self.input_field_values(NativeType::default_wgsl_value)
+    }
+
+    fn input_field_values(&self, default_value: impl Fn(&NativeType) -> String) -> Vec<String> {
         let mut fields = Vec::new();
         for (key, field) in self.input_fields() {
             let val = match &field.connection {
@@ -88,7 +143,7 @@ impl Node {
                         self.unique_name(),
                         key
                     );
-                    field.glsl_type.default_glsl_value().to_string()
+                    default_value(&field.glsl_type)
                 }
             };
             fields.push(val);
@@ -111,6 +166,22 @@ impl Node {
         Ok(*f)
     }
 
+    /// Retrieves every native type used by the node input and output
+    pub fn native_types(&self) -> Vec<NativeType> {
+        let mut res: Vec<NativeType> = self.input.fields.iter().map(|(_, f)| f.glsl_type).collect();
+        if let Output::Split(t) = &self.output {
+            res.push((*t).into());
+        }
+        res.extend(
+            self.output
+                .fields()
+                .field_names()
+                .into_iter()
+                .map(|(_, t)| t),
+        );
+        res
+    }
+
     /// Retrieves all input fields
     pub fn input_fields(&self) -> Vec<(String, InputField)> {
         self.input.fields.clone()
@@ -123,16 +194,21 @@ impl Node {
 
     /// Retrieves all the connections to other shader nodes
     pub fn node_connections(&self) -> Vec<String> {
+        self.node_dependencies().cloned().collect()
+    }
+
+    /// Iterates the ids of the nodes connected to the input fields, without allocating. The ids
+    /// are resolved to handles by the shader, see `SlotMap::handle`
+    pub fn node_dependencies(&self) -> impl Iterator<Item = &String> {
         self.input
             .fields
             .iter()
             .filter_map(|(_, f)| match f.connection.as_ref()? {
                 Connection::InputProperty { .. } | Connection::Constant { .. } => None,
                 Connection::ComplexOutputNode { id, .. } | Connection::SingleOutputNode { id } => {
-                    Some(id.clone())
+                    Some(id)
                 }
             })
-            .collect()
     }
 
     /// Retrieves all the connections to other shader nodes
@@ -149,9 +225,9 @@ impl Node {
         self.output.custom_declaration()
     }
 
-    /// Retrieves the optional function declaration for the shader code
-    pub fn function_declaration(&self) -> Result<Option<String>, ShadyError> {
-        self.operation.function_declaration()
+    /// Retrieves the optional WGSL `struct` declaration for the shader code
+    pub fn wgsl_struct_declaration(&self) -> Result<Option<String>, ShadyError> {
+        self.output.wgsl_custom_declaration()
     }
 
     /// Connects an output field (from a node or a property) to an input field of this Node.
@@ -174,11 +250,7 @@ impl Node {
             .fields
             .get_mut(field_pos)
             .ok_or_else(|| ShadyError::WrongFieldKey(target_field.to_string()))?;
-        let expected_types = if field.tolerant {
-            field.glsl_type.tolerated_input_types().to_vec()
-        } else {
-            vec![field.glsl_type()]
-        };
+        let expected_types = field.accepted_types();
         if !expected_types.contains(&connect_message.native_type) {
             return Err(ShadyError::WrongNativeType {
                 input_type: connect_message.native_type,
@@ -201,28 +273,172 @@ impl Node {
         Ok(field.connection.take())
     }
 
-    /// Produces the associated shader code
-    pub fn to_glsl(&self) -> String {
+    /// Removes every input field connection matching `predicate`.
+    ///
+    /// The removed connections are returned with their field name
+    pub fn disconnect_matching(
+        &mut self,
+        predicate: impl Fn(&Connection) -> bool,
+    ) -> Vec<(String, Connection)> {
+        self.input
+            .fields
+            .iter_mut()
+            .filter(|(_, f)| f.connection.as_ref().is_some_and(&predicate))
+            .filter_map(|(key, f)| f.connection.take().map(|c| (key.clone(), c)))
+            .collect()
+    }
+
+    /// Recomputes the input fields and output from the node operation, keeping the connections
+    /// of the matching fields.
+    ///
+    /// The connections of fields unknown to the operation are returned with their field name
+    pub fn reconcile_operation(&mut self) -> Vec<(String, Connection)> {
+        let operation = self.operation.node_operation(&self.input, &self.output);
+        let mut input = operation.input();
+        let mut removed = Vec::new();
+        for (key, field) in std::mem::take(&mut self.input.fields) {
+            let Some(connection) = field.connection else {
+                continue;
+            };
+            match input.fields.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, f)) => f.connection = Some(connection),
+                None => removed.push((key, connection)),
+            }
+        }
+        self.input = input;
+        self.output = operation.output();
+        removed
+    }
+
+    /// Copy of the node with the unique `id`, and every connection mapped through `rename`
+    pub(crate) fn renamed(&self, id: String, rename: impl Fn(&Connection) -> Connection) -> Self {
+        let mut node = self.clone();
+        node.id = id;
+        for (_, field) in &mut node.input.fields {
+            field.connection = field.connection.as_ref().map(&rename);
+        }
+        node
+    }
+
+    /// Produces the associated shader code for the targeted GLSL `version`
+    pub fn to_glsl(&self, version: GlslVersion, shader_type: ShaderType) -> String {
+        let mut field_values = self.input_field_glsl_values(version);
+        // The output structure is declared first to receive the `out` parameter
+        if let Some((result, out)) = self
+            .native_function()
+            .and_then(NativeFunction::glsl_out_parameter)
+        {
+            let out_type = self
+                .output
+                .custom_type()
+                .and_then(|c| c.fields.iter().find(|(name, _)| name == out))
+                .map(|(_, native_type)| *native_type)
+                .unwrap_or_default();
+            let precision =
+                glsl_precision_qualifier(self.precision, out_type, version, shader_type);
+            if precision.is_empty() {
+                field_values.push(format!("{}.{}", self.id, out));
+                return format!(
+                    "{0} {1}; {1}.{2} = {3}; // {4} Node",
+                    self.output.glsl_type(),
+                    self.id,
+                    result,
+                    self.operation.to_glsl(&field_values, version),
+                    self.name
+                );
+            }
+            // Structure members can't be qualified, the `out` parameter goes through a
+            // qualified variable
+            let out_variable = format!("{}_{}", self.id, out);
+            field_values.push(out_variable.clone());
+            return format!(
+                "{0} {1}; {{ {2}{3} {4}; {1}.{5} = {6}; {1}.{7} = {4}; }} // {8} Node",
+                self.output.glsl_type(),
+                self.id,
+                precision,
+                out_type.get_glsl_type(),
+                out_variable,
+                result,
+                self.operation.to_glsl(&field_values, version),
+                out,
+                self.name
+            );
+        }
+        let precision = match self.output {
+            Output::NativeType(native_type) => {
+                glsl_precision_qualifier(self.precision, native_type, version, shader_type)
+            }
+            Output::CustomType(_) | Output::Split(_) => {
+                if self.precision.is_some() {
+                    log::warn!(
+                        "Node {} has a structured output and can't have a precision qualifier",
+                        self.id
+                    );
+                }
+                String::new()
+            }
+        };
         format!(
-            "{} {} = {}; // {} Node",
+            "{}{} {} = {}; // {} Node",
+            precision,
             self.output.glsl_type(),
             self.id,
-            self.operation.to_glsl(&self.input_field_glsl_values()),
+            self.operation.to_glsl(&field_values, version),
             self.name
         )
     }
+
+    /// Produces the associated WGSL shader code for the targeted `shader_type`
+    pub fn to_wgsl(&self, shader_type: ShaderType) -> Result<String, ShadyError> {
+        let constant_fields = self
+            .native_function()
+            .map_or(&[][..], NativeFunction::constant_input_fields);
+        for (key, field) in &self.input.fields {
+            let constant = matches!(field.connection, None | Some(Connection::Constant { .. }));
+            if !constant && constant_fields.contains(&key.as_str()) {
+                return Err(ShadyError::NonConstantInput {
+                    node: self.id.clone(),
+                    field: key.clone(),
+                });
+            }
+        }
+        // The built-in result structure can't be named, it is bound once and its fields copied
+        if let Some((first, second)) = self
+            .native_function()
+            .and_then(NativeFunction::glsl_out_parameter)
+        {
+            return Ok(format!(
+                "var {0}: {1}; {{ let result = {2}; {0} = {1}(result.{3}, result.{4}); }} // {5} Node",
+                self.id,
+                self.output.wgsl_type()?,
+                self.operation.to_wgsl(&self.input_field_wgsl_values(), shader_type)?,
+                first,
+                second,
+                self.name
+            ));
+        }
+        Ok(format!(
+            "let {}: {} = {}; // {} Node",
+            self.id,
+            self.output.wgsl_type()?,
+            self.operation
+                .to_wgsl(&self.input_field_wgsl_values(), shader_type)?,
+            self.name
+        ))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::node_operation::NativeOperation;
-    use crate::{NonScalarNativeType, ScalarNativeType};
+    use crate::node_operation::{NativeFunction, NativeOperation};
+    use crate::{FloatingNativeType, NonScalarNativeType, ScalarNativeType};
 
     #[test]
     fn custom_vec2_node() {
-        let mut node = Node::new(
+        let mut node = Node::new_with_custom_id(
             "test",
+            "v0",
             NodeOperation::TypeConstruction(NonScalarNativeType::Vec2),
         );
         node.connect_input(
@@ -247,7 +463,7 @@ mod tests {
             },
         )
         .unwrap();
-        let res = node.to_glsl();
+        let res = node.to_glsl(GlslVersion::default(), ShaderType::default());
         assert_eq!(
             res,
             format!(
@@ -259,11 +475,12 @@ mod tests {
 
     #[test]
     fn default_vec2_node() {
-        let node = Node::new(
+        let node = Node::new_with_custom_id(
             "test",
+            "v0",
             NodeOperation::TypeConstruction(NonScalarNativeType::Vec2),
         );
-        let res = node.to_glsl();
+        let res = node.to_glsl(GlslVersion::default(), ShaderType::default());
         assert_eq!(
             res,
             format!("vec2 {} = vec2(0.0, 0.0); // test Node", node.id)
@@ -272,11 +489,12 @@ mod tests {
 
     #[test]
     fn default_vec3_node() {
-        let node = Node::new(
+        let node = Node::new_with_custom_id(
             "test",
+            "v0",
             NodeOperation::TypeConstruction(NonScalarNativeType::Vec3),
         );
-        let res = node.to_glsl();
+        let res = node.to_glsl(GlslVersion::default(), ShaderType::default());
         assert_eq!(
             res,
             format!("vec3 {} = vec3(0.0, 0.0, 0.0); // test Node", node.id)
@@ -285,11 +503,12 @@ mod tests {
 
     #[test]
     fn default_vec4_node() {
-        let node = Node::new(
+        let node = Node::new_with_custom_id(
             "test",
+            "v0",
             NodeOperation::TypeConstruction(NonScalarNativeType::Vec4),
         );
-        let res = node.to_glsl();
+        let res = node.to_glsl(GlslVersion::default(), ShaderType::default());
         assert_eq!(
             res,
             format!("vec4 {} = vec4(0.0, 0.0, 0.0, 0.0); // test Node", node.id)
@@ -298,14 +517,123 @@ mod tests {
 
     #[test]
     fn default_float_selection_node() {
-        let node = Node::new(
+        let node = Node::new_with_custom_id(
             "test",
+            "v0",
             NodeOperation::NativeOperation(NativeOperation::Selection(NativeType::Float)),
         );
-        let res = node.to_glsl();
+        let res = node.to_glsl(GlslVersion::default(), ShaderType::default());
         assert_eq!(
             res,
             format!("float {} = false ? 0.0 : 0.0; // test Node", node.id)
         );
     }
+
+    #[test]
+    fn texture_node_matches_version() {
+        let node = Node::new_with_custom_id(
+            "test",
+            "v0",
+            NodeOperation::NativeFunction(NativeFunction::Texture2d),
+        );
+        assert_eq!(
+            node.to_glsl(GlslVersion::GlslEs100, ShaderType::default()),
+            format!(
+                "vec4 {} = texture2D(__UNSET__, vec2(0.0, 0.0)); // test Node",
+                node.id
+            )
+        );
+        assert_eq!(
+            node.to_glsl(GlslVersion::Glsl330, ShaderType::default()),
+            format!(
+                "vec4 {} = texture(__UNSET__, vec2(0.0, 0.0)); // test Node",
+                node.id
+            )
+        );
+    }
+
+    #[test]
+    fn precision_node_matches_version() {
+        let mut node = Node::new_with_custom_id(
+            "test",
+            "v0",
+            NodeOperation::TypeConstruction(NonScalarNativeType::Vec2),
+        );
+        node.set_precision(Some(ShaderPrecision::High));
+        assert_eq!(
+            node.to_glsl(GlslVersion::GlslEs300, ShaderType::Vertex),
+            format!("highp vec2 {} = vec2(0.0, 0.0); // test Node", node.id)
+        );
+        assert_eq!(
+            node.to_glsl(GlslVersion::GlslEs300, ShaderType::Fragment),
+            format!(
+                "SHADY_HIGHP vec2 {} = vec2(0.0, 0.0); // test Node",
+                node.id
+            )
+        );
+        assert_eq!(
+            node.to_glsl(GlslVersion::Glsl330, ShaderType::Fragment),
+            format!("vec2 {} = vec2(0.0, 0.0); // test Node", node.id)
+        );
+    }
+
+    #[test]
+    fn precision_out_parameter_node() {
+        let mut node = Node::new_with_custom_id(
+            "test",
+            "m",
+            NodeOperation::NativeFunction(NativeFunction::SignificandExponent(
+                FloatingNativeType::Vec2,
+            )),
+        );
+        assert_eq!(
+            node.to_glsl(GlslVersion::GlslEs300, ShaderType::Vertex),
+            "frexp_result_vec2 m; m.fract = frexp(vec2(0.0, 0.0), m.exp); // test Node"
+        );
+        node.set_precision(Some(ShaderPrecision::High));
+        assert_eq!(
+            node.to_glsl(GlslVersion::GlslEs300, ShaderType::Vertex),
+            "frexp_result_vec2 m; { highp ivec2 m_exp; m.fract = frexp(vec2(0.0, 0.0), m_exp); \
+             m.exp = m_exp; } // test Node"
+        );
+        assert_eq!(
+            node.to_wgsl(ShaderType::Fragment).unwrap(),
+            "var m: frexp_result_vec2; { let result = frexp(vec2<f32>(0.0)); \
+             m = frexp_result_vec2(result.fract, result.exp); } // test Node"
+        );
+    }
+
+    #[test]
+    fn default_vec3_wgsl_node() {
+        let node = Node::new_with_custom_id(
+            "test",
+            "v0",
+            NodeOperation::TypeConstruction(NonScalarNativeType::Vec3),
+        );
+        let res = node.to_wgsl(ShaderType::Fragment).unwrap();
+        assert_eq!(
+            res,
+            format!(
+                "let {}: vec3<f32> = vec3<f32>(0.0, 0.0, 0.0); // test Node",
+                node.id
+            )
+        );
+    }
+
+    #[test]
+    fn default_float_selection_wgsl_node() {
+        let node = Node::new_with_custom_id(
+            "test",
+            "v0",
+            NodeOperation::NativeOperation(NativeOperation::Selection(NativeType::Float)),
+        );
+        let res = node.to_wgsl(ShaderType::Fragment).unwrap();
+        assert_eq!(
+            res,
+            format!(
+                "let {}: f32 = select(0.0, 0.0, false); // test Node",
+                node.id
+            )
+        );
+    }
 }
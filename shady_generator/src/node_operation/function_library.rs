@@ -0,0 +1,379 @@
+use super::function_signature::tokenize;
+use super::FunctionSignature;
+use crate::{check_identifier, GraphicLibrary, ShadyError};
+use std::collections::{HashMap, HashSet};
+use std::fs::read_to_string;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Custom functions embedded in the crate: name, GLSL code and WGSL code
+const BUILTIN_FUNCTIONS: &[(&str, &str, &str)] = &[
+    (
+        "grayscale",
+        include_str!("../../functions/grayscale.glsl"),
+        include_str!("../../functions/grayscale.wgsl"),
+    ),
+    (
+        "luminance",
+        include_str!("../../functions/luminance.glsl"),
+        include_str!("../../functions/luminance.wgsl"),
+    ),
+    (
+        "remap",
+        include_str!("../../functions/remap.glsl"),
+        include_str!("../../functions/remap.wgsl"),
+    ),
+];
+
+/// Function code by function name and code extension
+type FunctionSources<T> = HashMap<(String, &'static str), T>;
+
+/// Code of a function with the names of the library functions it calls, in reverse call order
+type FunctionCalls = (String, Arc<str>, Vec<String>);
+
+/// Custom functions available to the shader generation.
+///
+/// A function is looked up for a graphic library in the in-memory sources, then as a
+/// `<function_name>.<code extension>` file in every search path in insertion order, then in the
+/// built-in functions. The search path lookups are cached, see `FunctionLibrary::clear_cache`
+#[derive(Debug, Default)]
+pub struct FunctionLibrary {
+    sources: FunctionSources<Arc<str>>,
+    search_paths: Vec<PathBuf>,
+    /// Search path lookups, `None` for functions missing from every search path
+    cache: Mutex<FunctionSources<Option<Arc<str>>>>,
+}
+
+/// Identifiers of `code` followed by an opening parenthesis, in order of appearance
+fn called_functions(code: &str) -> Vec<&str> {
+    tokenize(code)
+        .windows(2)
+        .filter(|tokens| tokens[1] == "(" && check_identifier(tokens[0]).is_ok())
+        .map(|tokens| tokens[0])
+        .collect()
+}
+
+impl FunctionLibrary {
+    /// Library with only the built-in functions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Names of the built-in functions, sorted
+    pub fn builtin_functions() -> impl Iterator<Item = &'static str> {
+        BUILTIN_FUNCTIONS.iter().map(|(name, _, _)| *name)
+    }
+
+    /// Adds a directory of function files, looked up after the previously added ones
+    pub fn add_search_path(&mut self, path: impl Into<PathBuf>) {
+        self.search_paths.push(path.into());
+        self.clear_cache();
+    }
+
+    /// Adds a directory of function files, see `FunctionLibrary::add_search_path`
+    #[must_use]
+    pub fn with_search_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.add_search_path(path);
+        self
+    }
+
+    /// Adds the in-memory `code` of the function `function_name` written for `library`, replacing
+    /// any previous code. In-memory functions are looked up before the files
+    pub fn add_source(
+        &mut self,
+        function_name: impl Into<String>,
+        library: GraphicLibrary,
+        code: impl Into<String>,
+    ) {
+        let code: String = code.into();
+        self.sources.insert(
+            (function_name.into(), library.code_extension()),
+            code.into(),
+        );
+    }
+
+    /// Adds the in-memory code of a function, see `FunctionLibrary::add_source`
+    #[must_use]
+    pub fn with_source(
+        mut self,
+        function_name: impl Into<String>,
+        library: GraphicLibrary,
+        code: impl Into<String>,
+    ) -> Self {
+        self.add_source(function_name, library, code);
+        self
+    }
+
+    pub fn search_paths(&self) -> &[PathBuf] {
+        &self.search_paths
+    }
+
+    /// Forgets the loaded and missing function files, to take file changes into account
+    pub fn clear_cache(&self) {
+        self.cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    /// Reads the first `<function_name>.<extension>` file of the search paths
+    fn read_function_file(
+        &self,
+        function_name: &str,
+        extension: &str,
+    ) -> Result<Option<Arc<str>>, ShadyError> {
+        // Function names are identifiers, anything else isn't looked up as a path
+        if check_identifier(function_name).is_err() {
+            return Ok(None);
+        }
+        for directory in &self.search_paths {
+            let path = directory.join(format!("{}.{}", function_name, extension));
+            match read_to_string(&path) {
+                Ok(code) => {
+                    log::info!("Loading function from {} file", path.display());
+                    return Ok(Some(code.into()));
+                }
+                Err(e) if e.kind() == ErrorKind::NotFound => (),
+                Err(e) => return Err(ShadyError::IOError(e)),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Code of the custom function `function_name` written for `library`
+    pub fn source(
+        &self,
+        function_name: &str,
+        library: GraphicLibrary,
+    ) -> Result<Arc<str>, ShadyError> {
+        let extension = library.code_extension();
+        let key = (function_name.to_string(), extension);
+        if let Some(code) = self.sources.get(&key) {
+            return Ok(Arc::clone(code));
+        }
+        let code = {
+            let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+            if let Some(code) = cache.get(&key) {
+                code.clone()
+            } else {
+                let code = self.read_function_file(function_name, extension)?;
+                cache.insert(key, code.clone());
+                code
+            }
+        };
+        code.or_else(|| {
+            BUILTIN_FUNCTIONS
+                .iter()
+                .find(|(name, _, _)| *name == function_name)
+                .map(|(_, glsl, wgsl)| match library {
+                    GraphicLibrary::OpenGl | GraphicLibrary::OpenGlEs => (*glsl).into(),
+                    GraphicLibrary::WebGPU => (*wgsl).into(),
+                })
+        })
+        .ok_or_else(|| ShadyError::MissingFunctionSource {
+            function: function_name.to_string(),
+            library,
+        })
+    }
+
+    /// Whether the custom function `function_name` is available for `library`
+    pub fn contains(
+        &self,
+        function_name: &str,
+        library: GraphicLibrary,
+    ) -> Result<bool, ShadyError> {
+        match self.source(function_name, library) {
+            Ok(_) => Ok(true),
+            Err(ShadyError::MissingFunctionSource { .. }) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Parses the signature of the custom function `function_name` from its GLSL code
+    pub fn signature(&self, function_name: &str) -> Result<FunctionSignature, ShadyError> {
+        let code = self.source(function_name, GraphicLibrary::OpenGl)?;
+        FunctionSignature::parse(&code, function_name)
+    }
+
+    /// Code of `function_name` written for `library` with the other library functions it calls
+    fn function_calls(
+        &self,
+        function_name: &str,
+        library: GraphicLibrary,
+    ) -> Result<FunctionCalls, ShadyError> {
+        let code = self.source(function_name, library)?;
+        let mut calls: Vec<String> = Vec::new();
+        for call in called_functions(&code) {
+            if call != function_name
+                && !calls.iter().any(|c| c == call)
+                && self.contains(call, library)?
+            {
+                calls.push(call.to_string());
+            }
+        }
+        calls.reverse();
+        Ok((function_name.to_string(), code, calls))
+    }
+
+    /// Code of the custom functions `function_names` written for `library` and of the library
+    /// functions they call, directly or not. Every function is declared once, after the
+    /// functions it calls.
+    ///
+    /// Calls are detected as identifiers followed by a parenthesis, in order of appearance.
+    /// Functions calling each other return a `ShadyError::FunctionLoopDetected` with the cycle
+    /// path, each function calling the next one and the last function being the first one again.
+    pub fn declarations<'a>(
+        &self,
+        function_names: impl IntoIterator<Item = &'a str>,
+        library: GraphicLibrary,
+    ) -> Result<Vec<Arc<str>>, ShadyError> {
+        Ok(self
+            .named_declarations(function_names, library)?
+            .into_iter()
+            .map(|(_, code)| code)
+            .collect())
+    }
+
+    /// Names of the functions declared by `FunctionLibrary::declarations`, in declaration order
+    pub fn declaration_names<'a>(
+        &self,
+        function_names: impl IntoIterator<Item = &'a str>,
+        library: GraphicLibrary,
+    ) -> Result<Vec<String>, ShadyError> {
+        Ok(self
+            .named_declarations(function_names, library)?
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect())
+    }
+
+    fn named_declarations<'a>(
+        &self,
+        function_names: impl IntoIterator<Item = &'a str>,
+        library: GraphicLibrary,
+    ) -> Result<Vec<(String, Arc<str>)>, ShadyError> {
+        let mut res = Vec::new();
+        let mut declared = HashSet::new();
+        for root in function_names {
+            if declared.contains(root) {
+                continue;
+            }
+            let mut stack = vec![self.function_calls(root, library)?];
+            while let Some((function_name, code, calls)) = stack.last_mut() {
+                if let Some(call) = calls.pop() {
+                    if declared.contains(&call) {
+                        continue;
+                    }
+                    if let Some(start) = stack.iter().position(|(name, _, _)| *name == call) {
+                        let cycle = stack[start..]
+                            .iter()
+                            .map(|(name, _, _)| name.clone())
+                            .chain(std::iter::once(call))
+                            .collect();
+                        return Err(ShadyError::FunctionLoopDetected(cycle));
+                    }
+                    stack.push(self.function_calls(&call, library)?);
+                } else {
+                    declared.insert(function_name.clone());
+                    res.push((function_name.clone(), Arc::clone(code)));
+                    stack.pop();
+                }
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::test_library;
+
+    #[test]
+    fn looks_up_functions_in_order() {
+        let mut library = test_library().with_search_path("functions");
+        assert!(library
+            .source("test_func", GraphicLibrary::OpenGl)
+            .unwrap()
+            .contains("float test_func(float a, float b)"));
+        assert!(library
+            .source("test_func", GraphicLibrary::WebGPU)
+            .unwrap()
+            .starts_with("// Test function"));
+        for name in FunctionLibrary::builtin_functions() {
+            assert!(library.contains(name, GraphicLibrary::OpenGlEs).unwrap());
+            assert!(library.contains(name, GraphicLibrary::WebGPU).unwrap());
+        }
+        library.add_source(
+            "test_func",
+            GraphicLibrary::OpenGlEs,
+            "float test_func(float a, float b) { return b; }",
+        );
+        assert_eq!(
+            &*library.source("test_func", GraphicLibrary::OpenGl).unwrap(),
+            "float test_func(float a, float b) { return b; }"
+        );
+        assert!(matches!(
+            library.source("missing_func", GraphicLibrary::OpenGl),
+            Err(ShadyError::MissingFunctionSource { function, .. }) if function == "missing_func"
+        ));
+        assert!(!library
+            .contains("../test/test_func", GraphicLibrary::OpenGl)
+            .unwrap());
+        assert_eq!(
+            library.signature("remap").unwrap().to_string(),
+            "float remap(in float value, in float from_min, in float from_max, in float to_min, in float to_max)"
+        );
+    }
+
+    #[test]
+    fn orders_declarations_by_calls() {
+        let library = FunctionLibrary::new()
+            .with_source(
+                "c",
+                GraphicLibrary::OpenGl,
+                "float c(float x) { return b(a(x)); }",
+            )
+            .with_source(
+                "b",
+                GraphicLibrary::OpenGl,
+                "float b(float x) { return a(x) * 2.0; }",
+            )
+            .with_source(
+                "a",
+                GraphicLibrary::OpenGl,
+                "float a(float x) { return abs(x); }",
+            );
+        let declarations: Vec<String> = library
+            .declarations(["c", "grayscale", "a"], GraphicLibrary::OpenGl)
+            .unwrap()
+            .iter()
+            .map(|code| {
+                code.lines()
+                    .find(|l| !l.starts_with("//"))
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(
+            declarations,
+            vec![
+                "float a(float x) { return abs(x); }",
+                "float b(float x) { return a(x) * 2.0; }",
+                "float c(float x) { return b(a(x)); }",
+                "float luminance(vec3 color) {",
+                "vec3 grayscale(vec3 color) {",
+            ]
+        );
+        let library = library.with_source(
+            "a",
+            GraphicLibrary::OpenGl,
+            "float a(float x) { return c(x); }",
+        );
+        assert!(matches!(
+            library.declarations(["b"], GraphicLibrary::OpenGl),
+            Err(ShadyError::FunctionLoopDetected(cycle)) if cycle == ["b", "a", "c", "b"]
+        ));
+    }
+}
@@ -0,0 +1,447 @@
+use super::{FunctionLibrary, NodeOperation};
+use crate::{Input, InputField, NativeType, Output, ShadyError};
+use std::fmt::{self, Display, Formatter};
+
+/// Qualifiers which don't change the type of a parameter or returned value
+const IGNORED_QUALIFIERS: &[&str] = &["const", "highp", "mediump", "lowp", "precise"];
+
+/// Qualifier of a GLSL function parameter
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ParameterQualifier {
+    /// The value is copied into the function
+    In,
+    /// The value is copied out of the function
+    Out,
+    /// The value is copied into and out of the function
+    InOut,
+}
+
+/// Parameter of a GLSL function signature
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FunctionParameter {
+    /// Parameter name, optional in function prototypes
+    pub name: Option<String>,
+    pub native_type: NativeType,
+    pub qualifier: ParameterQualifier,
+}
+
+/// Signature of a GLSL function, parsed from its declaration
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FunctionSignature {
+    pub name: String,
+    /// Returned type, `None` for `void` functions
+    pub return_type: Option<NativeType>,
+    pub parameters: Vec<FunctionParameter>,
+}
+
+/// Unparsed types of a function header
+#[derive(Debug, Eq, PartialEq)]
+struct RawSignature<'a> {
+    return_type: &'a str,
+    /// Qualifier, type and optional name of every parameter
+    parameters: Vec<(ParameterQualifier, &'a str, Option<&'a str>)>,
+}
+
+impl ParameterQualifier {
+    /// All enum variants
+    pub const VARIANTS: &'static [Self] = &[Self::In, Self::Out, Self::InOut];
+}
+
+impl Default for ParameterQualifier {
+    fn default() -> Self {
+        Self::In
+    }
+}
+
+impl Display for ParameterQualifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::In => "in",
+                Self::Out => "out",
+                Self::InOut => "inout",
+            }
+        )
+    }
+}
+
+impl Display for FunctionParameter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.qualifier, self.native_type)?;
+        if let Some(name) = &self.name {
+            write!(f, " {}", name)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for FunctionSignature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let parameters: Vec<String> = self.parameters.iter().map(ToString::to_string).collect();
+        write!(
+            f,
+            "{} {}({})",
+            self.return_type
+                .as_ref()
+                .map_or("void", NativeType::get_glsl_type),
+            self.name,
+            parameters.join(", ")
+        )
+    }
+}
+
+/// Splits GLSL `source` into identifier, number and punctuation tokens, skipping comments and
+/// preprocessor directives
+pub(super) fn tokenize(source: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+    let mut line_start = true;
+    while let Some((start, c)) = chars.next() {
+        let rest = &source[start..];
+        if c == '\n' {
+            line_start = true;
+            continue;
+        }
+        if c.is_whitespace() {
+            continue;
+        }
+        if rest.starts_with("//") || (line_start && c == '#') {
+            while chars.next_if(|(_, c)| *c != '\n').is_some() {}
+            continue;
+        }
+        line_start = false;
+        if rest.starts_with("/*") {
+            let end = rest.find("*/").map_or(source.len(), |i| start + i + 2);
+            while chars.next_if(|(i, _)| *i < end).is_some() {}
+            continue;
+        }
+        let mut end = start + c.len_utf8();
+        if c.is_ascii_alphanumeric() || c == '_' {
+            while let Some((i, c)) = chars.next_if(|(_, c)| c.is_ascii_alphanumeric() || *c == '_')
+            {
+                end = i + c.len_utf8();
+            }
+        }
+        tokens.push(&source[start..end]);
+    }
+    tokens
+}
+
+/// Top level function headers of `tokens` declaring or defining the function `name`, from the
+/// returned type to the closing parenthesis
+fn function_headers<'a, 'b>(tokens: &'b [&'a str], name: &str) -> Vec<&'b [&'a str]> {
+    let mut headers = Vec::new();
+    let mut depth = 0_usize;
+    let mut statement_start = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        match *token {
+            "{" | ";" if depth == 0 => {
+                let statement = &tokens[statement_start..i];
+                let is_header = statement.last() == Some(&")")
+                    && !statement.contains(&"=")
+                    && statement
+                        .iter()
+                        .position(|t| *t == "(")
+                        .is_some_and(|p| p > 0 && statement[p - 1] == name);
+                if is_header {
+                    headers.push(statement);
+                }
+                if *token == "{" {
+                    depth = 1;
+                } else {
+                    statement_start = i + 1;
+                }
+            }
+            "{" => depth += 1,
+            "}" => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    statement_start = i + 1;
+                }
+            }
+            _ => (),
+        }
+    }
+    headers
+}
+
+impl<'a> RawSignature<'a> {
+    /// Parses a function `header`, see `function_headers`
+    fn parse(header: &[&'a str], name: &str) -> Result<Self, ShadyError> {
+        let invalid = |reason: String| ShadyError::InvalidFunctionSignature {
+            function: name.to_string(),
+            reason,
+        };
+        let open = header.iter().position(|t| *t == "(").unwrap_or_default();
+        let return_type = match header[..open.saturating_sub(1)]
+            .iter()
+            .filter(|t| !IGNORED_QUALIFIERS.contains(t))
+            .collect::<Vec<_>>()
+            .as_slice()
+        {
+            [return_type] => **return_type,
+            tokens => {
+                return Err(invalid(format!(
+                    "expected a single returned type, got `{}`",
+                    tokens.iter().map(|t| **t).collect::<Vec<_>>().join(" ")
+                )))
+            }
+        };
+        let parameter_tokens = &header[open + 1..header.len() - 1];
+        if parameter_tokens.is_empty() || parameter_tokens == ["void"] {
+            return Ok(Self {
+                return_type,
+                parameters: Vec::new(),
+            });
+        }
+        let mut parameters = Vec::new();
+        for tokens in parameter_tokens.split(|t| *t == ",") {
+            let mut qualifier = ParameterQualifier::default();
+            let mut tokens = tokens.iter().filter(|t| !IGNORED_QUALIFIERS.contains(t));
+            let mut native_type = tokens.next();
+            if let Some(q) = native_type.and_then(|t| match *t {
+                "in" => Some(ParameterQualifier::In),
+                "out" => Some(ParameterQualifier::Out),
+                "inout" => Some(ParameterQualifier::InOut),
+                _ => None,
+            }) {
+                qualifier = q;
+                native_type = tokens.next();
+            }
+            let native_type =
+                native_type.ok_or_else(|| invalid("missing parameter type".to_string()))?;
+            let name = tokens.next().copied();
+            if let Some(token) = tokens.next() {
+                return Err(invalid(format!(
+                    "unsupported `{}` in parameter {}",
+                    token,
+                    name.unwrap_or(native_type)
+                )));
+            }
+            parameters.push((qualifier, *native_type, name));
+        }
+        Ok(Self {
+            return_type,
+            parameters,
+        })
+    }
+
+    /// Whether both signatures have the same types, ignoring the parameter names
+    fn same_types(&self, other: &Self) -> bool {
+        self.return_type == other.return_type
+            && self.parameters.len() == other.parameters.len()
+            && self
+                .parameters
+                .iter()
+                .zip(&other.parameters)
+                .all(|((q1, t1, _), (q2, t2, _))| q1 == q2 && t1 == t2)
+    }
+}
+
+impl FunctionSignature {
+    /// Parses the signature of the function `name` from the GLSL `source`, which may declare other
+    /// functions.
+    ///
+    /// A function both declared by a prototype and defined is accepted, but overloads with
+    /// different parameters return a `ShadyError::FunctionOverload`
+    pub fn parse(source: &str, name: &str) -> Result<Self, ShadyError> {
+        let tokens = tokenize(source);
+        let mut signatures = function_headers(&tokens, name)
+            .into_iter()
+            .map(|header| RawSignature::parse(header, name))
+            .collect::<Result<Vec<_>, ShadyError>>()?;
+        // Definitions follow prototypes, their parameters are named
+        let raw = signatures
+            .pop()
+            .ok_or_else(|| ShadyError::MissingFunction(name.to_string()))?;
+        if signatures.iter().any(|s| !s.same_types(&raw)) {
+            return Err(ShadyError::FunctionOverload(name.to_string()));
+        }
+        let native_type = |glsl_type: &str| {
+            NativeType::from_glsl_type(glsl_type).ok_or_else(|| {
+                ShadyError::InvalidFunctionSignature {
+                    function: name.to_string(),
+                    reason: format!("unsupported type `{}`", glsl_type),
+                }
+            })
+        };
+        Ok(Self {
+            name: name.to_string(),
+            return_type: match raw.return_type {
+                "void" => None,
+                return_type => Some(native_type(return_type)?),
+            },
+            parameters: raw
+                .parameters
+                .into_iter()
+                .map(|(qualifier, glsl_type, name)| {
+                    Ok(FunctionParameter {
+                        name: name.map(ToString::to_string),
+                        native_type: native_type(glsl_type)?,
+                        qualifier,
+                    })
+                })
+                .collect::<Result<_, ShadyError>>()?,
+        })
+    }
+
+    /// Custom operation calling the function, its input fields being the function parameters and
+    /// its output the returned type.
+    ///
+    /// A node only receives the returned value, so `void` functions and `out` or `inout`
+    /// parameters are refused. Unnamed parameters are named after their position
+    pub fn node_operation(&self) -> Result<NodeOperation, ShadyError> {
+        let invalid = |reason: String| ShadyError::InvalidFunctionSignature {
+            function: self.name.clone(),
+            reason,
+        };
+        let return_type = self
+            .return_type
+            .ok_or_else(|| invalid("`void` functions can't be used as nodes".to_string()))?;
+        let mut fields = Vec::with_capacity(self.parameters.len());
+        for (index, parameter) in self.parameters.iter().enumerate() {
+            if parameter.qualifier != ParameterQualifier::In {
+                return Err(invalid(format!(
+                    "`{}` parameters can't be used as node inputs",
+                    parameter.qualifier
+                )));
+            }
+            let name = parameter
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("p{}", index));
+            fields.push((name, InputField::new(parameter.native_type)));
+        }
+        Ok(NodeOperation::CustomOperation {
+            function_name: self.name.clone(),
+            input: Input { fields },
+            output: Output::NativeType(return_type),
+        })
+    }
+}
+
+impl NodeOperation {
+    /// Custom operation calling the function `function_name` of `functions`, with the input
+    /// fields and output of its GLSL signature. See `FunctionSignature::node_operation`
+    pub fn custom_operation(
+        function_name: &str,
+        functions: &FunctionLibrary,
+    ) -> Result<Self, ShadyError> {
+        functions.signature(function_name)?.node_operation()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::test_library;
+
+    fn parameter(
+        qualifier: ParameterQualifier,
+        native_type: NativeType,
+        name: &str,
+    ) -> FunctionParameter {
+        FunctionParameter {
+            name: Some(name.to_string()),
+            native_type,
+            qualifier,
+        }
+    }
+
+    #[test]
+    fn parses_function_file() {
+        let signature = test_library().signature("test_func").unwrap();
+        assert_eq!(
+            signature.to_string(),
+            "float test_func(in float a, in float b)"
+        );
+        match signature.node_operation().unwrap() {
+            NodeOperation::CustomOperation {
+                function_name,
+                input,
+                output,
+            } => {
+                assert_eq!(function_name, "test_func");
+                let fields: Vec<(&String, NativeType)> =
+                    input.iter().map(|(k, f)| (k, f.glsl_type())).collect();
+                assert_eq!(
+                    fields,
+                    vec![
+                        (&"a".to_string(), NativeType::Float),
+                        (&"b".to_string(), NativeType::Float)
+                    ]
+                );
+                assert!(matches!(output, Output::NativeType(NativeType::Float)));
+            }
+            operation => panic!("Expected a custom operation, got {:?}", operation),
+        }
+    }
+
+    #[test]
+    fn parses_qualifiers_and_prototypes() {
+        let source = indoc! {"
+            #define SCALE 2.0
+            vec3 blend(const in vec3, inout float, out vec2);
+
+            struct Light { vec3 color; };
+
+            /* vec4 blend(vec4 a); */
+            highp vec3 blend(const in vec3 color, inout float weight, out vec2 uv) {
+                uv = vec2(weight);
+                if (weight > 0.0) { weight = 1.0; }
+                return color * SCALE; // vec4 blend(vec4 a);
+            }
+
+            float other(float a) { return blend(vec3(a), a, vec2(a)).x; }
+        "};
+        let signature = FunctionSignature::parse(source, "blend").unwrap();
+        assert_eq!(
+            signature,
+            FunctionSignature {
+                name: "blend".to_string(),
+                return_type: Some(NativeType::Vec3),
+                parameters: vec![
+                    parameter(ParameterQualifier::In, NativeType::Vec3, "color"),
+                    parameter(ParameterQualifier::InOut, NativeType::Float, "weight"),
+                    parameter(ParameterQualifier::Out, NativeType::Vec2, "uv"),
+                ],
+            }
+        );
+        assert!(matches!(
+            signature.node_operation(),
+            Err(ShadyError::InvalidFunctionSignature { .. })
+        ));
+        let signature = FunctionSignature::parse("void reset(void) {}", "reset").unwrap();
+        assert_eq!(signature.to_string(), "void reset()");
+        assert!(signature.node_operation().is_err());
+    }
+
+    #[test]
+    fn refuses_invalid_signatures() {
+        let overloaded = "float f(float a) { return a; }\nfloat f(vec2 a) { return a.x; }";
+        assert!(matches!(
+            FunctionSignature::parse(overloaded, "f"),
+            Err(ShadyError::FunctionOverload(name)) if name == "f"
+        ));
+        assert!(matches!(
+            FunctionSignature::parse(overloaded, "g"),
+            Err(ShadyError::MissingFunction(name)) if name == "g"
+        ));
+        for source in [
+            "mat2x3 f(float a) { return mat2x3(a); }",
+            "float f(float a[2]) { return a[0]; }",
+            "float f(Light light) { return 1.0; }",
+        ] {
+            assert!(matches!(
+                FunctionSignature::parse(source, "f"),
+                Err(ShadyError::InvalidFunctionSignature { .. })
+            ));
+        }
+        assert!(matches!(
+            NodeOperation::custom_operation("missing_func", &test_library()),
+            Err(ShadyError::MissingFunctionSource { .. })
+        ));
+    }
+}
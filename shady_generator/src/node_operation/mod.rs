@@ -1,16 +1,18 @@
+mod function_library;
+mod function_signature;
 mod native_function;
 mod native_operation;
 mod non_scalar_swizzle;
 
-pub use {native_function::*, native_operation::*, non_scalar_swizzle::*};
+pub use {
+    function_library::*, function_signature::*, native_function::*, native_operation::*,
+    non_scalar_swizzle::*,
+};
 
-use crate::{Input, InputField, NonScalarNativeType, Output, ShadyError};
+use crate::{
+    GlslVersion, Input, InputField, NativeType, NonScalarNativeType, Output, ShaderType, ShadyError,
+};
 use serde::{Deserialize, Serialize};
-use std::fs::read_to_string;
-
-lazy_static::lazy_static! {
-    static ref FUNCTIONS_PATH: String = std::env::var("CUSTOM_FUNCTIONS_PATH").unwrap_or_else(|_| "functions".to_string());
-}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) enum InternalNodeOperation {
@@ -25,6 +27,8 @@ pub(crate) enum InternalNodeOperation {
     /// Native Function
     NativeFunction(NativeFunction),
     NonScalarSwizzle(NonScalarSwizzle),
+    /// Sub-graph instance, calling the function generated for the named sub-graph
+    SubGraph(String),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,7 +36,7 @@ pub enum NodeOperation {
     /// Custom function operation, with custom input and output
     CustomOperation {
         /// Custom function name
-        /// Must match a `functions` file
+        /// Must match a function of the `FunctionLibrary` used for generation
         function_name: String,
         /// Input fields
         input: Input,
@@ -48,13 +52,23 @@ pub enum NodeOperation {
     /// Native Function
     NativeFunction(NativeFunction),
     NonScalarSwizzle(NonScalarSwizzle),
+    /// Sub-graph instance, see `SubGraph::operation`
+    SubGraph {
+        /// Sub-graph name, also the name of its generated function
+        name: String,
+        /// Input fields, matching the sub-graph input properties
+        input: Input,
+        /// Output, matching the sub-graph output properties
+        output: Output,
+    },
 }
 
 impl NodeOperation {
     /// Retrieves the input data for the operation
     pub fn input(&self) -> Input {
         match self {
-            NodeOperation::CustomOperation { input, .. } => input.clone(),
+            NodeOperation::CustomOperation { input, .. }
+            | NodeOperation::SubGraph { input, .. } => input.clone(),
             NodeOperation::NativeOperation(o) => o.input(),
             NodeOperation::TypeConstruction(t) => t.type_construction_input(),
             NodeOperation::TypeSplit(t) => Input {
@@ -68,7 +82,8 @@ impl NodeOperation {
     /// Retrieves the output data for the operation
     pub fn output(&self) -> Output {
         match self {
-            NodeOperation::CustomOperation { output, .. } => output.clone(),
+            NodeOperation::CustomOperation { output, .. }
+            | NodeOperation::SubGraph { output, .. } => output.clone(),
             NodeOperation::NativeOperation(o) => o.output(),
             NodeOperation::NativeFunction(f) => f.output(),
             NodeOperation::TypeConstruction(t) => Output::NativeType((*t).into()),
@@ -79,42 +94,64 @@ impl NodeOperation {
 }
 
 impl InternalNodeOperation {
-    pub fn to_glsl(&self, input_fields: &[String]) -> String {
+    /// Rebuilds the public operation, custom operations and sub-graphs use the stored `input` and
+    /// `output`
+    pub fn node_operation(&self, input: &Input, output: &Output) -> NodeOperation {
+        match self {
+            Self::CustomOperation(function_name) => NodeOperation::CustomOperation {
+                function_name: function_name.clone(),
+                input: input.clone(),
+                output: output.clone(),
+            },
+            Self::NativeOperation(o) => NodeOperation::NativeOperation(o.clone()),
+            Self::TypeSplit(t) => NodeOperation::TypeSplit(*t),
+            Self::TypeConstruction(t) => NodeOperation::TypeConstruction(*t),
+            Self::NativeFunction(f) => NodeOperation::NativeFunction(f.clone()),
+            Self::NonScalarSwizzle(s) => NodeOperation::NonScalarSwizzle(s.clone()),
+            Self::SubGraph(name) => NodeOperation::SubGraph {
+                name: name.clone(),
+                input: input.clone(),
+                output: output.clone(),
+            },
+        }
+    }
+
+    pub fn to_glsl(&self, input_fields: &[String], version: GlslVersion) -> String {
         match self {
-            Self::CustomOperation(function_name) => {
+            Self::CustomOperation(function_name) | Self::SubGraph(function_name) => {
                 format!("{}({})", function_name, input_fields.join(", "))
             }
             Self::TypeConstruction(t) | Self::TypeSplit(t) => {
                 format!("{}({})", t, input_fields.join(", "))
             }
             Self::NativeOperation(o) => o.glsl_operation(input_fields),
-            Self::NativeFunction(f) => {
-                format!("{}({})", f.function_name(), input_fields.join(", "))
-            }
+            Self::NativeFunction(f) => f.glsl_call(input_fields, version),
             Self::NonScalarSwizzle(s) => {
                 format!("{}.{}", input_fields.join(","), s.glsl_method())
             }
         }
     }
 
-    pub fn function_declaration(&self) -> Result<Option<String>, ShadyError> {
-        match self {
-            Self::CustomOperation(function_name) => {
-                let path = format!("{}/{}.glsl", *FUNCTIONS_PATH, function_name);
-                log::info!("Loading function from {} file", path);
-                let buff = match read_to_string(path.as_str()) {
-                    Ok(b) => b,
-                    Err(e) => {
-                        return Err(ShadyError::FileNotFound {
-                            file: path,
-                            source: e,
-                        })
-                    }
-                };
-                Ok(Some(buff))
+    pub fn to_wgsl(
+        &self,
+        input_fields: &[String],
+        shader_type: ShaderType,
+    ) -> Result<String, ShadyError> {
+        Ok(match self {
+            Self::CustomOperation(function_name) | Self::SubGraph(function_name) => {
+                format!("{}({})", function_name, input_fields.join(", "))
             }
-            _ => Ok(None),
-        }
+            Self::TypeConstruction(t) | Self::TypeSplit(t) => format!(
+                "{}({})",
+                NativeType::from(*t).get_wgsl_type()?,
+                input_fields.join(", ")
+            ),
+            Self::NativeOperation(o) => o.wgsl_operation(input_fields),
+            Self::NativeFunction(f) => f.wgsl_call(input_fields, shader_type)?,
+            Self::NonScalarSwizzle(s) => {
+                format!("{}.{}", input_fields.join(","), s.glsl_method())
+            }
+        })
     }
 }
 
@@ -129,6 +166,7 @@ impl From<NodeOperation> for InternalNodeOperation {
             NodeOperation::TypeSplit(t) => Self::TypeSplit(t),
             NodeOperation::NativeFunction(f) => Self::NativeFunction(f),
             NodeOperation::NonScalarSwizzle(f) => Self::NonScalarSwizzle(f),
+            NodeOperation::SubGraph { name, .. } => Self::SubGraph(name),
         }
     }
 }
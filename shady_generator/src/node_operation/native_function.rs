@@ -1,4 +1,8 @@
-use crate::{FloatingNativeType, Input, InputField, NativeType, Output};
+use crate::{
+    CustomOutput, FloatingNativeType, GlslVersion, GraphicLibrary, Input, InputField,
+    IntegerNativeType, MatrixNativeType, NativeType, Output, SamplerNativeType, ScalarNativeType,
+    ShaderType, ShadyError,
+};
 use serde::{Deserialize, Serialize};
 
 /// Shader native functions
@@ -137,6 +141,185 @@ pub enum NativeFunction {
     /// After calculating the appropriate level of detail for a texture with mipmaps the bias is added
     /// before the actual texture lookup operation is executed.
     TextureCubeBias,
+    /// The `transpose` function returns the transpose of the matrix m.
+    Transpose(MatrixNativeType),
+    /// The `inverse` function returns the inverse of the matrix m.
+    /// The values in the returned matrix are undefined if m is singular or poorly-conditioned.
+    Inverse(MatrixNativeType),
+    /// The `determinant` function returns the determinant of the matrix m.
+    Determinant(MatrixNativeType),
+    /// The `outerProduct` function returns the linear algebraic matrix multiplication of c and r,
+    /// c being treated as a column vector and r as a row vector.
+    OuterProduct(MatrixNativeType),
+    /// The `matrixCompMult` function returns the component-wise product of the matrices x and y.
+    MatrixCompMult(MatrixNativeType),
+    /// The `sinh` function returns the hyperbolic sine of x.
+    HyperbolicSine(FloatingNativeType),
+    /// The `cosh` function returns the hyperbolic cosine of x.
+    HyperbolicCosine(FloatingNativeType),
+    /// The `tanh` function returns the hyperbolic tangent of x.
+    HyperbolicTangent(FloatingNativeType),
+    /// The `asinh` function returns the inverse of the hyperbolic sine of x.
+    HyperbolicArcSine(FloatingNativeType),
+    /// The `acosh` function returns the non negative inverse of the hyperbolic cosine of x.
+    HyperbolicArcCosine(FloatingNativeType),
+    /// The `atanh` function returns the inverse of the hyperbolic tangent of x.
+    HyperbolicArcTangent(FloatingNativeType),
+    /// The `trunc` function returns the nearest integer to x whose absolute value is not larger
+    /// than the absolute value of x.
+    Truncate(FloatingNativeType),
+    /// The `round` function returns the nearest integer to x, halves being rounded in an
+    /// implementation defined direction.
+    Round(FloatingNativeType),
+    /// The `roundEven` function returns the nearest integer to x, halves being rounded to the
+    /// nearest even integer.
+    RoundEven(FloatingNativeType),
+    /// The `modf` function separates x in a fractional part and an integral part, both with the
+    /// same sign as x. The output is a `fract` and `whole` structure.
+    FractionalIntegralParts(FloatingNativeType),
+    /// The `isnan` function returns true if x is a NaN, component-wise.
+    IsNan(FloatingNativeType),
+    /// The `isinf` function returns true if x is a positive or negative infinity, component-wise.
+    IsInfinite(FloatingNativeType),
+    /// The `floatBitsToInt` function returns the encoding of x as a signed integer.
+    FloatBitsToInt(FloatingNativeType),
+    /// The `floatBitsToUint` function returns the encoding of x as an unsigned integer.
+    FloatBitsToUInt(FloatingNativeType),
+    /// The `intBitsToFloat` function returns the floating number encoded by the signed integer x.
+    /// The type parameter is the output type.
+    IntBitsToFloat(FloatingNativeType),
+    /// The `uintBitsToFloat` function returns the floating number encoded by the unsigned integer
+    /// x. The type parameter is the output type.
+    UIntBitsToFloat(FloatingNativeType),
+    /// The `fma` function returns a * b + c, possibly computed as a single operation.
+    FusedMultiplyAdd(FloatingNativeType),
+    /// The `frexp` function splits x in a significand in the range [0.5, 1.0) and an integral
+    /// exponent of two. The output is a `fract` and `exp` structure.
+    SignificandExponent(FloatingNativeType),
+    /// The `ldexp` function returns x multiplied by two raised to the power of exp.
+    LoadExponent(FloatingNativeType),
+    /// The `packUnorm2x16` function packs the normalized components of a vec2 in an unsigned
+    /// integer, 16 bits each.
+    PackUnorm2x16,
+    /// The `packSnorm2x16` function packs the signed normalized components of a vec2 in an
+    /// unsigned integer, 16 bits each.
+    PackSnorm2x16,
+    /// The `packHalf2x16` function packs the components of a vec2 as half floats in an unsigned
+    /// integer.
+    PackHalf2x16,
+    /// The `packUnorm4x8` function packs the normalized components of a vec4 in an unsigned
+    /// integer, 8 bits each.
+    PackUnorm4x8,
+    /// The `packSnorm4x8` function packs the signed normalized components of a vec4 in an
+    /// unsigned integer, 8 bits each.
+    PackSnorm4x8,
+    /// The `packDouble2x32` function returns the double encoded by the two components of an
+    /// uvec2, the first one being the low bits.
+    PackDouble2x32,
+    /// The `unpackUnorm2x16` function unpacks an unsigned integer in a vec2 of normalized values,
+    /// see `PackUnorm2x16`.
+    UnpackUnorm2x16,
+    /// The `unpackSnorm2x16` function unpacks an unsigned integer in a vec2 of signed normalized
+    /// values, see `PackSnorm2x16`.
+    UnpackSnorm2x16,
+    /// The `unpackHalf2x16` function unpacks an unsigned integer in a vec2 of half floats, see
+    /// `PackHalf2x16`.
+    UnpackHalf2x16,
+    /// The `unpackUnorm4x8` function unpacks an unsigned integer in a vec4 of normalized values,
+    /// see `PackUnorm4x8`.
+    UnpackUnorm4x8,
+    /// The `unpackSnorm4x8` function unpacks an unsigned integer in a vec4 of signed normalized
+    /// values, see `PackSnorm4x8`.
+    UnpackSnorm4x8,
+    /// The `unpackDouble2x32` function returns the encoding of a double as an uvec2, see
+    /// `PackDouble2x32`.
+    UnpackDouble2x32,
+    /// The `bitfieldExtract` function returns the `bits` bits of value starting at `offset`,
+    /// sign extended for signed integers.
+    BitfieldExtract(IntegerNativeType),
+    /// The `bitfieldInsert` function returns base with its `bits` bits starting at `offset`
+    /// replaced by the lowest bits of insert.
+    BitfieldInsert(IntegerNativeType),
+    /// The `bitfieldReverse` function returns the bits of value in reverse order.
+    BitfieldReverse(IntegerNativeType),
+    /// The `bitCount` function returns the number of bits set in value.
+    BitCount(IntegerNativeType),
+    /// The `findLSB` function returns the index of the least significant bit set in value, or -1
+    /// if value is zero.
+    FindLsb(IntegerNativeType),
+    /// The `findMSB` function returns the index of the most significant bit set in value, or of
+    /// the most significant bit not set for negative values, or -1 if there is none.
+    FindMsb(IntegerNativeType),
+    /// The `texture` function returns the texel of the sampler at the given coordinates.
+    /// The array layer and the depth comparison reference of shadow samplers are the last
+    /// coordinates, shadow samplers return the comparison result.
+    Texture(SamplerNativeType),
+    /// The `textureLod` function returns the texel of the sampler at the given coordinates for
+    /// an explicit level of detail.
+    TextureLod(SamplerNativeType),
+    /// The `textureGrad` function returns the texel of the sampler at the given coordinates,
+    /// the level of detail being computed from the explicit coordinates partial derivatives.
+    TextureGrad(SamplerNativeType),
+    /// The `textureOffset` function returns the texel of the sampler at the given coordinates
+    /// offset by a texel count. The offset must be a constant expression.
+    TextureOffset(SamplerNativeType),
+    /// The `texelFetch` function returns the texel at the given integer texel coordinates and
+    /// level of detail, without filtering.
+    TexelFetch(SamplerNativeType),
+    /// The `textureSize` function returns the dimensions of the sampler texture for the given
+    /// level of detail, array textures having their layer count as last component.
+    TextureSize(SamplerNativeType),
+    /// The `textureGather` function returns the first component of the four texels sampled
+    /// for bilinear filtering, shadow samplers return the four comparison results.
+    TextureGather(SamplerNativeType),
+    /// The `textureProj` function returns the texel of the sampler at the given homogeneous
+    /// coordinates, divided by their last component before the lookup.
+    TextureProj(SamplerNativeType),
+    /// The `textureProjLod` function returns the texel of the sampler at the given homogeneous
+    /// coordinates for an explicit level of detail.
+    TextureProjLod(SamplerNativeType),
+}
+
+/// Type of the same size as `native_type` made of `component` typed components
+fn sized_like(native_type: impl Into<NativeType>, component: ScalarNativeType) -> NativeType {
+    native_type
+        .into()
+        .vector_size()
+        .and_then(|size| component.vector_type(size))
+        .unwrap_or_else(|| component.into())
+}
+
+/// WGSL arguments of the GLSL packed `coordinates` of a `sampler` lookup, WGSL takes the array
+/// layer and the depth comparison reference as separate arguments
+fn wgsl_coordinates(
+    sampler: SamplerNativeType,
+    coordinates: &str,
+    packed_reference: bool,
+) -> String {
+    let layer = format!("i32(floor({}.z + 0.5))", coordinates);
+    match (sampler.is_array(), sampler.is_shadow() && packed_reference) {
+        (true, true) => format!("{0}.xy, {1}, {0}.w", coordinates, layer),
+        (true, false) => format!("{}.xy, {}", coordinates, layer),
+        (false, true) if sampler.is_cube() => format!("{0}.xyz, {0}.w", coordinates),
+        (false, true) => format!("{0}.xy, {0}.z", coordinates),
+        (false, false) => coordinates.to_string(),
+    }
+}
+
+/// Structured output of functions with two results, the `fract` field being the returned value
+fn parts_output(
+    function_name: &str,
+    t: FloatingNativeType,
+    second_field: &str,
+    second_type: NativeType,
+) -> Output {
+    Output::CustomType(CustomOutput {
+        struct_name: format!("{}_result_{}", function_name, NativeType::from(t)),
+        fields: vec![
+            ("fract".to_string(), t.into()),
+            (second_field.to_string(), second_type),
+        ],
+    })
 }
 
 impl NativeFunction {
@@ -178,11 +361,423 @@ impl NativeFunction {
             NativeFunction::FaceForward(_) => "faceforward",
             NativeFunction::Reflect(_) => "reflect",
             NativeFunction::Refract(_) => "refract",
-            NativeFunction::Texture2d | NativeFunction::Texture2dBias => "texture2d",
+            NativeFunction::Texture2d | NativeFunction::Texture2dBias => "texture2D",
             NativeFunction::TextureCube | NativeFunction::TextureCubeBias => "textureCube",
+            NativeFunction::Transpose(_) => "transpose",
+            NativeFunction::Inverse(_) => "inverse",
+            NativeFunction::Determinant(_) => "determinant",
+            NativeFunction::OuterProduct(_) => "outerProduct",
+            NativeFunction::MatrixCompMult(_) => "matrixCompMult",
+            NativeFunction::HyperbolicSine(_) => "sinh",
+            NativeFunction::HyperbolicCosine(_) => "cosh",
+            NativeFunction::HyperbolicTangent(_) => "tanh",
+            NativeFunction::HyperbolicArcSine(_) => "asinh",
+            NativeFunction::HyperbolicArcCosine(_) => "acosh",
+            NativeFunction::HyperbolicArcTangent(_) => "atanh",
+            NativeFunction::Truncate(_) => "trunc",
+            NativeFunction::Round(_) => "round",
+            NativeFunction::RoundEven(_) => "roundEven",
+            NativeFunction::FractionalIntegralParts(_) => "modf",
+            NativeFunction::IsNan(_) => "isnan",
+            NativeFunction::IsInfinite(_) => "isinf",
+            NativeFunction::FloatBitsToInt(_) => "floatBitsToInt",
+            NativeFunction::FloatBitsToUInt(_) => "floatBitsToUint",
+            NativeFunction::IntBitsToFloat(_) => "intBitsToFloat",
+            NativeFunction::UIntBitsToFloat(_) => "uintBitsToFloat",
+            NativeFunction::FusedMultiplyAdd(_) => "fma",
+            NativeFunction::SignificandExponent(_) => "frexp",
+            NativeFunction::LoadExponent(_) => "ldexp",
+            NativeFunction::PackUnorm2x16 => "packUnorm2x16",
+            NativeFunction::PackSnorm2x16 => "packSnorm2x16",
+            NativeFunction::PackHalf2x16 => "packHalf2x16",
+            NativeFunction::PackUnorm4x8 => "packUnorm4x8",
+            NativeFunction::PackSnorm4x8 => "packSnorm4x8",
+            NativeFunction::PackDouble2x32 => "packDouble2x32",
+            NativeFunction::UnpackUnorm2x16 => "unpackUnorm2x16",
+            NativeFunction::UnpackSnorm2x16 => "unpackSnorm2x16",
+            NativeFunction::UnpackHalf2x16 => "unpackHalf2x16",
+            NativeFunction::UnpackUnorm4x8 => "unpackUnorm4x8",
+            NativeFunction::UnpackSnorm4x8 => "unpackSnorm4x8",
+            NativeFunction::UnpackDouble2x32 => "unpackDouble2x32",
+            NativeFunction::BitfieldExtract(_) => "bitfieldExtract",
+            NativeFunction::BitfieldInsert(_) => "bitfieldInsert",
+            NativeFunction::BitfieldReverse(_) => "bitfieldReverse",
+            NativeFunction::BitCount(_) => "bitCount",
+            NativeFunction::FindLsb(_) => "findLSB",
+            NativeFunction::FindMsb(_) => "findMSB",
+            NativeFunction::Texture(_) => "texture",
+            NativeFunction::TextureLod(_) => "textureLod",
+            NativeFunction::TextureGrad(_) => "textureGrad",
+            NativeFunction::TextureOffset(_) => "textureOffset",
+            NativeFunction::TexelFetch(_) => "texelFetch",
+            NativeFunction::TextureSize(_) => "textureSize",
+            NativeFunction::TextureGather(_) => "textureGather",
+            NativeFunction::TextureProj(_) => "textureProj",
+            NativeFunction::TextureProjLod(_) => "textureProjLod",
+        }
+    }
+
+    /// Sampler type of the texture lookup functions
+    pub const fn sampler_type(&self) -> Option<SamplerNativeType> {
+        match self {
+            NativeFunction::Texture(s)
+            | NativeFunction::TextureLod(s)
+            | NativeFunction::TextureGrad(s)
+            | NativeFunction::TextureOffset(s)
+            | NativeFunction::TexelFetch(s)
+            | NativeFunction::TextureSize(s)
+            | NativeFunction::TextureGather(s)
+            | NativeFunction::TextureProj(s)
+            | NativeFunction::TextureProjLod(s) => Some(*s),
+            _ => None,
+        }
+    }
+
+    /// Mutable sampler type of the texture lookup functions
+    pub fn sampler_type_mut(&mut self) -> Option<&mut SamplerNativeType> {
+        match self {
+            NativeFunction::Texture(s)
+            | NativeFunction::TextureLod(s)
+            | NativeFunction::TextureGrad(s)
+            | NativeFunction::TextureOffset(s)
+            | NativeFunction::TexelFetch(s)
+            | NativeFunction::TextureSize(s)
+            | NativeFunction::TextureGather(s)
+            | NativeFunction::TextureProj(s)
+            | NativeFunction::TextureProjLod(s) => Some(s),
+            _ => None,
         }
     }
 
+    /// Is the texture lookup function defined for its sampler type: cube maps have no offset,
+    /// only non shadow 2D, 3D and array textures can be fetched, 3D textures can't be gathered,
+    /// only 2D and 3D textures have projective lookups and shadow cube maps and arrays have no
+    /// explicit level of detail lookups
+    pub const fn accepts_sampler(&self) -> bool {
+        match self {
+            NativeFunction::TextureLod(s) => !matches!(
+                s,
+                SamplerNativeType::SamplerCubeShadow | SamplerNativeType::Sampler2dArrayShadow
+            ),
+            NativeFunction::TextureOffset(s) => {
+                s.offset_type().is_some() && !matches!(s, SamplerNativeType::Sampler2dArrayShadow)
+            }
+            NativeFunction::TexelFetch(s) => s.fetch_coordinates_type().is_some(),
+            NativeFunction::TextureGather(s) => !s.is_3d(),
+            NativeFunction::TextureProj(s) | NativeFunction::TextureProjLod(s) => {
+                s.projective_coordinates_type().is_some()
+            }
+            _ => true,
+        }
+    }
+
+    /// Retrieves the name of the native function for the targeted GLSL `version`
+    pub const fn glsl_function_name(&self, version: GlslVersion) -> &'static str {
+        match self {
+            NativeFunction::Texture2d
+            | NativeFunction::Texture2dBias
+            | NativeFunction::TextureCube
+            | NativeFunction::TextureCubeBias
+                if !version.is_legacy() =>
+            {
+                "texture"
+            }
+            _ => self.function_name(),
+        }
+    }
+
+    /// Outputs the function call as GLSL code for the targeted `version`
+    pub fn glsl_call(&self, field_values: &[String], version: GlslVersion) -> String {
+        format!(
+            "{}({})",
+            self.glsl_function_name(version),
+            field_values.join(", ")
+        )
+    }
+
+    /// GLSL functions with two results return the first one and write the second one in an
+    /// `out` parameter, WGSL returns both in a built-in structure with the same fields.
+    /// Retrieves the output fields of both results
+    pub const fn glsl_out_parameter(&self) -> Option<(&'static str, &'static str)> {
+        match self {
+            NativeFunction::FractionalIntegralParts(_) => Some(("fract", "whole")),
+            NativeFunction::SignificandExponent(_) => Some(("fract", "exp")),
+            _ => None,
+        }
+    }
+
+    /// Input fields which must be constant expressions, either a constant or their default value
+    pub const fn constant_input_fields(&self) -> &'static [&'static str] {
+        match self {
+            NativeFunction::TextureOffset(_) => &["offset"],
+            _ => &[],
+        }
+    }
+
+    /// Retrieves the name of the native function in WGSL
+    pub const fn wgsl_function_name(&self) -> &'static str {
+        match self {
+            NativeFunction::ArcTangent2(_) => "atan2",
+            NativeFunction::InverseSquareRoot(_) => "inverseSqrt",
+            NativeFunction::FaceForward(_) => "faceForward",
+            NativeFunction::RoundEven(_) => "round",
+            NativeFunction::PackUnorm2x16 => "pack2x16unorm",
+            NativeFunction::PackSnorm2x16 => "pack2x16snorm",
+            NativeFunction::PackHalf2x16 => "pack2x16float",
+            NativeFunction::PackUnorm4x8 => "pack4x8unorm",
+            NativeFunction::PackSnorm4x8 => "pack4x8snorm",
+            NativeFunction::UnpackUnorm2x16 => "unpack2x16unorm",
+            NativeFunction::UnpackSnorm2x16 => "unpack2x16snorm",
+            NativeFunction::UnpackHalf2x16 => "unpack2x16float",
+            NativeFunction::UnpackUnorm4x8 => "unpack4x8unorm",
+            NativeFunction::UnpackSnorm4x8 => "unpack4x8snorm",
+            NativeFunction::BitfieldExtract(_) => "extractBits",
+            NativeFunction::BitfieldInsert(_) => "insertBits",
+            NativeFunction::BitfieldReverse(_) => "reverseBits",
+            NativeFunction::BitCount(_) => "countOneBits",
+            NativeFunction::FindLsb(_) => "firstTrailingBit",
+            NativeFunction::FindMsb(_) => "firstLeadingBit",
+            NativeFunction::Texture2d
+            | NativeFunction::TextureCube
+            | NativeFunction::Texture2dBias
+            | NativeFunction::TextureCubeBias => "textureSample",
+            _ => self.function_name(),
+        }
+    }
+
+    /// Outputs the function call as WGSL code. WGSL has no `inverse` and double packing
+    /// functions, and no `isnan` and `isinf` functions which are emulated on the float bits.
+    /// WGSL texture lookups take the sampler as a separate `{texture}_sampler` binding.
+    ///
+    /// Implicit level of detail lookups are only available in fragment shaders, vertex shaders
+    /// sample the base level instead and can't apply a bias
+    #[allow(clippy::too_many_lines)]
+    pub fn wgsl_call(
+        &self,
+        field_values: &[String],
+        shader_type: ShaderType,
+    ) -> Result<String, ShadyError> {
+        let vertex = shader_type == ShaderType::Vertex;
+        let splat = |t: &FloatingNativeType, v: &String| match t {
+            FloatingNativeType::Float => v.clone(),
+            FloatingNativeType::Vec2 => format!("vec2<f32>({})", v),
+            FloatingNativeType::Vec3 => format!("vec3<f32>({})", v),
+            FloatingNativeType::Vec4 => format!("vec4<f32>({})", v),
+        };
+        // Matrix built column by column from the `column` expression of every column index
+        let columns = |t: &MatrixNativeType, column: &dyn Fn(usize) -> String| {
+            let columns: Vec<String> = (0..t.size()).map(column).collect();
+            NativeType::from(*t)
+                .get_wgsl_type()
+                .map(|wgsl_type| format!("{}({})", wgsl_type, columns.join(", ")))
+        };
+        let output_type = || self.output().wgsl_type();
+        // Unsigned integer encoding of a float, or its vector
+        let float_bits = |t: &FloatingNativeType, v: &String| {
+            sized_like(*t, ScalarNativeType::UInt)
+                .get_wgsl_type()
+                .map(|bits| {
+                    (
+                        format!("(bitcast<{0}>({1}) & {0}(0x7fffffffu))", bits, v),
+                        format!("{}(0x7f800000u)", bits),
+                    )
+                })
+        };
+        let unsupported = || ShadyError::UnsupportedNativeFunction {
+            function: self.sampler_type().map_or_else(
+                || self.function_name().to_string(),
+                |sampler| format!("{}({})", self.function_name(), sampler),
+            ),
+            target: GraphicLibrary::WebGPU.to_string(),
+        };
+        let filtered_integer = matches!(
+            self,
+            NativeFunction::Texture(_)
+                | NativeFunction::TextureLod(_)
+                | NativeFunction::TextureGrad(_)
+                | NativeFunction::TextureOffset(_)
+                | NativeFunction::TextureProj(_)
+                | NativeFunction::TextureProjLod(_)
+        ) && self
+            .sampler_type()
+            .is_some_and(SamplerNativeType::is_integer);
+        if !self.accepts_sampler() || filtered_integer {
+            return Err(unsupported());
+        }
+        Ok(match self {
+            NativeFunction::Modulo(_) | NativeFunction::FloatModulo(_) => format!(
+                "({0} - {1} * floor({0} / {1}))",
+                field_values[0], field_values[1]
+            ),
+            NativeFunction::FloatMinimum(t) | NativeFunction::FloatMaximum(t) => format!(
+                "{}({}, {})",
+                self.wgsl_function_name(),
+                field_values[0],
+                splat(t, &field_values[1])
+            ),
+            NativeFunction::FloatClamp(t) => format!(
+                "clamp({}, {}, {})",
+                field_values[0],
+                splat(t, &field_values[1]),
+                splat(t, &field_values[2])
+            ),
+            NativeFunction::FloatStep(t) => {
+                format!("step({}, {})", field_values[0], splat(t, &field_values[1]))
+            }
+            NativeFunction::FloatSmoothStep(t) => format!(
+                "smoothstep({}, {}, {})",
+                splat(t, &field_values[0]),
+                splat(t, &field_values[1]),
+                field_values[2]
+            ),
+            NativeFunction::Texture2d | NativeFunction::TextureCube if vertex => format!(
+                "textureSampleLevel({0}, {0}_sampler, {1}, 0.0)",
+                field_values[0], field_values[1]
+            ),
+            NativeFunction::Texture2d | NativeFunction::TextureCube => format!(
+                "textureSample({0}, {0}_sampler, {1})",
+                field_values[0], field_values[1]
+            ),
+            NativeFunction::Texture2dBias | NativeFunction::TextureCubeBias if vertex => {
+                return Err(ShadyError::UnsupportedNativeFunction {
+                    function: self.function_name().to_string(),
+                    target: format!("{} vertex shader", GraphicLibrary::WebGPU),
+                })
+            }
+            NativeFunction::Texture2dBias | NativeFunction::TextureCubeBias => format!(
+                "textureSampleBias({0}, {0}_sampler, {1}, {2})",
+                field_values[0], field_values[1], field_values[2]
+            ),
+            NativeFunction::TexelFetch(s) if s.is_array() => format!(
+                "textureLoad({0}, {1}.xy, {1}.z, {2})",
+                field_values[0], field_values[1], field_values[2]
+            ),
+            NativeFunction::TexelFetch(_) => format!(
+                "textureLoad({}, {}, {})",
+                field_values[0], field_values[1], field_values[2]
+            ),
+            NativeFunction::TextureSize(s) if s.is_array() => format!(
+                "vec3<i32>(vec2<i32>(textureDimensions({0}, {1})), i32(textureNumLayers({0})))",
+                field_values[0], field_values[1]
+            ),
+            NativeFunction::TextureSize(_) => format!(
+                "{}(textureDimensions({}, {}))",
+                output_type()?,
+                field_values[0],
+                field_values[1]
+            ),
+            NativeFunction::TextureGather(s) if s.is_shadow() => format!(
+                "textureGatherCompare({0}, {0}_sampler, {1}, {2})",
+                field_values[0],
+                wgsl_coordinates(*s, &field_values[1], false),
+                field_values[2]
+            ),
+            NativeFunction::TextureGather(s) => format!(
+                "textureGather(0, {0}, {0}_sampler, {1})",
+                field_values[0],
+                wgsl_coordinates(*s, &field_values[1], false)
+            ),
+            // WGSL can't filter integer textures and has no explicit level of detail or
+            // gradient comparison lookups
+            NativeFunction::Texture(s)
+            | NativeFunction::TextureLod(s)
+            | NativeFunction::TextureGrad(s)
+            | NativeFunction::TextureOffset(s)
+            | NativeFunction::TextureProj(s)
+            | NativeFunction::TextureProjLod(s) => {
+                // Vertex shaders sample the base level, given right after the coordinates
+                let (lookup, level) = match (self, s.is_shadow()) {
+                    (NativeFunction::TextureLod(_) | NativeFunction::TextureProjLod(_), false) => {
+                        ("textureSampleLevel", None)
+                    }
+                    (NativeFunction::TextureGrad(_), false) => ("textureSampleGrad", None),
+                    (
+                        NativeFunction::Texture(_)
+                        | NativeFunction::TextureOffset(_)
+                        | NativeFunction::TextureProj(_),
+                        true,
+                    ) if vertex => ("textureSampleCompareLevel", None),
+                    (
+                        NativeFunction::Texture(_)
+                        | NativeFunction::TextureOffset(_)
+                        | NativeFunction::TextureProj(_),
+                        true,
+                    ) => ("textureSampleCompare", None),
+                    (_, false) if vertex => ("textureSampleLevel", Some("0.0")),
+                    (_, false) => ("textureSample", None),
+                    (_, true) => return Err(unsupported()),
+                };
+                let coordinates = match s {
+                    _ if !matches!(
+                        self,
+                        NativeFunction::TextureProj(_) | NativeFunction::TextureProjLod(_)
+                    ) =>
+                    {
+                        wgsl_coordinates(*s, &field_values[1], true)
+                    }
+                    SamplerNativeType::Sampler2dShadow => {
+                        format!("{0}.xy / {0}.w, {0}.z / {0}.w", field_values[1])
+                    }
+                    _ if s.is_3d() => format!("{0}.xyz / {0}.w", field_values[1]),
+                    _ => format!("{0}.xy / {0}.z", field_values[1]),
+                };
+                let arguments: Vec<&str> = std::iter::once(coordinates.as_str())
+                    .chain(level)
+                    .chain(field_values[2..].iter().map(String::as_str))
+                    .collect();
+                format!(
+                    "{1}({0}, {0}_sampler, {2})",
+                    field_values[0],
+                    lookup,
+                    arguments.join(", ")
+                )
+            }
+            NativeFunction::OuterProduct(t) => columns(t, &|i| {
+                format!("{} * {}[{}]", field_values[0], field_values[1], i)
+            })?,
+            NativeFunction::MatrixCompMult(t) => columns(t, &|i| {
+                format!("{0}[{2}] * {1}[{2}]", field_values[0], field_values[1], i)
+            })?,
+            NativeFunction::IsNan(t) => {
+                let (bits, infinity) = float_bits(t, &field_values[0])?;
+                format!("({} > {})", bits, infinity)
+            }
+            NativeFunction::IsInfinite(t) => {
+                let (bits, infinity) = float_bits(t, &field_values[0])?;
+                format!("({} == {})", bits, infinity)
+            }
+            NativeFunction::FloatBitsToInt(_)
+            | NativeFunction::FloatBitsToUInt(_)
+            | NativeFunction::IntBitsToFloat(_)
+            | NativeFunction::UIntBitsToFloat(_) => {
+                format!("bitcast<{}>({})", output_type()?, field_values[0])
+            }
+            NativeFunction::BitfieldExtract(_) => format!(
+                "extractBits({}, u32({}), u32({}))",
+                field_values[0], field_values[1], field_values[2]
+            ),
+            NativeFunction::BitfieldInsert(_) => format!(
+                "insertBits({}, {}, u32({}), u32({}))",
+                field_values[0], field_values[1], field_values[2], field_values[3]
+            ),
+            // WGSL returns the input type, GLSL signed integers
+            NativeFunction::BitCount(_)
+            | NativeFunction::FindLsb(_)
+            | NativeFunction::FindMsb(_) => {
+                format!(
+                    "{}({}({}))",
+                    output_type()?,
+                    self.wgsl_function_name(),
+                    field_values[0]
+                )
+            }
+            NativeFunction::Inverse(_)
+            | NativeFunction::PackDouble2x32
+            | NativeFunction::UnpackDouble2x32 => return Err(unsupported()),
+            _ => format!("{}({})", self.wgsl_function_name(), field_values.join(", ")),
+        })
+    }
+
     /// Retrieves a unique descriptive name for the native function
     pub const fn descriptive_name(&self) -> &'static str {
         match self {
@@ -233,6 +828,57 @@ impl NativeFunction {
             NativeFunction::Texture2dBias => "texture2d_bias",
             NativeFunction::TextureCube => "textureCube",
             NativeFunction::TextureCubeBias => "textureCube_bias",
+            NativeFunction::Transpose(_) => "transpose",
+            NativeFunction::Inverse(_) => "inverse",
+            NativeFunction::Determinant(_) => "determinant",
+            NativeFunction::OuterProduct(_) => "outerProduct",
+            NativeFunction::MatrixCompMult(_) => "matrixCompMult",
+            NativeFunction::HyperbolicSine(_) => "sinh",
+            NativeFunction::HyperbolicCosine(_) => "cosh",
+            NativeFunction::HyperbolicTangent(_) => "tanh",
+            NativeFunction::HyperbolicArcSine(_) => "asinh",
+            NativeFunction::HyperbolicArcCosine(_) => "acosh",
+            NativeFunction::HyperbolicArcTangent(_) => "atanh",
+            NativeFunction::Truncate(_) => "trunc",
+            NativeFunction::Round(_) => "round",
+            NativeFunction::RoundEven(_) => "roundEven",
+            NativeFunction::FractionalIntegralParts(_) => "modf",
+            NativeFunction::IsNan(_) => "isnan",
+            NativeFunction::IsInfinite(_) => "isinf",
+            NativeFunction::FloatBitsToInt(_) => "floatBitsToInt",
+            NativeFunction::FloatBitsToUInt(_) => "floatBitsToUint",
+            NativeFunction::IntBitsToFloat(_) => "intBitsToFloat",
+            NativeFunction::UIntBitsToFloat(_) => "uintBitsToFloat",
+            NativeFunction::FusedMultiplyAdd(_) => "fma",
+            NativeFunction::SignificandExponent(_) => "frexp",
+            NativeFunction::LoadExponent(_) => "ldexp",
+            NativeFunction::PackUnorm2x16 => "packUnorm2x16",
+            NativeFunction::PackSnorm2x16 => "packSnorm2x16",
+            NativeFunction::PackHalf2x16 => "packHalf2x16",
+            NativeFunction::PackUnorm4x8 => "packUnorm4x8",
+            NativeFunction::PackSnorm4x8 => "packSnorm4x8",
+            NativeFunction::PackDouble2x32 => "packDouble2x32",
+            NativeFunction::UnpackUnorm2x16 => "unpackUnorm2x16",
+            NativeFunction::UnpackSnorm2x16 => "unpackSnorm2x16",
+            NativeFunction::UnpackHalf2x16 => "unpackHalf2x16",
+            NativeFunction::UnpackUnorm4x8 => "unpackUnorm4x8",
+            NativeFunction::UnpackSnorm4x8 => "unpackSnorm4x8",
+            NativeFunction::UnpackDouble2x32 => "unpackDouble2x32",
+            NativeFunction::BitfieldExtract(_) => "bitfieldExtract",
+            NativeFunction::BitfieldInsert(_) => "bitfieldInsert",
+            NativeFunction::BitfieldReverse(_) => "bitfieldReverse",
+            NativeFunction::BitCount(_) => "bitCount",
+            NativeFunction::FindLsb(_) => "findLSB",
+            NativeFunction::FindMsb(_) => "findMSB",
+            NativeFunction::Texture(_) => "texture",
+            NativeFunction::TextureLod(_) => "textureLod",
+            NativeFunction::TextureGrad(_) => "textureGrad",
+            NativeFunction::TextureOffset(_) => "textureOffset",
+            NativeFunction::TexelFetch(_) => "texelFetch",
+            NativeFunction::TextureSize(_) => "textureSize",
+            NativeFunction::TextureGather(_) => "textureGather",
+            NativeFunction::TextureProj(_) => "textureProj",
+            NativeFunction::TextureProjLod(_) => "textureProjLod",
         }
     }
 
@@ -260,9 +906,94 @@ impl NativeFunction {
             | NativeFunction::Ceiling(t)
             | NativeFunction::Length(t)
             | NativeFunction::Normalize(t)
-            | NativeFunction::FractionalPart(t) => Input {
+            | NativeFunction::FractionalPart(t)
+            | NativeFunction::HyperbolicSine(t)
+            | NativeFunction::HyperbolicCosine(t)
+            | NativeFunction::HyperbolicTangent(t)
+            | NativeFunction::HyperbolicArcSine(t)
+            | NativeFunction::HyperbolicArcCosine(t)
+            | NativeFunction::HyperbolicArcTangent(t)
+            | NativeFunction::Truncate(t)
+            | NativeFunction::Round(t)
+            | NativeFunction::RoundEven(t)
+            | NativeFunction::FractionalIntegralParts(t)
+            | NativeFunction::IsNan(t)
+            | NativeFunction::IsInfinite(t)
+            | NativeFunction::FloatBitsToInt(t)
+            | NativeFunction::FloatBitsToUInt(t)
+            | NativeFunction::SignificandExponent(t) => Input {
                 fields: vec![("v".to_string(), InputField::new(NativeType::from(*t)))],
             },
+            NativeFunction::IntBitsToFloat(t) => Input {
+                fields: vec![(
+                    "v".to_string(),
+                    InputField::new(sized_like(*t, ScalarNativeType::Int)),
+                )],
+            },
+            NativeFunction::UIntBitsToFloat(t) => Input {
+                fields: vec![(
+                    "v".to_string(),
+                    InputField::new(sized_like(*t, ScalarNativeType::UInt)),
+                )],
+            },
+            NativeFunction::FusedMultiplyAdd(t) => Input {
+                fields: vec![
+                    ("a".to_string(), InputField::new(NativeType::from(*t))),
+                    ("b".to_string(), InputField::new(NativeType::from(*t))),
+                    ("c".to_string(), InputField::new(NativeType::from(*t))),
+                ],
+            },
+            NativeFunction::LoadExponent(t) => Input {
+                fields: vec![
+                    ("v".to_string(), InputField::new(NativeType::from(*t))),
+                    (
+                        "exp".to_string(),
+                        InputField::new(sized_like(*t, ScalarNativeType::Int)),
+                    ),
+                ],
+            },
+            NativeFunction::PackUnorm2x16
+            | NativeFunction::PackSnorm2x16
+            | NativeFunction::PackHalf2x16 => Input {
+                fields: vec![("v".to_string(), InputField::new(NativeType::Vec2))],
+            },
+            NativeFunction::PackUnorm4x8 | NativeFunction::PackSnorm4x8 => Input {
+                fields: vec![("v".to_string(), InputField::new(NativeType::Vec4))],
+            },
+            NativeFunction::PackDouble2x32 => Input {
+                fields: vec![("v".to_string(), InputField::new(NativeType::UVec2))],
+            },
+            NativeFunction::UnpackUnorm2x16
+            | NativeFunction::UnpackSnorm2x16
+            | NativeFunction::UnpackHalf2x16
+            | NativeFunction::UnpackUnorm4x8
+            | NativeFunction::UnpackSnorm4x8 => Input {
+                fields: vec![("p".to_string(), InputField::new(NativeType::UInt))],
+            },
+            NativeFunction::UnpackDouble2x32 => Input {
+                fields: vec![("v".to_string(), InputField::new(NativeType::Double))],
+            },
+            NativeFunction::BitfieldExtract(t) => Input {
+                fields: vec![
+                    ("value".to_string(), InputField::new(NativeType::from(*t))),
+                    ("offset".to_string(), InputField::new(NativeType::Int)),
+                    ("bits".to_string(), InputField::new(NativeType::Int)),
+                ],
+            },
+            NativeFunction::BitfieldInsert(t) => Input {
+                fields: vec![
+                    ("base".to_string(), InputField::new(NativeType::from(*t))),
+                    ("insert".to_string(), InputField::new(NativeType::from(*t))),
+                    ("offset".to_string(), InputField::new(NativeType::Int)),
+                    ("bits".to_string(), InputField::new(NativeType::Int)),
+                ],
+            },
+            NativeFunction::BitfieldReverse(t)
+            | NativeFunction::BitCount(t)
+            | NativeFunction::FindLsb(t)
+            | NativeFunction::FindMsb(t) => Input {
+                fields: vec![("value".to_string(), InputField::new(NativeType::from(*t)))],
+            },
             NativeFunction::ArcTangent2(t)
             | NativeFunction::Power(t)
             | NativeFunction::Modulo(t)
@@ -396,10 +1127,85 @@ impl NativeFunction {
                     ("bias".to_string(), InputField::new(NativeType::Float)),
                 ],
             },
+            NativeFunction::Texture(s)
+            | NativeFunction::TextureLod(s)
+            | NativeFunction::TextureGrad(s)
+            | NativeFunction::TextureOffset(s)
+            | NativeFunction::TexelFetch(s)
+            | NativeFunction::TextureSize(s)
+            | NativeFunction::TextureGather(s)
+            | NativeFunction::TextureProj(s)
+            | NativeFunction::TextureProjLod(s) => {
+                let field = |name: &str, native_type: NativeType| {
+                    (name.to_string(), InputField::new(native_type))
+                };
+                let mut fields = vec![field("sampler", NativeType::from(*s))];
+                fields.extend(match self {
+                    NativeFunction::TextureLod(_) => vec![
+                        field("coords", s.coordinates_type()),
+                        field("lod", NativeType::Float),
+                    ],
+                    NativeFunction::TextureGrad(_) => vec![
+                        field("coords", s.coordinates_type()),
+                        field("dPdx", s.gradient_type()),
+                        field("dPdy", s.gradient_type()),
+                    ],
+                    NativeFunction::TextureOffset(_) => vec![
+                        field("coords", s.coordinates_type()),
+                        field("offset", s.offset_type().unwrap_or(NativeType::IVec2)),
+                    ],
+                    NativeFunction::TexelFetch(_) => vec![
+                        field(
+                            "coords",
+                            s.fetch_coordinates_type().unwrap_or(NativeType::IVec2),
+                        ),
+                        field("lod", NativeType::Int),
+                    ],
+                    NativeFunction::TextureSize(_) => vec![field("lod", NativeType::Int)],
+                    NativeFunction::TextureGather(_) if s.is_shadow() => vec![
+                        field("coords", s.unpacked_coordinates_type()),
+                        field("refZ", NativeType::Float),
+                    ],
+                    NativeFunction::TextureGather(_) => {
+                        vec![field("coords", s.unpacked_coordinates_type())]
+                    }
+                    NativeFunction::TextureProj(_) => vec![field(
+                        "coords",
+                        s.projective_coordinates_type().unwrap_or(NativeType::Vec4),
+                    )],
+                    NativeFunction::TextureProjLod(_) => vec![
+                        field(
+                            "coords",
+                            s.projective_coordinates_type().unwrap_or(NativeType::Vec4),
+                        ),
+                        field("lod", NativeType::Float),
+                    ],
+                    _ => vec![field("coords", s.coordinates_type())],
+                });
+                Input { fields }
+            }
+            NativeFunction::Transpose(t)
+            | NativeFunction::Inverse(t)
+            | NativeFunction::Determinant(t) => Input {
+                fields: vec![("m".to_string(), InputField::new(NativeType::from(*t)))],
+            },
+            NativeFunction::OuterProduct(t) => Input {
+                fields: vec![
+                    ("c".to_string(), InputField::new(t.column_type())),
+                    ("r".to_string(), InputField::new(t.column_type())),
+                ],
+            },
+            NativeFunction::MatrixCompMult(t) => Input {
+                fields: vec![
+                    ("x".to_string(), InputField::new(NativeType::from(*t))),
+                    ("y".to_string(), InputField::new(NativeType::from(*t))),
+                ],
+            },
         }
     }
 
     /// Retrieves the output data for the native function
+    #[allow(clippy::too_many_lines)]
     pub fn output(&self) -> Output {
         match self {
             NativeFunction::Radians(t)
@@ -440,15 +1246,84 @@ impl NativeFunction {
             | NativeFunction::Normalize(t)
             | NativeFunction::FaceForward(t)
             | NativeFunction::Reflect(t)
-            | NativeFunction::Refract(t) => Output::NativeType(NativeType::from(*t)),
+            | NativeFunction::Refract(t)
+            | NativeFunction::HyperbolicSine(t)
+            | NativeFunction::HyperbolicCosine(t)
+            | NativeFunction::HyperbolicTangent(t)
+            | NativeFunction::HyperbolicArcSine(t)
+            | NativeFunction::HyperbolicArcCosine(t)
+            | NativeFunction::HyperbolicArcTangent(t)
+            | NativeFunction::Truncate(t)
+            | NativeFunction::Round(t)
+            | NativeFunction::RoundEven(t)
+            | NativeFunction::IntBitsToFloat(t)
+            | NativeFunction::UIntBitsToFloat(t)
+            | NativeFunction::FusedMultiplyAdd(t)
+            | NativeFunction::LoadExponent(t) => Output::NativeType(NativeType::from(*t)),
+            NativeFunction::FractionalIntegralParts(t) => {
+                parts_output(self.function_name(), *t, "whole", NativeType::from(*t))
+            }
+            NativeFunction::SignificandExponent(t) => parts_output(
+                self.function_name(),
+                *t,
+                "exp",
+                sized_like(*t, ScalarNativeType::Int),
+            ),
+            NativeFunction::IsNan(t) | NativeFunction::IsInfinite(t) => {
+                Output::NativeType(sized_like(*t, ScalarNativeType::Bool))
+            }
+            NativeFunction::FloatBitsToInt(t) => {
+                Output::NativeType(sized_like(*t, ScalarNativeType::Int))
+            }
+            NativeFunction::FloatBitsToUInt(t) => {
+                Output::NativeType(sized_like(*t, ScalarNativeType::UInt))
+            }
+            NativeFunction::PackUnorm2x16
+            | NativeFunction::PackSnorm2x16
+            | NativeFunction::PackHalf2x16
+            | NativeFunction::PackUnorm4x8
+            | NativeFunction::PackSnorm4x8 => Output::NativeType(NativeType::UInt),
+            NativeFunction::PackDouble2x32 => Output::NativeType(NativeType::Double),
+            NativeFunction::UnpackUnorm2x16
+            | NativeFunction::UnpackSnorm2x16
+            | NativeFunction::UnpackHalf2x16 => Output::NativeType(NativeType::Vec2),
+            NativeFunction::UnpackUnorm4x8 | NativeFunction::UnpackSnorm4x8 => {
+                Output::NativeType(NativeType::Vec4)
+            }
+            NativeFunction::UnpackDouble2x32 => Output::NativeType(NativeType::UVec2),
+            NativeFunction::BitfieldExtract(t)
+            | NativeFunction::BitfieldInsert(t)
+            | NativeFunction::BitfieldReverse(t) => Output::NativeType(NativeType::from(*t)),
+            NativeFunction::BitCount(t)
+            | NativeFunction::FindLsb(t)
+            | NativeFunction::FindMsb(t) => {
+                Output::NativeType(sized_like(*t, ScalarNativeType::Int))
+            }
             NativeFunction::Distance(_)
             | NativeFunction::Length(_)
-            | NativeFunction::DotProduct(_) => Output::NativeType(NativeType::Float),
+            | NativeFunction::DotProduct(_)
+            | NativeFunction::Determinant(_) => Output::NativeType(NativeType::Float),
             NativeFunction::CrossProduct => Output::NativeType(NativeType::Vec3),
             NativeFunction::Texture2d
             | NativeFunction::Texture2dBias
             | NativeFunction::TextureCube
             | NativeFunction::TextureCubeBias => Output::NativeType(NativeType::Vec4),
+            NativeFunction::TextureSize(s) => Output::NativeType(s.size_type()),
+            NativeFunction::TextureGather(s) if s.is_shadow() => {
+                Output::NativeType(NativeType::Vec4)
+            }
+            NativeFunction::Texture(s)
+            | NativeFunction::TextureLod(s)
+            | NativeFunction::TextureGrad(s)
+            | NativeFunction::TextureOffset(s)
+            | NativeFunction::TexelFetch(s)
+            | NativeFunction::TextureGather(s)
+            | NativeFunction::TextureProj(s)
+            | NativeFunction::TextureProjLod(s) => Output::NativeType(s.texel_type()),
+            NativeFunction::Transpose(t)
+            | NativeFunction::Inverse(t)
+            | NativeFunction::OuterProduct(t)
+            | NativeFunction::MatrixCompMult(t) => Output::NativeType(NativeType::from(*t)),
         }
     }
 
@@ -501,5 +1376,566 @@ impl NativeFunction {
         Self::Texture2dBias,
         Self::TextureCube,
         Self::TextureCubeBias,
+        Self::Transpose(MatrixNativeType::Mat4),
+        Self::Inverse(MatrixNativeType::Mat4),
+        Self::Determinant(MatrixNativeType::Mat4),
+        Self::OuterProduct(MatrixNativeType::Mat4),
+        Self::MatrixCompMult(MatrixNativeType::Mat4),
+        Self::HyperbolicSine(FloatingNativeType::Float),
+        Self::HyperbolicCosine(FloatingNativeType::Float),
+        Self::HyperbolicTangent(FloatingNativeType::Float),
+        Self::HyperbolicArcSine(FloatingNativeType::Float),
+        Self::HyperbolicArcCosine(FloatingNativeType::Float),
+        Self::HyperbolicArcTangent(FloatingNativeType::Float),
+        Self::Truncate(FloatingNativeType::Float),
+        Self::Round(FloatingNativeType::Float),
+        Self::RoundEven(FloatingNativeType::Float),
+        Self::FractionalIntegralParts(FloatingNativeType::Float),
+        Self::IsNan(FloatingNativeType::Float),
+        Self::IsInfinite(FloatingNativeType::Float),
+        Self::FloatBitsToInt(FloatingNativeType::Float),
+        Self::FloatBitsToUInt(FloatingNativeType::Float),
+        Self::IntBitsToFloat(FloatingNativeType::Float),
+        Self::UIntBitsToFloat(FloatingNativeType::Float),
+        Self::FusedMultiplyAdd(FloatingNativeType::Float),
+        Self::SignificandExponent(FloatingNativeType::Float),
+        Self::LoadExponent(FloatingNativeType::Float),
+        Self::PackUnorm2x16,
+        Self::PackSnorm2x16,
+        Self::PackHalf2x16,
+        Self::PackUnorm4x8,
+        Self::PackSnorm4x8,
+        Self::PackDouble2x32,
+        Self::UnpackUnorm2x16,
+        Self::UnpackSnorm2x16,
+        Self::UnpackHalf2x16,
+        Self::UnpackUnorm4x8,
+        Self::UnpackSnorm4x8,
+        Self::UnpackDouble2x32,
+        Self::BitfieldExtract(IntegerNativeType::Int),
+        Self::BitfieldInsert(IntegerNativeType::Int),
+        Self::BitfieldReverse(IntegerNativeType::Int),
+        Self::BitCount(IntegerNativeType::Int),
+        Self::FindLsb(IntegerNativeType::Int),
+        Self::FindMsb(IntegerNativeType::Int),
+        Self::Texture(SamplerNativeType::Sampler2d),
+        Self::TextureLod(SamplerNativeType::Sampler2d),
+        Self::TextureGrad(SamplerNativeType::Sampler2d),
+        Self::TextureOffset(SamplerNativeType::Sampler2d),
+        Self::TexelFetch(SamplerNativeType::Sampler2d),
+        Self::TextureSize(SamplerNativeType::Sampler2d),
+        Self::TextureGather(SamplerNativeType::Sampler2d),
+        Self::TextureProj(SamplerNativeType::Sampler2d),
+        Self::TextureProjLod(SamplerNativeType::Sampler2d),
     ];
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(count: usize) -> Vec<String> {
+        ["a", "b", "c", "d"][..count]
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    fn assert_calls(function: &NativeFunction, glsl: &str, wgsl: &str) {
+        let field_values = values(function.input().fields.len());
+        assert_eq!(
+            function.glsl_call(&field_values, GlslVersion::Glsl450),
+            glsl,
+            "{:?}",
+            function
+        );
+        assert_eq!(
+            function
+                .wgsl_call(&field_values, ShaderType::Fragment)
+                .unwrap(),
+            wgsl,
+            "{:?}",
+            function
+        );
+    }
+
+    #[test]
+    fn generates_floating_calls() {
+        use FloatingNativeType::{Float, Vec3};
+
+        for (function, glsl, wgsl) in [
+            (NativeFunction::HyperbolicSine(Vec3), "sinh(a)", "sinh(a)"),
+            (NativeFunction::HyperbolicCosine(Vec3), "cosh(a)", "cosh(a)"),
+            (
+                NativeFunction::HyperbolicTangent(Vec3),
+                "tanh(a)",
+                "tanh(a)",
+            ),
+            (
+                NativeFunction::HyperbolicArcSine(Float),
+                "asinh(a)",
+                "asinh(a)",
+            ),
+            (
+                NativeFunction::HyperbolicArcCosine(Float),
+                "acosh(a)",
+                "acosh(a)",
+            ),
+            (
+                NativeFunction::HyperbolicArcTangent(Float),
+                "atanh(a)",
+                "atanh(a)",
+            ),
+            (NativeFunction::Truncate(Vec3), "trunc(a)", "trunc(a)"),
+            (NativeFunction::Round(Vec3), "round(a)", "round(a)"),
+            (NativeFunction::RoundEven(Vec3), "roundEven(a)", "round(a)"),
+            (
+                NativeFunction::FractionalIntegralParts(Vec3),
+                "modf(a)",
+                "modf(a)",
+            ),
+            (
+                NativeFunction::IsNan(Float),
+                "isnan(a)",
+                "((bitcast<u32>(a) & u32(0x7fffffffu)) > u32(0x7f800000u))",
+            ),
+            (
+                NativeFunction::IsInfinite(Vec3),
+                "isinf(a)",
+                "((bitcast<vec3<u32>>(a) & vec3<u32>(0x7fffffffu)) == vec3<u32>(0x7f800000u))",
+            ),
+            (
+                NativeFunction::FloatBitsToInt(Vec3),
+                "floatBitsToInt(a)",
+                "bitcast<vec3<i32>>(a)",
+            ),
+            (
+                NativeFunction::FloatBitsToUInt(Float),
+                "floatBitsToUint(a)",
+                "bitcast<u32>(a)",
+            ),
+            (
+                NativeFunction::IntBitsToFloat(Vec3),
+                "intBitsToFloat(a)",
+                "bitcast<vec3<f32>>(a)",
+            ),
+            (
+                NativeFunction::UIntBitsToFloat(Float),
+                "uintBitsToFloat(a)",
+                "bitcast<f32>(a)",
+            ),
+            (
+                NativeFunction::FusedMultiplyAdd(Vec3),
+                "fma(a, b, c)",
+                "fma(a, b, c)",
+            ),
+            (
+                NativeFunction::SignificandExponent(Float),
+                "frexp(a)",
+                "frexp(a)",
+            ),
+            (
+                NativeFunction::LoadExponent(Vec3),
+                "ldexp(a, b)",
+                "ldexp(a, b)",
+            ),
+        ] {
+            assert_calls(&function, glsl, wgsl);
+        }
+    }
+
+    #[test]
+    fn generates_packing_calls() {
+        for (function, glsl, wgsl) in [
+            (
+                NativeFunction::PackUnorm2x16,
+                "packUnorm2x16(a)",
+                "pack2x16unorm(a)",
+            ),
+            (
+                NativeFunction::PackSnorm2x16,
+                "packSnorm2x16(a)",
+                "pack2x16snorm(a)",
+            ),
+            (
+                NativeFunction::PackHalf2x16,
+                "packHalf2x16(a)",
+                "pack2x16float(a)",
+            ),
+            (
+                NativeFunction::PackUnorm4x8,
+                "packUnorm4x8(a)",
+                "pack4x8unorm(a)",
+            ),
+            (
+                NativeFunction::PackSnorm4x8,
+                "packSnorm4x8(a)",
+                "pack4x8snorm(a)",
+            ),
+            (
+                NativeFunction::UnpackUnorm2x16,
+                "unpackUnorm2x16(a)",
+                "unpack2x16unorm(a)",
+            ),
+            (
+                NativeFunction::UnpackSnorm2x16,
+                "unpackSnorm2x16(a)",
+                "unpack2x16snorm(a)",
+            ),
+            (
+                NativeFunction::UnpackHalf2x16,
+                "unpackHalf2x16(a)",
+                "unpack2x16float(a)",
+            ),
+            (
+                NativeFunction::UnpackUnorm4x8,
+                "unpackUnorm4x8(a)",
+                "unpack4x8unorm(a)",
+            ),
+            (
+                NativeFunction::UnpackSnorm4x8,
+                "unpackSnorm4x8(a)",
+                "unpack4x8snorm(a)",
+            ),
+        ] {
+            assert_calls(&function, glsl, wgsl);
+        }
+        for function in [
+            NativeFunction::PackDouble2x32,
+            NativeFunction::UnpackDouble2x32,
+        ] {
+            assert_eq!(
+                function.glsl_call(&values(1), GlslVersion::Glsl450),
+                format!("{}(a)", function.function_name())
+            );
+            assert!(matches!(
+                function.wgsl_call(&values(1), ShaderType::Fragment),
+                Err(ShadyError::UnsupportedNativeFunction { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn generates_integer_calls() {
+        use IntegerNativeType::{Int, UVec2};
+
+        for (function, glsl, wgsl) in [
+            (
+                NativeFunction::BitfieldExtract(UVec2),
+                "bitfieldExtract(a, b, c)",
+                "extractBits(a, u32(b), u32(c))",
+            ),
+            (
+                NativeFunction::BitfieldInsert(Int),
+                "bitfieldInsert(a, b, c, d)",
+                "insertBits(a, b, u32(c), u32(d))",
+            ),
+            (
+                NativeFunction::BitfieldReverse(Int),
+                "bitfieldReverse(a)",
+                "reverseBits(a)",
+            ),
+            (
+                NativeFunction::BitCount(UVec2),
+                "bitCount(a)",
+                "vec2<i32>(countOneBits(a))",
+            ),
+            (
+                NativeFunction::FindLsb(Int),
+                "findLSB(a)",
+                "i32(firstTrailingBit(a))",
+            ),
+            (
+                NativeFunction::FindMsb(UVec2),
+                "findMSB(a)",
+                "vec2<i32>(firstLeadingBit(a))",
+            ),
+        ] {
+            assert_calls(&function, glsl, wgsl);
+        }
+    }
+
+    #[test]
+    fn generates_texture_calls() {
+        use SamplerNativeType::{
+            ISampler2dArray, Sampler2d, Sampler2dArray, Sampler2dArrayShadow, Sampler2dShadow,
+            Sampler3d, SamplerCubeShadow, USampler3d,
+        };
+
+        for (function, glsl, wgsl) in [
+            (
+                NativeFunction::Texture(Sampler2dArray),
+                "texture(a, b)",
+                "textureSample(a, a_sampler, b.xy, i32(floor(b.z + 0.5)))",
+            ),
+            (
+                NativeFunction::Texture(SamplerCubeShadow),
+                "texture(a, b)",
+                "textureSampleCompare(a, a_sampler, b.xyz, b.w)",
+            ),
+            (
+                NativeFunction::Texture(Sampler2dArrayShadow),
+                "texture(a, b)",
+                "textureSampleCompare(a, a_sampler, b.xy, i32(floor(b.z + 0.5)), b.w)",
+            ),
+            (
+                NativeFunction::TextureLod(Sampler3d),
+                "textureLod(a, b, c)",
+                "textureSampleLevel(a, a_sampler, b, c)",
+            ),
+            (
+                NativeFunction::TextureGrad(Sampler2d),
+                "textureGrad(a, b, c, d)",
+                "textureSampleGrad(a, a_sampler, b, c, d)",
+            ),
+            (
+                NativeFunction::TextureOffset(Sampler2dShadow),
+                "textureOffset(a, b, c)",
+                "textureSampleCompare(a, a_sampler, b.xy, b.z, c)",
+            ),
+            (
+                NativeFunction::TexelFetch(ISampler2dArray),
+                "texelFetch(a, b, c)",
+                "textureLoad(a, b.xy, b.z, c)",
+            ),
+            (
+                NativeFunction::TextureSize(USampler3d),
+                "textureSize(a, b)",
+                "vec3<i32>(textureDimensions(a, b))",
+            ),
+            (
+                NativeFunction::TextureSize(Sampler2dArrayShadow),
+                "textureSize(a, b)",
+                "vec3<i32>(vec2<i32>(textureDimensions(a, b)), i32(textureNumLayers(a)))",
+            ),
+            (
+                NativeFunction::TextureGather(Sampler2d),
+                "textureGather(a, b)",
+                "textureGather(0, a, a_sampler, b)",
+            ),
+            (
+                NativeFunction::TextureGather(Sampler2dShadow),
+                "textureGather(a, b, c)",
+                "textureGatherCompare(a, a_sampler, b, c)",
+            ),
+            (
+                NativeFunction::TextureProj(Sampler2dShadow),
+                "textureProj(a, b)",
+                "textureSampleCompare(a, a_sampler, b.xy / b.w, b.z / b.w)",
+            ),
+            (
+                NativeFunction::TextureProjLod(Sampler2d),
+                "textureProjLod(a, b, c)",
+                "textureSampleLevel(a, a_sampler, b.xy / b.z, c)",
+            ),
+        ] {
+            assert_calls(&function, glsl, wgsl);
+        }
+        for function in [
+            NativeFunction::Texture(SamplerNativeType::ISampler2d),
+            NativeFunction::TextureLod(Sampler2dShadow),
+            NativeFunction::TextureOffset(SamplerNativeType::SamplerCube),
+        ] {
+            let field_values = values(function.input().fields.len());
+            assert!(
+                function
+                    .wgsl_call(&field_values, ShaderType::Fragment)
+                    .is_err(),
+                "{:?}",
+                function
+            );
+        }
+    }
+
+    #[test]
+    fn generates_vertex_texture_calls() {
+        use SamplerNativeType::{Sampler2d, Sampler2dArray, Sampler2dShadow, Sampler3d};
+
+        for (function, wgsl) in [
+            (
+                NativeFunction::Texture2d,
+                "textureSampleLevel(a, a_sampler, b, 0.0)",
+            ),
+            (
+                NativeFunction::TextureCube,
+                "textureSampleLevel(a, a_sampler, b, 0.0)",
+            ),
+            (
+                NativeFunction::Texture(Sampler2dArray),
+                "textureSampleLevel(a, a_sampler, b.xy, i32(floor(b.z + 0.5)), 0.0)",
+            ),
+            (
+                NativeFunction::Texture(Sampler2dShadow),
+                "textureSampleCompareLevel(a, a_sampler, b.xy, b.z)",
+            ),
+            (
+                NativeFunction::TextureOffset(Sampler2d),
+                "textureSampleLevel(a, a_sampler, b, 0.0, c)",
+            ),
+            (
+                NativeFunction::TextureOffset(Sampler2dShadow),
+                "textureSampleCompareLevel(a, a_sampler, b.xy, b.z, c)",
+            ),
+            (
+                NativeFunction::TextureProj(Sampler3d),
+                "textureSampleLevel(a, a_sampler, b.xyz / b.w, 0.0)",
+            ),
+            (
+                NativeFunction::TextureLod(Sampler2d),
+                "textureSampleLevel(a, a_sampler, b, c)",
+            ),
+        ] {
+            let field_values = values(function.input().fields.len());
+            assert_eq!(
+                function
+                    .wgsl_call(&field_values, ShaderType::Vertex)
+                    .unwrap(),
+                wgsl,
+                "{:?}",
+                function
+            );
+        }
+        for function in [
+            NativeFunction::Texture2dBias,
+            NativeFunction::TextureCubeBias,
+        ] {
+            assert!(matches!(
+                function.wgsl_call(&values(3), ShaderType::Vertex),
+                Err(ShadyError::UnsupportedNativeFunction { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn types_texture_lookups() {
+        let input_types = |function: NativeFunction| -> Vec<NativeType> {
+            function
+                .input()
+                .fields
+                .into_iter()
+                .map(|(_, field)| field.glsl_type)
+                .collect()
+        };
+        let output_type = |function: NativeFunction| function.output().glsl_type();
+        assert_eq!(
+            input_types(NativeFunction::TexelFetch(SamplerNativeType::USampler3d)),
+            vec![NativeType::USampler3d, NativeType::IVec3, NativeType::Int]
+        );
+        assert_eq!(
+            input_types(NativeFunction::TextureGather(
+                SamplerNativeType::SamplerCubeShadow
+            )),
+            vec![
+                NativeType::SamplerCubeShadow,
+                NativeType::Vec3,
+                NativeType::Float
+            ]
+        );
+        assert_eq!(
+            output_type(NativeFunction::TextureSize(SamplerNativeType::Sampler2d)),
+            "ivec2"
+        );
+        assert_eq!(
+            output_type(NativeFunction::Texture(SamplerNativeType::ISamplerCube)),
+            "ivec4"
+        );
+        assert_eq!(
+            output_type(NativeFunction::TextureGather(
+                SamplerNativeType::Sampler2dShadow
+            )),
+            "vec4"
+        );
+        assert!(!NativeFunction::TexelFetch(SamplerNativeType::SamplerCube).accepts_sampler());
+        assert!(!NativeFunction::TextureGather(SamplerNativeType::Sampler3d).accepts_sampler());
+    }
+
+    #[test]
+    fn types_overloads() {
+        let input_types = |function: NativeFunction| -> Vec<NativeType> {
+            function
+                .input()
+                .fields
+                .into_iter()
+                .map(|(_, field)| field.glsl_type)
+                .collect()
+        };
+        let output_type = |function: NativeFunction| function.output().glsl_type();
+        assert_eq!(
+            output_type(NativeFunction::IsNan(FloatingNativeType::Vec4)),
+            "bvec4"
+        );
+        assert_eq!(
+            input_types(NativeFunction::IntBitsToFloat(FloatingNativeType::Vec2)),
+            vec![NativeType::IVec2]
+        );
+        assert_eq!(
+            input_types(NativeFunction::LoadExponent(FloatingNativeType::Vec3)),
+            vec![NativeType::Vec3, NativeType::IVec3]
+        );
+        assert_eq!(
+            output_type(NativeFunction::BitCount(IntegerNativeType::UVec3)),
+            "ivec3"
+        );
+        assert_eq!(
+            output_type(NativeFunction::BitfieldExtract(IntegerNativeType::UVec3)),
+            "uvec3"
+        );
+        assert_eq!(
+            NativeFunction::SignificandExponent(FloatingNativeType::Vec2)
+                .output()
+                .fields()
+                .field_names(),
+            vec![
+                ("fract".to_string(), NativeType::Vec2),
+                ("exp".to_string(), NativeType::IVec2),
+            ]
+        );
+    }
+
+    #[test]
+    fn gates_glsl_versions() {
+        let function_versions = |function: NativeFunction| -> Vec<GlslVersion> {
+            GlslVersion::VARIANTS
+                .iter()
+                .copied()
+                .filter(|version| version.supports_function(&function))
+                .collect()
+        };
+        assert_eq!(
+            function_versions(NativeFunction::Round(FloatingNativeType::Float)),
+            vec![
+                GlslVersion::Glsl330,
+                GlslVersion::Glsl450,
+                GlslVersion::GlslEs300
+            ]
+        );
+        assert_eq!(
+            function_versions(NativeFunction::PackHalf2x16),
+            vec![GlslVersion::Glsl450, GlslVersion::GlslEs300]
+        );
+        assert_eq!(
+            function_versions(NativeFunction::BitCount(IntegerNativeType::Int)),
+            vec![GlslVersion::Glsl450]
+        );
+        assert_eq!(
+            function_versions(NativeFunction::Sine(FloatingNativeType::Float)),
+            GlslVersion::VARIANTS
+        );
+        assert_eq!(
+            function_versions(NativeFunction::TextureSize(SamplerNativeType::Sampler2d)),
+            vec![
+                GlslVersion::Glsl330,
+                GlslVersion::Glsl450,
+                GlslVersion::GlslEs300
+            ]
+        );
+        assert_eq!(
+            function_versions(NativeFunction::TextureGather(SamplerNativeType::Sampler2d)),
+            vec![GlslVersion::Glsl450]
+        );
+        assert!(
+            function_versions(NativeFunction::TextureProj(SamplerNativeType::SamplerCube))
+                .is_empty()
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{Input, InputField, NativeType, NumericScalarNativeType, Output};
+use crate::{
+    BooleanVectorNativeType, Input, InputField, IntegerNativeType, MatrixNativeType, NativeType,
+    NumericScalarNativeType, NumericVectorNativeType, Output, ScalarNativeType, VectorNativeType,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,8 +16,12 @@ pub enum NativeOperation {
     Add(NativeType),
     /// Sub operation: `a - b`
     Sub(NativeType),
-    /// Mul operation: `a * b`
+    /// Mul operation: `a * b`, multiplies matrices with matrices or scalars
     Mul(NativeType),
+    /// Matrix and column vector product: `m * v`
+    MatrixVectorMul(MatrixNativeType),
+    /// Row vector and matrix product: `v * m`
+    VectorMatrixMul(MatrixNativeType),
     /// Div operation: `a / b`
     Div(NativeType),
     /// Selection operation: ` c ? a : b`, `c` is a boolean
@@ -33,6 +40,66 @@ pub enum NativeOperation {
     Or,
     /// XOr operation: `a ^^ b`, takes and return booleans
     Xor,
+    /// Not equals operation: `a != b`, returns a boolean
+    NotEquals(NativeType),
+    /// Less than operation: `a < b`, returns a boolean
+    LessThan(NumericScalarNativeType),
+    /// Less or equal operation: `a <= b`, returns a boolean
+    LessThanEqual(NumericScalarNativeType),
+    /// Integer modulo operation: `a % b`
+    Modulo(IntegerNativeType),
+    /// Bitwise and operation: `a & b`
+    BitAnd(IntegerNativeType),
+    /// Bitwise or operation: `a | b`
+    BitOr(IntegerNativeType),
+    /// Bitwise exclusive or operation: `a ^ b`
+    BitXor(IntegerNativeType),
+    /// Bitwise complement operation: `~a`
+    BitNot(IntegerNativeType),
+    /// Left shift operation: `a << b`
+    LeftShift(IntegerNativeType),
+    /// Right shift operation: `a >> b`, sign extended for signed integers
+    RightShift(IntegerNativeType),
+    /// Component wise equals function: `equal(a, b)`, returns a boolean vector
+    VectorEquals(VectorNativeType),
+    /// Component wise not equals function: `notEqual(a, b)`, returns a boolean vector
+    VectorNotEquals(VectorNativeType),
+    /// Component wise less than function: `lessThan(a, b)`, returns a boolean vector
+    VectorLessThan(NumericVectorNativeType),
+    /// Component wise less or equal function: `lessThanEqual(a, b)`, returns a boolean vector
+    VectorLessThanEqual(NumericVectorNativeType),
+    /// Component wise greater than function: `greaterThan(a, b)`, returns a boolean vector
+    VectorGreaterThan(NumericVectorNativeType),
+    /// Component wise greater or equal function: `greaterThanEqual(a, b)`, returns a boolean
+    /// vector
+    VectorGreaterThanEqual(NumericVectorNativeType),
+    /// Any function: `any(v)`, returns true if any component of the boolean vector is true
+    Any(BooleanVectorNativeType),
+    /// All function: `all(v)`, returns true if all components of the boolean vector are true
+    All(BooleanVectorNativeType),
+    /// Component wise not function: `not(v)`, takes and return boolean vectors
+    VectorNo(BooleanVectorNativeType),
+}
+
+/// WGSL has no mixed vector and scalar bitwise operations nor signed shift amounts: converts the
+/// right operand `value` to a `component` vector of the same size as `t`, or to a `component`
+/// scalar if `scalar` is set
+fn wgsl_right_operand(
+    t: IntegerNativeType,
+    component: ScalarNativeType,
+    value: &str,
+    scalar: bool,
+) -> String {
+    let component = if component == ScalarNativeType::UInt {
+        "u32"
+    } else {
+        "i32"
+    };
+    match NativeType::from(t).vector_size() {
+        Some(size) => format!("vec{}<{}>({})", size, component, value),
+        None if scalar => format!("{}({})", component, value),
+        None => value.to_string(),
+    }
 }
 
 impl NativeOperation {
@@ -47,13 +114,36 @@ impl NativeOperation {
             | NativeOperation::Mul(t)
             | NativeOperation::Div(t)
             | NativeOperation::Selection(t) => Output::NativeType(*t),
+            NativeOperation::Modulo(t)
+            | NativeOperation::BitAnd(t)
+            | NativeOperation::BitOr(t)
+            | NativeOperation::BitXor(t)
+            | NativeOperation::BitNot(t)
+            | NativeOperation::LeftShift(t)
+            | NativeOperation::RightShift(t) => Output::NativeType(t.native_type()),
+            NativeOperation::VectorEquals(t) | NativeOperation::VectorNotEquals(t) => {
+                Output::NativeType(t.boolean_vector())
+            }
+            NativeOperation::VectorLessThan(t)
+            | NativeOperation::VectorLessThanEqual(t)
+            | NativeOperation::VectorGreaterThan(t)
+            | NativeOperation::VectorGreaterThanEqual(t) => Output::NativeType(t.boolean_vector()),
+            NativeOperation::VectorNo(t) => Output::NativeType(t.native_type()),
+            NativeOperation::MatrixVectorMul(t) | NativeOperation::VectorMatrixMul(t) => {
+                Output::NativeType(t.column_type())
+            }
             NativeOperation::And
             | NativeOperation::Or
             | NativeOperation::Xor
             | NativeOperation::No
             | NativeOperation::Equals(_)
             | NativeOperation::GreaterThan(_)
-            | NativeOperation::GreaterThanEqual(_) => Output::NativeType(NativeType::Bool),
+            | NativeOperation::GreaterThanEqual(_)
+            | NativeOperation::NotEquals(_)
+            | NativeOperation::LessThan(_)
+            | NativeOperation::LessThanEqual(_)
+            | NativeOperation::Any(_)
+            | NativeOperation::All(_) => Output::NativeType(NativeType::Bool),
         }
     }
 
@@ -74,13 +164,28 @@ impl NativeOperation {
                     ("b".to_string(), InputField::new_tolerant(*t)),
                 ],
             },
-            NativeOperation::Equals(t) => Input {
+            NativeOperation::MatrixVectorMul(t) => Input {
+                fields: vec![
+                    ("m".to_string(), InputField::new((*t).into())),
+                    ("v".to_string(), InputField::new(t.column_type())),
+                ],
+            },
+            NativeOperation::VectorMatrixMul(t) => Input {
+                fields: vec![
+                    ("v".to_string(), InputField::new(t.column_type())),
+                    ("m".to_string(), InputField::new((*t).into())),
+                ],
+            },
+            NativeOperation::Equals(t) | NativeOperation::NotEquals(t) => Input {
                 fields: vec![
                     ("a".to_string(), InputField::new(*t)),
                     ("b".to_string(), InputField::new(*t)),
                 ],
             },
-            NativeOperation::GreaterThan(t) | NativeOperation::GreaterThanEqual(t) => Input {
+            NativeOperation::GreaterThan(t)
+            | NativeOperation::GreaterThanEqual(t)
+            | NativeOperation::LessThan(t)
+            | NativeOperation::LessThanEqual(t) => Input {
                 fields: vec![
                     ("a".to_string(), InputField::new((*t).into())),
                     ("b".to_string(), InputField::new((*t).into())),
@@ -102,6 +207,40 @@ impl NativeOperation {
                     ("b".to_string(), InputField::new(*t)),
                 ],
             },
+            NativeOperation::Modulo(t)
+            | NativeOperation::BitAnd(t)
+            | NativeOperation::BitOr(t)
+            | NativeOperation::BitXor(t)
+            | NativeOperation::LeftShift(t)
+            | NativeOperation::RightShift(t) => Input {
+                fields: vec![
+                    ("a".to_string(), InputField::new((*t).into())),
+                    ("b".to_string(), InputField::new_tolerant((*t).into())),
+                ],
+            },
+            NativeOperation::BitNot(t) => Input {
+                fields: vec![("i".to_string(), InputField::new((*t).into()))],
+            },
+            NativeOperation::VectorEquals(t) | NativeOperation::VectorNotEquals(t) => Input {
+                fields: vec![
+                    ("a".to_string(), InputField::new((*t).into())),
+                    ("b".to_string(), InputField::new((*t).into())),
+                ],
+            },
+            NativeOperation::VectorLessThan(t)
+            | NativeOperation::VectorLessThanEqual(t)
+            | NativeOperation::VectorGreaterThan(t)
+            | NativeOperation::VectorGreaterThanEqual(t) => Input {
+                fields: vec![
+                    ("a".to_string(), InputField::new((*t).into())),
+                    ("b".to_string(), InputField::new((*t).into())),
+                ],
+            },
+            NativeOperation::Any(t) | NativeOperation::All(t) | NativeOperation::VectorNo(t) => {
+                Input {
+                    fields: vec![("v".to_string(), InputField::new((*t).into()))],
+                }
+            }
         }
     }
 
@@ -114,7 +253,9 @@ impl NativeOperation {
             NativeOperation::No => format!("!{}", field_values.first().unwrap()),
             NativeOperation::Add(_) => field_values.join(" + "),
             NativeOperation::Sub(_) => field_values.join(" - "),
-            NativeOperation::Mul(_) => field_values.join(" * "),
+            NativeOperation::Mul(_)
+            | NativeOperation::MatrixVectorMul(_)
+            | NativeOperation::VectorMatrixMul(_) => field_values.join(" * "),
             NativeOperation::Div(_) => field_values.join(" / "),
             NativeOperation::And => field_values.join(" && "),
             NativeOperation::Or => field_values.join(" || "),
@@ -126,6 +267,127 @@ impl NativeOperation {
             NativeOperation::Equals(_) => field_values.join(" == "),
             NativeOperation::GreaterThan(_) => field_values.join(" > "),
             NativeOperation::GreaterThanEqual(_) => field_values.join(" >= "),
+            NativeOperation::NotEquals(_) => field_values.join(" != "),
+            NativeOperation::LessThan(_) => field_values.join(" < "),
+            NativeOperation::LessThanEqual(_) => field_values.join(" <= "),
+            NativeOperation::Modulo(_) => field_values.join(" % "),
+            NativeOperation::BitAnd(_) => field_values.join(" & "),
+            NativeOperation::BitOr(_) => field_values.join(" | "),
+            NativeOperation::BitXor(_) => field_values.join(" ^ "),
+            NativeOperation::BitNot(_) => format!("~{}", field_values.first().unwrap()),
+            NativeOperation::LeftShift(_) => field_values.join(" << "),
+            NativeOperation::RightShift(_) => field_values.join(" >> "),
+            NativeOperation::VectorEquals(_) => format!("equal({})", field_values.join(", ")),
+            NativeOperation::VectorNotEquals(_) => {
+                format!("notEqual({})", field_values.join(", "))
+            }
+            NativeOperation::VectorLessThan(_) => {
+                format!("lessThan({})", field_values.join(", "))
+            }
+            NativeOperation::VectorLessThanEqual(_) => {
+                format!("lessThanEqual({})", field_values.join(", "))
+            }
+            NativeOperation::VectorGreaterThan(_) => {
+                format!("greaterThan({})", field_values.join(", "))
+            }
+            NativeOperation::VectorGreaterThanEqual(_) => {
+                format!("greaterThanEqual({})", field_values.join(", "))
+            }
+            NativeOperation::Any(_) => format!("any({})", field_values.first().unwrap()),
+            NativeOperation::All(_) => format!("all({})", field_values.first().unwrap()),
+            NativeOperation::VectorNo(_) => format!("not({})", field_values.first().unwrap()),
+        }
+    }
+
+    /// Outputs the operation as WGSL code. WGSL has no matrix increment, division and
+    /// comparisons, they are applied column by column
+    pub fn wgsl_operation(&self, field_values: &[String]) -> String {
+        // Expressions of every column index of a matrix operand
+        let columns = |t: &NativeType, column: &dyn Fn(usize) -> String| -> Vec<String> {
+            (0..t.matrix_type().map_or(0, MatrixNativeType::size))
+                .map(column)
+                .collect()
+        };
+        let matrix = |columns: Vec<String>| {
+            format!("mat{0}x{0}<f32>({1})", columns.len(), columns.join(", "))
+        };
+        match self {
+            NativeOperation::Inc(t) | NativeOperation::Dec(t) if t.is_matrix() => {
+                let operator = if matches!(self, NativeOperation::Inc(_)) {
+                    "+"
+                } else {
+                    "-"
+                };
+                matrix(columns(t, &|i| {
+                    format!("{}[{}] {} 1.0", field_values[0], i, operator)
+                }))
+            }
+            NativeOperation::Div(t) if t.is_matrix() => matrix(columns(t, &|i| {
+                format!("{0}[{2}] / {1}[{2}]", field_values[0], field_values[1], i)
+            })),
+            NativeOperation::Equals(t) if t.is_matrix() => format!(
+                "({})",
+                columns(t, &|i| {
+                    format!(
+                        "all({0}[{2}] == {1}[{2}])",
+                        field_values[0], field_values[1], i
+                    )
+                })
+                .join(" && ")
+            ),
+            NativeOperation::NotEquals(t) if t.is_matrix() => format!(
+                "({})",
+                columns(t, &|i| {
+                    format!(
+                        "any({0}[{2}] != {1}[{2}])",
+                        field_values[0], field_values[1], i
+                    )
+                })
+                .join(" || ")
+            ),
+            NativeOperation::Inc(_) => format!("{} + 1", field_values.first().unwrap()),
+            NativeOperation::Dec(_) => format!("{} - 1", field_values.first().unwrap()),
+            NativeOperation::Selection(_) => format!(
+                "select({}, {}, {})",
+                field_values[2], field_values[1], field_values[0]
+            ),
+            NativeOperation::Xor | NativeOperation::VectorNotEquals(_) => field_values.join(" != "),
+            NativeOperation::Equals(t) if t.is_vector() => {
+                format!("all({})", field_values.join(" == "))
+            }
+            NativeOperation::NotEquals(t) if t.is_vector() => {
+                format!("any({})", field_values.join(" != "))
+            }
+            NativeOperation::BitAnd(t) | NativeOperation::BitOr(t) | NativeOperation::BitXor(t) => {
+                let component = NativeType::from(*t).component_type().unwrap_or_default();
+                format!(
+                    "{} {} {}",
+                    field_values[0],
+                    match self {
+                        NativeOperation::BitAnd(_) => "&",
+                        NativeOperation::BitOr(_) => "|",
+                        _ => "^",
+                    },
+                    wgsl_right_operand(*t, component, &field_values[1], false)
+                )
+            }
+            NativeOperation::LeftShift(t) | NativeOperation::RightShift(t) => format!(
+                "{} {} {}",
+                field_values[0],
+                if matches!(self, NativeOperation::LeftShift(_)) {
+                    "<<"
+                } else {
+                    ">>"
+                },
+                wgsl_right_operand(*t, ScalarNativeType::UInt, &field_values[1], true)
+            ),
+            NativeOperation::VectorEquals(_) => field_values.join(" == "),
+            NativeOperation::VectorLessThan(_) => field_values.join(" < "),
+            NativeOperation::VectorLessThanEqual(_) => field_values.join(" <= "),
+            NativeOperation::VectorGreaterThan(_) => field_values.join(" > "),
+            NativeOperation::VectorGreaterThanEqual(_) => field_values.join(" >= "),
+            NativeOperation::VectorNo(_) => format!("!{}", field_values.first().unwrap()),
+            _ => self.glsl_operation(field_values),
         }
     }
 
@@ -138,6 +400,8 @@ impl NativeOperation {
             NativeOperation::Sub(t) => format!("{0} - {0}", t),
             NativeOperation::Mul(t) => format!("{0} * {0}", t),
             NativeOperation::Div(t) => format!("{0} / {0}", t),
+            NativeOperation::MatrixVectorMul(t) => format!("{} * {}", t, t.column_type()),
+            NativeOperation::VectorMatrixMul(t) => format!("{} * {}", t.column_type(), t),
             NativeOperation::No => "NO".to_string(),
             NativeOperation::And => "AND".to_string(),
             NativeOperation::Or => "OR".to_string(),
@@ -146,6 +410,25 @@ impl NativeOperation {
             NativeOperation::Equals(t) => format!("{0} == {0}", t),
             NativeOperation::GreaterThan(t) => format!("{0} > {0}", t),
             NativeOperation::GreaterThanEqual(t) => format!("{0} >= {0}", t),
+            NativeOperation::NotEquals(t) => format!("{0} != {0}", t),
+            NativeOperation::LessThan(t) => format!("{0} < {0}", t),
+            NativeOperation::LessThanEqual(t) => format!("{0} <= {0}", t),
+            NativeOperation::Modulo(t) => format!("{0} % {0}", t),
+            NativeOperation::BitAnd(t) => format!("{0} & {0}", t),
+            NativeOperation::BitOr(t) => format!("{0} | {0}", t),
+            NativeOperation::BitXor(t) => format!("{0} ^ {0}", t),
+            NativeOperation::BitNot(t) => format!("~{}", t),
+            NativeOperation::LeftShift(t) => format!("{0} << {0}", t),
+            NativeOperation::RightShift(t) => format!("{0} >> {0}", t),
+            NativeOperation::VectorEquals(t) => format!("equal({0}, {0})", t),
+            NativeOperation::VectorNotEquals(t) => format!("notEqual({0}, {0})", t),
+            NativeOperation::VectorLessThan(t) => format!("lessThan({0}, {0})", t),
+            NativeOperation::VectorLessThanEqual(t) => format!("lessThanEqual({0}, {0})", t),
+            NativeOperation::VectorGreaterThan(t) => format!("greaterThan({0}, {0})", t),
+            NativeOperation::VectorGreaterThanEqual(t) => format!("greaterThanEqual({0}, {0})", t),
+            NativeOperation::Any(t) => format!("any({})", t),
+            NativeOperation::All(t) => format!("all({})", t),
+            NativeOperation::VectorNo(t) => format!("not({})", t),
         }
     }
 
@@ -159,6 +442,8 @@ impl NativeOperation {
             NativeOperation::Sub(_) => "a - b",
             NativeOperation::Mul(_) => "a * b",
             NativeOperation::Div(_) => "a / b",
+            NativeOperation::MatrixVectorMul(_) => "m * v",
+            NativeOperation::VectorMatrixMul(_) => "v * m",
             NativeOperation::No => "NO",
             NativeOperation::And => "AND",
             NativeOperation::Or => "OR",
@@ -167,6 +452,25 @@ impl NativeOperation {
             NativeOperation::Equals(_) => "a == b",
             NativeOperation::GreaterThan(_) => " a > b",
             NativeOperation::GreaterThanEqual(_) => " a >= b",
+            NativeOperation::NotEquals(_) => "a != b",
+            NativeOperation::LessThan(_) => "a < b",
+            NativeOperation::LessThanEqual(_) => "a <= b",
+            NativeOperation::Modulo(_) => "a % b",
+            NativeOperation::BitAnd(_) => "a & b",
+            NativeOperation::BitOr(_) => "a | b",
+            NativeOperation::BitXor(_) => "a ^ b",
+            NativeOperation::BitNot(_) => "~a",
+            NativeOperation::LeftShift(_) => "a << b",
+            NativeOperation::RightShift(_) => "a >> b",
+            NativeOperation::VectorEquals(_) => "equal(a, b)",
+            NativeOperation::VectorNotEquals(_) => "notEqual(a, b)",
+            NativeOperation::VectorLessThan(_) => "lessThan(a, b)",
+            NativeOperation::VectorLessThanEqual(_) => "lessThanEqual(a, b)",
+            NativeOperation::VectorGreaterThan(_) => "greaterThan(a, b)",
+            NativeOperation::VectorGreaterThanEqual(_) => "greaterThanEqual(a, b)",
+            NativeOperation::Any(_) => "any(v)",
+            NativeOperation::All(_) => "all(v)",
+            NativeOperation::VectorNo(_) => "not(v)",
         }
     }
 
@@ -179,6 +483,8 @@ impl NativeOperation {
         Self::Sub(NativeType::Float),
         Self::Mul(NativeType::Float),
         Self::Div(NativeType::Float),
+        Self::MatrixVectorMul(MatrixNativeType::Mat4),
+        Self::VectorMatrixMul(MatrixNativeType::Mat4),
         Self::Selection(NativeType::Float),
         Self::Equals(NativeType::Float),
         Self::GreaterThan(NumericScalarNativeType::Float),
@@ -187,5 +493,161 @@ impl NativeOperation {
         Self::And,
         Self::Or,
         Self::Xor,
+        Self::NotEquals(NativeType::Float),
+        Self::LessThan(NumericScalarNativeType::Float),
+        Self::LessThanEqual(NumericScalarNativeType::Float),
+        Self::Modulo(IntegerNativeType::Int),
+        Self::BitAnd(IntegerNativeType::Int),
+        Self::BitOr(IntegerNativeType::Int),
+        Self::BitXor(IntegerNativeType::Int),
+        Self::BitNot(IntegerNativeType::Int),
+        Self::LeftShift(IntegerNativeType::Int),
+        Self::RightShift(IntegerNativeType::Int),
+        Self::VectorEquals(VectorNativeType::Vec4),
+        Self::VectorNotEquals(VectorNativeType::Vec4),
+        Self::VectorLessThan(NumericVectorNativeType::Vec4),
+        Self::VectorLessThanEqual(NumericVectorNativeType::Vec4),
+        Self::VectorGreaterThan(NumericVectorNativeType::Vec4),
+        Self::VectorGreaterThanEqual(NumericVectorNativeType::Vec4),
+        Self::Any(BooleanVectorNativeType::BVec4),
+        Self::All(BooleanVectorNativeType::BVec4),
+        Self::VectorNo(BooleanVectorNativeType::BVec4),
     ];
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GlslVersion;
+
+    #[test]
+    fn generates_operations() {
+        use IntegerNativeType::{IVec3, UInt, UVec2};
+
+        for (operation, glsl, wgsl) in [
+            (
+                NativeOperation::NotEquals(NativeType::Vec2),
+                "a != b",
+                "any(a != b)",
+            ),
+            (
+                NativeOperation::LessThan(NumericScalarNativeType::Int),
+                "a < b",
+                "a < b",
+            ),
+            (NativeOperation::Modulo(IVec3), "a % b", "a % b"),
+            (NativeOperation::BitAnd(UInt), "a & b", "a & b"),
+            (NativeOperation::BitXor(IVec3), "a ^ b", "a ^ vec3<i32>(b)"),
+            (NativeOperation::BitNot(UVec2), "~a", "~a"),
+            (NativeOperation::LeftShift(UInt), "a << b", "a << u32(b)"),
+            (
+                NativeOperation::RightShift(IVec3),
+                "a >> b",
+                "a >> vec3<u32>(b)",
+            ),
+            (
+                NativeOperation::VectorLessThan(NumericVectorNativeType::UVec2),
+                "lessThan(a, b)",
+                "a < b",
+            ),
+            (
+                NativeOperation::VectorEquals(VectorNativeType::BVec3),
+                "equal(a, b)",
+                "a == b",
+            ),
+            (
+                NativeOperation::Any(BooleanVectorNativeType::BVec2),
+                "any(a)",
+                "any(a)",
+            ),
+            (
+                NativeOperation::VectorNo(BooleanVectorNativeType::BVec4),
+                "not(a)",
+                "!a",
+            ),
+            (NativeOperation::Add(NativeType::Mat3), "a + b", "a + b"),
+            (
+                NativeOperation::Div(NativeType::Mat2),
+                "a / b",
+                "mat2x2<f32>(a[0] / b[0], a[1] / b[1])",
+            ),
+            (
+                NativeOperation::Inc(NativeType::Mat2),
+                "a++",
+                "mat2x2<f32>(a[0] + 1.0, a[1] + 1.0)",
+            ),
+            (
+                NativeOperation::Dec(NativeType::Mat3),
+                "a--",
+                "mat3x3<f32>(a[0] - 1.0, a[1] - 1.0, a[2] - 1.0)",
+            ),
+            (
+                NativeOperation::Equals(NativeType::Mat2),
+                "a == b",
+                "(all(a[0] == b[0]) && all(a[1] == b[1]))",
+            ),
+            (
+                NativeOperation::NotEquals(NativeType::Mat3),
+                "a != b",
+                "(any(a[0] != b[0]) || any(a[1] != b[1]) || any(a[2] != b[2]))",
+            ),
+        ] {
+            let field_values: Vec<String> = ["a", "b"][..operation.input().fields.len()]
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            assert_eq!(
+                operation.glsl_operation(&field_values),
+                glsl,
+                "{:?}",
+                operation
+            );
+            assert_eq!(
+                operation.wgsl_operation(&field_values),
+                wgsl,
+                "{:?}",
+                operation
+            );
+        }
+    }
+
+    #[test]
+    fn types_operations() {
+        let output_type = |operation: NativeOperation| operation.output().glsl_type();
+        assert_eq!(
+            output_type(NativeOperation::VectorGreaterThan(
+                NumericVectorNativeType::DVec3
+            )),
+            "bvec3"
+        );
+        assert_eq!(
+            output_type(NativeOperation::All(BooleanVectorNativeType::BVec4)),
+            "bool"
+        );
+        assert_eq!(
+            output_type(NativeOperation::RightShift(IntegerNativeType::UVec4)),
+            "uvec4"
+        );
+        let b = &NativeOperation::Modulo(IntegerNativeType::IVec2)
+            .input()
+            .fields[1]
+            .1;
+        assert!(b.tolerant);
+        assert_eq!(
+            b.glsl_type.tolerated_input_types(),
+            &[NativeType::Int, NativeType::IVec2]
+        );
+    }
+
+    #[test]
+    fn gates_glsl_versions() {
+        let operation = NativeOperation::BitAnd(IntegerNativeType::Int);
+        assert!(!GlslVersion::GlslEs100.supports_operation(&operation));
+        assert!(GlslVersion::GlslEs300.supports_operation(&operation));
+        assert!(
+            GlslVersion::Glsl110.supports_operation(&NativeOperation::VectorLessThan(
+                NumericVectorNativeType::Vec2
+            ))
+        );
+    }
+}
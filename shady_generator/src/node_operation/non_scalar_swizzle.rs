@@ -1,4 +1,4 @@
-use crate::{Input, InputField, NativeType, Output};
+use crate::{Input, InputField, NativeType, Output, ScalarNativeType};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq)]
@@ -22,20 +22,22 @@ pub enum Vec4Field {
     W,
 }
 
+/// Swizzle of a vector, the first field being the component type of the swizzled vector and of
+/// the result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum NonScalarSwizzle {
     // Vec2
-    Vec2ToVec2([Vec2Field; 2]),
-    Vec2ToVec3([Vec2Field; 3]),
-    Vec2ToVec4([Vec2Field; 4]),
+    Vec2ToVec2(ScalarNativeType, [Vec2Field; 2]),
+    Vec2ToVec3(ScalarNativeType, [Vec2Field; 3]),
+    Vec2ToVec4(ScalarNativeType, [Vec2Field; 4]),
     // Vec3
-    Vec3ToVec2([Vec3Field; 2]),
-    Vec3ToVec3([Vec3Field; 3]),
-    Vec3ToVec4([Vec3Field; 4]),
+    Vec3ToVec2(ScalarNativeType, [Vec3Field; 2]),
+    Vec3ToVec3(ScalarNativeType, [Vec3Field; 3]),
+    Vec3ToVec4(ScalarNativeType, [Vec3Field; 4]),
     // Vec4
-    Vec4ToVec2([Vec4Field; 2]),
-    Vec4ToVec3([Vec4Field; 3]),
-    Vec4ToVec4([Vec4Field; 4]),
+    Vec4ToVec2(ScalarNativeType, [Vec4Field; 2]),
+    Vec4ToVec3(ScalarNativeType, [Vec4Field; 3]),
+    Vec4ToVec4(ScalarNativeType, [Vec4Field; 4]),
 }
 
 // TODO: rename and reorganize
@@ -128,104 +130,158 @@ fn field_to_glsl<F: FieldToGlsl>(arr: &[F]) -> String {
     vec.join("")
 }
 
+/// Vector type of `size` components of `component` type, `size` being 2, 3 or 4
+const fn vector_type(component: ScalarNativeType, size: usize) -> NativeType {
+    match component.vector_type(size) {
+        Some(native_type) => native_type,
+        None => unreachable!(),
+    }
+}
+
 impl NonScalarSwizzle {
     pub const VARIANTS: &'static [Self] = &[
-        Self::Vec2ToVec2([Vec2Field::X, Vec2Field::Y]),
-        Self::Vec2ToVec3([Vec2Field::X, Vec2Field::Y, Vec2Field::X]),
-        Self::Vec2ToVec4([Vec2Field::X, Vec2Field::Y, Vec2Field::X, Vec2Field::Y]),
-        Self::Vec3ToVec2([Vec3Field::X, Vec3Field::Y]),
-        Self::Vec3ToVec3([Vec3Field::X, Vec3Field::Y, Vec3Field::Z]),
-        Self::Vec3ToVec4([Vec3Field::X, Vec3Field::Y, Vec3Field::Z, Vec3Field::Z]),
-        Self::Vec4ToVec2([Vec4Field::X, Vec4Field::Y]),
-        Self::Vec4ToVec3([Vec4Field::X, Vec4Field::Y, Vec4Field::Z]),
-        Self::Vec4ToVec4([Vec4Field::X, Vec4Field::Y, Vec4Field::Z, Vec4Field::W]),
+        Self::Vec2ToVec2(ScalarNativeType::Float, [Vec2Field::X, Vec2Field::Y]),
+        Self::Vec2ToVec3(
+            ScalarNativeType::Float,
+            [Vec2Field::X, Vec2Field::Y, Vec2Field::X],
+        ),
+        Self::Vec2ToVec4(
+            ScalarNativeType::Float,
+            [Vec2Field::X, Vec2Field::Y, Vec2Field::X, Vec2Field::Y],
+        ),
+        Self::Vec3ToVec2(ScalarNativeType::Float, [Vec3Field::X, Vec3Field::Y]),
+        Self::Vec3ToVec3(
+            ScalarNativeType::Float,
+            [Vec3Field::X, Vec3Field::Y, Vec3Field::Z],
+        ),
+        Self::Vec3ToVec4(
+            ScalarNativeType::Float,
+            [Vec3Field::X, Vec3Field::Y, Vec3Field::Z, Vec3Field::Z],
+        ),
+        Self::Vec4ToVec2(ScalarNativeType::Float, [Vec4Field::X, Vec4Field::Y]),
+        Self::Vec4ToVec3(
+            ScalarNativeType::Float,
+            [Vec4Field::X, Vec4Field::Y, Vec4Field::Z],
+        ),
+        Self::Vec4ToVec4(
+            ScalarNativeType::Float,
+            [Vec4Field::X, Vec4Field::Y, Vec4Field::Z, Vec4Field::W],
+        ),
     ];
 
     pub fn glsl_method(&self) -> String {
         match self {
-            NonScalarSwizzle::Vec2ToVec2(arr) => field_to_glsl(arr),
-            NonScalarSwizzle::Vec2ToVec3(arr) => field_to_glsl(arr),
-            NonScalarSwizzle::Vec2ToVec4(arr) => field_to_glsl(arr),
-            NonScalarSwizzle::Vec3ToVec2(arr) => field_to_glsl(arr),
-            NonScalarSwizzle::Vec3ToVec3(arr) => field_to_glsl(arr),
-            NonScalarSwizzle::Vec3ToVec4(arr) => field_to_glsl(arr),
-            NonScalarSwizzle::Vec4ToVec2(arr) => field_to_glsl(arr),
-            NonScalarSwizzle::Vec4ToVec3(arr) => field_to_glsl(arr),
-            NonScalarSwizzle::Vec4ToVec4(arr) => field_to_glsl(arr),
+            NonScalarSwizzle::Vec2ToVec2(_, arr) => field_to_glsl(arr),
+            NonScalarSwizzle::Vec2ToVec3(_, arr) => field_to_glsl(arr),
+            NonScalarSwizzle::Vec2ToVec4(_, arr) => field_to_glsl(arr),
+            NonScalarSwizzle::Vec3ToVec2(_, arr) => field_to_glsl(arr),
+            NonScalarSwizzle::Vec3ToVec3(_, arr) => field_to_glsl(arr),
+            NonScalarSwizzle::Vec3ToVec4(_, arr) => field_to_glsl(arr),
+            NonScalarSwizzle::Vec4ToVec2(_, arr) => field_to_glsl(arr),
+            NonScalarSwizzle::Vec4ToVec3(_, arr) => field_to_glsl(arr),
+            NonScalarSwizzle::Vec4ToVec4(_, arr) => field_to_glsl(arr),
         }
     }
 
-    pub const fn output(&self) -> Output {
+    /// Component type of the swizzled vector and of the result
+    pub const fn component_type(&self) -> ScalarNativeType {
         match self {
-            NonScalarSwizzle::Vec2ToVec2(_)
-            | NonScalarSwizzle::Vec3ToVec2(_)
-            | NonScalarSwizzle::Vec4ToVec2(_) => Output::NativeType(NativeType::Vec2),
-            NonScalarSwizzle::Vec2ToVec3(_)
-            | NonScalarSwizzle::Vec3ToVec3(_)
-            | NonScalarSwizzle::Vec4ToVec3(_) => Output::NativeType(NativeType::Vec3),
-            NonScalarSwizzle::Vec2ToVec4(_)
-            | NonScalarSwizzle::Vec3ToVec4(_)
-            | NonScalarSwizzle::Vec4ToVec4(_) => Output::NativeType(NativeType::Vec4),
+            NonScalarSwizzle::Vec2ToVec2(t, _)
+            | NonScalarSwizzle::Vec2ToVec3(t, _)
+            | NonScalarSwizzle::Vec2ToVec4(t, _)
+            | NonScalarSwizzle::Vec3ToVec2(t, _)
+            | NonScalarSwizzle::Vec3ToVec3(t, _)
+            | NonScalarSwizzle::Vec3ToVec4(t, _)
+            | NonScalarSwizzle::Vec4ToVec2(t, _)
+            | NonScalarSwizzle::Vec4ToVec3(t, _)
+            | NonScalarSwizzle::Vec4ToVec4(t, _) => *t,
         }
     }
 
-    pub fn input(&self) -> Input {
+    /// Mutable component type, see `component_type`
+    pub fn component_type_mut(&mut self) -> &mut ScalarNativeType {
         match self {
-            NonScalarSwizzle::Vec2ToVec2(_)
-            | NonScalarSwizzle::Vec2ToVec3(_)
-            | NonScalarSwizzle::Vec2ToVec4(_) => Input {
-                fields: vec![("i".to_string(), InputField::new(NativeType::Vec2))],
-            },
-            NonScalarSwizzle::Vec3ToVec2(_)
-            | NonScalarSwizzle::Vec3ToVec3(_)
-            | NonScalarSwizzle::Vec3ToVec4(_) => Input {
-                fields: vec![("i".to_string(), InputField::new(NativeType::Vec3))],
-            },
-            NonScalarSwizzle::Vec4ToVec2(_)
-            | NonScalarSwizzle::Vec4ToVec3(_)
-            | NonScalarSwizzle::Vec4ToVec4(_) => Input {
-                fields: vec![("i".to_string(), InputField::new(NativeType::Vec4))],
-            },
+            NonScalarSwizzle::Vec2ToVec2(t, _)
+            | NonScalarSwizzle::Vec2ToVec3(t, _)
+            | NonScalarSwizzle::Vec2ToVec4(t, _)
+            | NonScalarSwizzle::Vec3ToVec2(t, _)
+            | NonScalarSwizzle::Vec3ToVec3(t, _)
+            | NonScalarSwizzle::Vec3ToVec4(t, _)
+            | NonScalarSwizzle::Vec4ToVec2(t, _)
+            | NonScalarSwizzle::Vec4ToVec3(t, _)
+            | NonScalarSwizzle::Vec4ToVec4(t, _) => t,
         }
     }
 
-    pub const fn descriptive_name(&self) -> &'static str {
+    /// Number of components of the swizzled vector
+    const fn input_size(&self) -> usize {
         match self {
-            NonScalarSwizzle::Vec2ToVec2(_) => "Vec2 To Vec2",
-            NonScalarSwizzle::Vec2ToVec3(_) => "Vec2 To Vec3",
-            NonScalarSwizzle::Vec2ToVec4(_) => "Vec2 To Vec4",
-            NonScalarSwizzle::Vec3ToVec2(_) => "Vec3 To Vec2",
-            NonScalarSwizzle::Vec3ToVec3(_) => "Vec3 To Vec3",
-            NonScalarSwizzle::Vec3ToVec4(_) => "Vec3 To Vec4",
-            NonScalarSwizzle::Vec4ToVec2(_) => "Vec4 To Vec2",
-            NonScalarSwizzle::Vec4ToVec3(_) => "Vec4 To Vec3",
-            NonScalarSwizzle::Vec4ToVec4(_) => "Vec4 To Vec4",
+            NonScalarSwizzle::Vec2ToVec2(..)
+            | NonScalarSwizzle::Vec2ToVec3(..)
+            | NonScalarSwizzle::Vec2ToVec4(..) => 2,
+            NonScalarSwizzle::Vec3ToVec2(..)
+            | NonScalarSwizzle::Vec3ToVec3(..)
+            | NonScalarSwizzle::Vec3ToVec4(..) => 3,
+            NonScalarSwizzle::Vec4ToVec2(..)
+            | NonScalarSwizzle::Vec4ToVec3(..)
+            | NonScalarSwizzle::Vec4ToVec4(..) => 4,
         }
     }
 
-    pub fn complete_name(&self) -> String {
+    /// Number of components of the result
+    const fn output_size(&self) -> usize {
         match self {
-            NonScalarSwizzle::Vec2ToVec2(_)
-            | NonScalarSwizzle::Vec2ToVec3(_)
-            | NonScalarSwizzle::Vec2ToVec4(_) => {
-                format!("{}.{}", NativeType::Vec2, self.glsl_method())
-            }
-            NonScalarSwizzle::Vec3ToVec2(_)
-            | NonScalarSwizzle::Vec3ToVec3(_)
-            | NonScalarSwizzle::Vec3ToVec4(_) => {
-                format!("{}.{}", NativeType::Vec3, self.glsl_method())
-            }
-            NonScalarSwizzle::Vec4ToVec2(_)
-            | NonScalarSwizzle::Vec4ToVec3(_)
-            | NonScalarSwizzle::Vec4ToVec4(_) => {
-                format!("{}.{}", NativeType::Vec4, self.glsl_method())
-            }
+            NonScalarSwizzle::Vec2ToVec2(..)
+            | NonScalarSwizzle::Vec3ToVec2(..)
+            | NonScalarSwizzle::Vec4ToVec2(..) => 2,
+            NonScalarSwizzle::Vec2ToVec3(..)
+            | NonScalarSwizzle::Vec3ToVec3(..)
+            | NonScalarSwizzle::Vec4ToVec3(..) => 3,
+            NonScalarSwizzle::Vec2ToVec4(..)
+            | NonScalarSwizzle::Vec3ToVec4(..)
+            | NonScalarSwizzle::Vec4ToVec4(..) => 4,
         }
     }
+
+    /// Swizzled vector type
+    pub const fn input_type(&self) -> NativeType {
+        vector_type(self.component_type(), self.input_size())
+    }
+
+    pub const fn output(&self) -> Output {
+        Output::NativeType(vector_type(self.component_type(), self.output_size()))
+    }
+
+    pub fn input(&self) -> Input {
+        Input {
+            fields: vec![("i".to_string(), InputField::new(self.input_type()))],
+        }
+    }
+
+    pub const fn descriptive_name(&self) -> &'static str {
+        match self {
+            NonScalarSwizzle::Vec2ToVec2(..) => "Vec2 To Vec2",
+            NonScalarSwizzle::Vec2ToVec3(..) => "Vec2 To Vec3",
+            NonScalarSwizzle::Vec2ToVec4(..) => "Vec2 To Vec4",
+            NonScalarSwizzle::Vec3ToVec2(..) => "Vec3 To Vec2",
+            NonScalarSwizzle::Vec3ToVec3(..) => "Vec3 To Vec3",
+            NonScalarSwizzle::Vec3ToVec4(..) => "Vec3 To Vec4",
+            NonScalarSwizzle::Vec4ToVec2(..) => "Vec4 To Vec2",
+            NonScalarSwizzle::Vec4ToVec3(..) => "Vec4 To Vec3",
+            NonScalarSwizzle::Vec4ToVec4(..) => "Vec4 To Vec4",
+        }
+    }
+
+    pub fn complete_name(&self) -> String {
+        format!("{}.{}", self.input_type(), self.glsl_method())
+    }
 }
 
 impl Default for NonScalarSwizzle {
     fn default() -> Self {
-        Self::Vec2ToVec3([Vec2Field::X, Vec2Field::Y, Vec2Field::X])
+        Self::Vec2ToVec3(
+            ScalarNativeType::Float,
+            [Vec2Field::X, Vec2Field::Y, Vec2Field::X],
+        )
     }
 }
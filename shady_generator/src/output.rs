@@ -1,15 +1,16 @@
-use crate::{NativeType, NonScalarNativeType};
+use crate::{CodeWriter, NativeType, NonScalarNativeType, ShadyError};
 use serde::{Deserialize, Serialize};
+use std::fmt::Write;
 use std::ops::Deref;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub enum Output {
     NativeType(NativeType),
     CustomType(CustomOutput),
     Split(NonScalarNativeType),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub struct CustomOutput {
     pub struct_name: String,
     pub fields: Vec<(String, NativeType)>,
@@ -30,6 +31,14 @@ impl Output {
         }
     }
 
+    pub fn wgsl_type(&self) -> Result<String, ShadyError> {
+        Ok(match self {
+            Output::NativeType(native_type) => native_type.get_wgsl_type()?.to_string(),
+            Output::CustomType(c) => c.struct_name.clone(),
+            Output::Split(t) => NativeType::from(*t).get_wgsl_type()?.to_string(),
+        })
+    }
+
     pub fn fields(&self) -> OutputFields {
         match self {
             Output::NativeType(glsl_type) => OutputFields::SingleOutput(*glsl_type),
@@ -38,6 +47,15 @@ impl Output {
         }
     }
 
+    /// The custom `struct` output, if any
+    pub const fn custom_type(&self) -> Option<&CustomOutput> {
+        if let Output::CustomType(c) = self {
+            Some(c)
+        } else {
+            None
+        }
+    }
+
     pub fn custom_declaration(&self) -> Option<String> {
         if let Output::CustomType(c) = &self {
             Some(c.glsl_struct_declaration())
@@ -45,6 +63,14 @@ impl Output {
             None
         }
     }
+
+    pub fn wgsl_custom_declaration(&self) -> Result<Option<String>, ShadyError> {
+        if let Output::CustomType(c) = &self {
+            Ok(Some(c.wgsl_struct_declaration()?))
+        } else {
+            Ok(None)
+        }
+    }
 }
 
 impl OutputFields {
@@ -74,11 +100,49 @@ impl OutputFields {
 
 impl CustomOutput {
     pub fn glsl_struct_declaration(&self) -> String {
-        let mut buff = format!("struct {} {{\n", self.struct_name);
+        let mut buff = String::new();
+        // Writing into a `String` can't fail
+        let _ = self.write_glsl_struct_declaration(&mut CodeWriter::new(&mut buff));
+        buff
+    }
+
+    pub fn wgsl_struct_declaration(&self) -> Result<String, ShadyError> {
+        let mut buff = String::new();
+        self.write_wgsl_struct_declaration(&mut CodeWriter::new(&mut buff))?;
+        Ok(buff)
+    }
+
+    /// Writes the GLSL `struct` declaration into `writer`
+    pub fn write_glsl_struct_declaration<W: Write>(
+        &self,
+        writer: &mut CodeWriter<W>,
+    ) -> std::fmt::Result {
+        writer.line(format_args!("struct {} {{", self.struct_name))?;
         for (field_name, glsl_type) in &self.fields {
-            buff = format!("{}  {} {};\n", buff, glsl_type.get_glsl_type(), field_name);
+            writer.line(format_args!(
+                "  {} {};",
+                glsl_type.get_glsl_type(),
+                field_name
+            ))?;
+        }
+        writer.line("};")
+    }
+
+    /// Writes the WGSL `struct` declaration into `writer`
+    pub fn write_wgsl_struct_declaration<W: Write>(
+        &self,
+        writer: &mut CodeWriter<W>,
+    ) -> Result<(), ShadyError> {
+        writer.line(format_args!("struct {} {{", self.struct_name))?;
+        for (field_name, native_type) in &self.fields {
+            writer.line(format_args!(
+                "  {}: {},",
+                field_name,
+                native_type.get_wgsl_type()?
+            ))?;
         }
-        format!("{}}};\n", buff)
+        writer.line("};")?;
+        Ok(())
     }
 }
 
@@ -120,4 +184,33 @@ mod tests {
             "}
         );
     }
+
+    #[test]
+    fn wgsl_custom_declaration_works() {
+        let field = Output::CustomType(CustomOutput {
+            struct_name: "MyStruct".to_string(),
+            fields: vec![
+                ("coords".to_string(), NonScalarNativeType::IVec2.into()),
+                ("n".to_string(), ScalarNativeType::Float.into()),
+                ("count".to_string(), ScalarNativeType::UInt.into()),
+            ],
+        });
+        assert_eq!(field.wgsl_type().unwrap(), "MyStruct".to_string());
+        assert_eq!(
+            field.wgsl_custom_declaration().unwrap().unwrap(),
+            formatdoc! {"
+                struct MyStruct {{
+                  coords: vec2<i32>,
+                  n: f32,
+                  count: u32,
+                }};
+            "}
+        );
+    }
+
+    #[test]
+    fn wgsl_rejects_double() {
+        let field = Output::NativeType(ScalarNativeType::Double.into());
+        assert!(field.wgsl_type().is_err());
+    }
 }
@@ -1,4 +1,7 @@
-use crate::NativeType;
+use crate::{
+    sanitize_identifier, vector_literal, GlslVersion, GraphicLibrary, Literal, LiteralTarget,
+    NativeType, ShadyError,
+};
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 use std::ops::Deref;
@@ -27,6 +30,30 @@ pub enum ConstantValue {
     Vec4([f32; 4]),
     /// 4D Integer Vector (x, y, z, w)
     IVec4([i32; 4]),
+    /// 2D Boolean vector (x, y)
+    BVec2([bool; 2]),
+    /// 3D Boolean vector (x, y, z)
+    BVec3([bool; 3]),
+    /// 4D Boolean vector (x, y, z, w)
+    BVec4([bool; 4]),
+    /// 2D Unsigned Integer vector (x, y)
+    UVec2([u32; 2]),
+    /// 3D Unsigned Integer vector (x, y, z)
+    UVec3([u32; 3]),
+    /// 4D Unsigned Integer vector (x, y, z, w)
+    UVec4([u32; 4]),
+    /// 2D Double vector (x, y)
+    DVec2([f64; 2]),
+    /// 3D Double vector (x, y, z)
+    DVec3([f64; 3]),
+    /// 4D Double vector (x, y, z, w)
+    DVec4([f64; 4]),
+    /// 2x2 Float matrix, by column
+    Mat2([[f32; 2]; 2]),
+    /// 3x3 Float matrix, by column
+    Mat3([[f32; 3]; 3]),
+    /// 4x4 Float matrix, by column
+    Mat4([[f32; 4]; 4]),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,21 +63,28 @@ pub struct Constant {
 }
 
 impl Constant {
+    /// Identifier of the constant, its uppercase name sanitized through `sanitize_identifier`
     pub fn key(&self) -> String {
-        self.name
-            .to_ascii_uppercase()
-            .replace(" ", "_")
-            .trim()
-            .to_string()
+        sanitize_identifier(&self.name.to_ascii_uppercase())
     }
 
-    pub fn glsl_declaration(&self) -> String {
-        format!(
+    /// GLSL declaration of the constant for the targeted `version`
+    pub fn glsl_declaration(&self, version: GlslVersion) -> Result<String, ShadyError> {
+        Ok(format!(
             "const {} {} = {};",
             self.native_type(),
             self.key(),
-            self.str_value()
-        )
+            self.literal(LiteralTarget::Glsl(version))?
+        ))
+    }
+
+    pub fn wgsl_declaration(&self) -> Result<String, ShadyError> {
+        Ok(format!(
+            "const {}: {} = {};",
+            self.key(),
+            self.native_type().get_wgsl_type()?,
+            self.literal(LiteralTarget::Wgsl)?
+        ))
     }
 }
 
@@ -67,6 +101,23 @@ impl ConstantValue {
         Self::IVec3([1, 1, 1]),
         Self::Vec4([1.0, 1.0, 1.0, 1.0]),
         Self::IVec4([1, 1, 1, 1]),
+        Self::BVec2([true, true]),
+        Self::BVec3([true, true, true]),
+        Self::BVec4([true, true, true, true]),
+        Self::UVec2([1, 1]),
+        Self::UVec3([1, 1, 1]),
+        Self::UVec4([1, 1, 1, 1]),
+        Self::DVec2([1.0, 1.0]),
+        Self::DVec3([1.0, 1.0, 1.0]),
+        Self::DVec4([1.0, 1.0, 1.0, 1.0]),
+        Self::Mat2([[1.0, 0.0], [0.0, 1.0]]),
+        Self::Mat3([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]),
+        Self::Mat4([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]),
     ];
 
     pub const fn native_type(&self) -> NativeType {
@@ -82,27 +133,76 @@ impl ConstantValue {
             ConstantValue::IVec3(_) => NativeType::IVec3,
             ConstantValue::Vec4(_) => NativeType::Vec4,
             ConstantValue::IVec4(_) => NativeType::IVec4,
+            ConstantValue::BVec2(_) => NativeType::BVec2,
+            ConstantValue::BVec3(_) => NativeType::BVec3,
+            ConstantValue::BVec4(_) => NativeType::BVec4,
+            ConstantValue::UVec2(_) => NativeType::UVec2,
+            ConstantValue::UVec3(_) => NativeType::UVec3,
+            ConstantValue::UVec4(_) => NativeType::UVec4,
+            ConstantValue::DVec2(_) => NativeType::DVec2,
+            ConstantValue::DVec3(_) => NativeType::DVec3,
+            ConstantValue::DVec4(_) => NativeType::DVec4,
+            ConstantValue::Mat2(_) => NativeType::Mat2,
+            ConstantValue::Mat3(_) => NativeType::Mat3,
+            ConstantValue::Mat4(_) => NativeType::Mat4,
         }
     }
 
-    fn complex_declaration<T: Display, const SIZE: usize>(v: &[T; SIZE], t: NativeType) -> String {
-        let vec: Vec<String> = v.iter().map(ToString::to_string).collect();
-        format!("{}({})", t, vec.join(", "))
+    /// Are all the floating components of the value finite
+    pub fn is_finite(&self) -> bool {
+        match self {
+            ConstantValue::Float(v) => v.is_finite(),
+            ConstantValue::Double(v) => v.is_finite(),
+            ConstantValue::Vec2(v) => v.iter().all(|c| c.is_finite()),
+            ConstantValue::Vec3(v) => v.iter().all(|c| c.is_finite()),
+            ConstantValue::Vec4(v) => v.iter().all(|c| c.is_finite()),
+            ConstantValue::DVec2(v) => v.iter().all(|c| c.is_finite()),
+            ConstantValue::DVec3(v) => v.iter().all(|c| c.is_finite()),
+            ConstantValue::DVec4(v) => v.iter().all(|c| c.is_finite()),
+            ConstantValue::Mat2(v) => v.iter().flatten().all(|c| c.is_finite()),
+            ConstantValue::Mat3(v) => v.iter().flatten().all(|c| c.is_finite()),
+            ConstantValue::Mat4(v) => v.iter().flatten().all(|c| c.is_finite()),
+            _ => true,
+        }
     }
 
-    pub fn str_value(&self) -> String {
+    /// Type correct shader code literal of the value for `target`
+    pub fn literal(&self, target: LiteralTarget) -> Result<String, ShadyError> {
+        if target == LiteralTarget::Wgsl {
+            self.native_type().get_wgsl_type()?;
+            if !self.is_finite() {
+                return Err(ShadyError::NonFiniteLiteral {
+                    value: format!("{:?}", self),
+                    target: GraphicLibrary::WebGPU.to_string(),
+                });
+            }
+        }
+        let native_type = self.native_type();
         match self {
-            ConstantValue::Bool(v) => v.to_string(),
-            ConstantValue::Int(v) => v.to_string(),
-            ConstantValue::UInt(v) => v.to_string(),
-            ConstantValue::Float(v) => v.to_string(),
-            ConstantValue::Double(v) => v.to_string(),
-            ConstantValue::Vec2(v) => Self::complex_declaration(v, self.native_type()),
-            ConstantValue::IVec2(v) => Self::complex_declaration(v, self.native_type()),
-            ConstantValue::Vec3(v) => Self::complex_declaration(v, self.native_type()),
-            ConstantValue::IVec3(v) => Self::complex_declaration(v, self.native_type()),
-            ConstantValue::Vec4(v) => Self::complex_declaration(v, self.native_type()),
-            ConstantValue::IVec4(v) => Self::complex_declaration(v, self.native_type()),
+            ConstantValue::Bool(v) => Ok(v.literal(target)),
+            ConstantValue::Int(v) => Ok(v.literal(target)),
+            ConstantValue::UInt(v) => Ok(v.literal(target)),
+            ConstantValue::Float(v) => Ok(v.literal(target)),
+            ConstantValue::Double(v) => Ok(v.literal(target)),
+            ConstantValue::Vec2(v) => vector_literal(native_type, v, target),
+            ConstantValue::IVec2(v) => vector_literal(native_type, v, target),
+            ConstantValue::Vec3(v) => vector_literal(native_type, v, target),
+            ConstantValue::IVec3(v) => vector_literal(native_type, v, target),
+            ConstantValue::Vec4(v) => vector_literal(native_type, v, target),
+            ConstantValue::IVec4(v) => vector_literal(native_type, v, target),
+            ConstantValue::BVec2(v) => vector_literal(native_type, v, target),
+            ConstantValue::BVec3(v) => vector_literal(native_type, v, target),
+            ConstantValue::BVec4(v) => vector_literal(native_type, v, target),
+            ConstantValue::UVec2(v) => vector_literal(native_type, v, target),
+            ConstantValue::UVec3(v) => vector_literal(native_type, v, target),
+            ConstantValue::UVec4(v) => vector_literal(native_type, v, target),
+            ConstantValue::DVec2(v) => vector_literal(native_type, v, target),
+            ConstantValue::DVec3(v) => vector_literal(native_type, v, target),
+            ConstantValue::DVec4(v) => vector_literal(native_type, v, target),
+            // Matrix constructors take the components column by column
+            ConstantValue::Mat2(v) => vector_literal(native_type, &v.concat(), target),
+            ConstantValue::Mat3(v) => vector_literal(native_type, &v.concat(), target),
+            ConstantValue::Mat4(v) => vector_literal(native_type, &v.concat(), target),
         }
     }
 }
@@ -130,6 +230,18 @@ impl Display for ConstantValue {
                 ConstantValue::IVec3(_) => "IVec3",
                 ConstantValue::Vec4(_) => "Vec4",
                 ConstantValue::IVec4(_) => "IVec4",
+                ConstantValue::BVec2(_) => "BVec2",
+                ConstantValue::BVec3(_) => "BVec3",
+                ConstantValue::BVec4(_) => "BVec4",
+                ConstantValue::UVec2(_) => "UVec2",
+                ConstantValue::UVec3(_) => "UVec3",
+                ConstantValue::UVec4(_) => "UVec4",
+                ConstantValue::DVec2(_) => "DVec2",
+                ConstantValue::DVec3(_) => "DVec3",
+                ConstantValue::DVec4(_) => "DVec4",
+                ConstantValue::Mat2(_) => "Mat2",
+                ConstantValue::Mat3(_) => "Mat3",
+                ConstantValue::Mat4(_) => "Mat4",
             }
         )
     }
@@ -142,3 +254,106 @@ impl Deref for Constant {
         &self.value
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn constant(value: ConstantValue) -> Constant {
+        Constant {
+            name: "My Value".to_string(),
+            value,
+        }
+    }
+
+    #[test]
+    fn glsl_declarations_are_typed() {
+        let declaration = |value| {
+            constant(value)
+                .glsl_declaration(GlslVersion::Glsl450)
+                .unwrap()
+        };
+        assert_eq!(
+            declaration(ConstantValue::Float(1.0)),
+            "const float MY_VALUE = 1.0;"
+        );
+        assert_eq!(
+            declaration(ConstantValue::UInt(3)),
+            "const uint MY_VALUE = 3u;"
+        );
+        assert_eq!(
+            declaration(ConstantValue::Double(2.0)),
+            "const double MY_VALUE = 2.0lf;"
+        );
+        assert_eq!(
+            declaration(ConstantValue::Vec3([1.0, -2.0, 0.25])),
+            "const vec3 MY_VALUE = vec3(1.0, -2.0, 0.25);"
+        );
+        assert_eq!(
+            declaration(ConstantValue::Mat2([[1.0, 2.0], [3.0, 4.0]])),
+            "const mat2 MY_VALUE = mat2(1.0, 2.0, 3.0, 4.0);"
+        );
+        assert_eq!(
+            declaration(ConstantValue::BVec2([true, false])),
+            "const bvec2 MY_VALUE = bvec2(true, false);"
+        );
+        assert_eq!(
+            declaration(ConstantValue::UVec3([1, 2, 3])),
+            "const uvec3 MY_VALUE = uvec3(1u, 2u, 3u);"
+        );
+        assert_eq!(
+            declaration(ConstantValue::DVec2([0.5, 1.0])),
+            "const dvec2 MY_VALUE = dvec2(0.5lf, 1.0lf);"
+        );
+    }
+
+    #[test]
+    fn wgsl_declarations_are_typed() {
+        assert_eq!(
+            constant(ConstantValue::UInt(3)).wgsl_declaration().unwrap(),
+            "const MY_VALUE: u32 = 3u;"
+        );
+        assert_eq!(
+            constant(ConstantValue::Mat2([[1.0, 2.0], [3.0, 4.0]]))
+                .wgsl_declaration()
+                .unwrap(),
+            "const MY_VALUE: mat2x2<f32> = mat2x2<f32>(1.0, 2.0, 3.0, 4.0);"
+        );
+        assert_eq!(
+            constant(ConstantValue::BVec3([true, false, true]))
+                .wgsl_declaration()
+                .unwrap(),
+            "const MY_VALUE: vec3<bool> = vec3<bool>(true, false, true);"
+        );
+        assert_eq!(
+            constant(ConstantValue::UVec2([4, 5]))
+                .wgsl_declaration()
+                .unwrap(),
+            "const MY_VALUE: vec2<u32> = vec2<u32>(4u, 5u);"
+        );
+        for value in [ConstantValue::Double(2.0), ConstantValue::DVec4([1.0; 4])] {
+            assert!(matches!(
+                constant(value).wgsl_declaration(),
+                Err(ShadyError::UnsupportedNativeType { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn wgsl_rejects_non_finite_values() {
+        for value in [
+            ConstantValue::Float(f32::INFINITY),
+            ConstantValue::Vec3([0.0, f32::NAN, 1.0]),
+            ConstantValue::Mat2([[1.0, 0.0], [0.0, f32::NEG_INFINITY]]),
+        ] {
+            assert!(!value.is_finite());
+            assert!(value
+                .literal(LiteralTarget::Glsl(GlslVersion::Glsl450))
+                .is_ok());
+            assert!(matches!(
+                constant(value).wgsl_declaration(),
+                Err(ShadyError::NonFiniteLiteral { .. })
+            ));
+        }
+    }
+}
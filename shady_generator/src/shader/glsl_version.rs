@@ -0,0 +1,172 @@
+use crate::node_operation::{NativeFunction, NativeOperation};
+use crate::{GraphicLibrary, NativeType, ScalarNativeType};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// Targeted GLSL language version and profile
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub enum GlslVersion {
+    /// GLSL 1.10 (OpenGL 2.0)
+    Glsl110,
+    /// GLSL 3.30 core profile (OpenGL 3.3)
+    Glsl330,
+    /// GLSL 4.50 core profile (OpenGL 4.5)
+    Glsl450,
+    /// GLSL ES 1.00 (OpenGL ES 2.0, WebGL 1)
+    GlslEs100,
+    /// GLSL ES 3.00 (OpenGL ES 3.0, WebGL 2)
+    GlslEs300,
+}
+
+impl Default for GlslVersion {
+    fn default() -> Self {
+        Self::Glsl450
+    }
+}
+
+impl GlslVersion {
+    /// All enum variants
+    pub const VARIANTS: &'static [Self] = &[
+        Self::Glsl110,
+        Self::Glsl330,
+        Self::Glsl450,
+        Self::GlslEs100,
+        Self::GlslEs300,
+    ];
+
+    /// The `#version` directive for the target
+    pub const fn header(&self) -> &'static str {
+        match self {
+            Self::Glsl110 => "#version 110",
+            Self::Glsl330 => "#version 330 core",
+            Self::Glsl450 => "#version 450 core",
+            Self::GlslEs100 => "#version 100",
+            Self::GlslEs300 => "#version 300 es",
+        }
+    }
+
+    /// The graphic library matching the GLSL profile
+    pub const fn library(&self) -> GraphicLibrary {
+        match self {
+            Self::Glsl110 | Self::Glsl330 | Self::Glsl450 => GraphicLibrary::OpenGl,
+            Self::GlslEs100 | Self::GlslEs300 => GraphicLibrary::OpenGlEs,
+        }
+    }
+
+    /// Is the target an OpenGL ES profile
+    pub const fn is_es(&self) -> bool {
+        matches!(self, Self::GlslEs100 | Self::GlslEs300)
+    }
+
+    /// Legacy targets use `attribute`/`varying` qualifiers, `gl_FragData` and
+    /// type specific texture functions (`texture2D`)
+    pub const fn is_legacy(&self) -> bool {
+        matches!(self, Self::Glsl110 | Self::GlslEs100)
+    }
+
+    /// Is `native_type` available for the target, legacy targets only have 2D and cube samplers
+    pub const fn supports(&self, native_type: NativeType) -> bool {
+        if native_type.is_sampler() {
+            return matches!(native_type, NativeType::Sampler2d | NativeType::SamplerCube)
+                || !self.is_legacy();
+        }
+        match native_type.component_type() {
+            Some(ScalarNativeType::UInt) => !self.is_legacy(),
+            Some(ScalarNativeType::Double) => matches!(self, Self::Glsl450),
+            _ => true,
+        }
+    }
+
+    /// Is `function` available for the target. Legacy targets lack most matrix functions and
+    /// the GLSL 1.30 additions, the GLSL 4.00 additions are only available to `Glsl450` and the
+    /// 16 bits packing functions to `Glsl450` and `GlslEs300`. Texture lookups are only available
+    /// for their accepted samplers, see `NativeFunction::accepts_sampler`
+    pub const fn supports_function(&self, function: &NativeFunction) -> bool {
+        match function {
+            NativeFunction::Transpose(_)
+            | NativeFunction::Inverse(_)
+            | NativeFunction::Determinant(_)
+            | NativeFunction::OuterProduct(_)
+            | NativeFunction::HyperbolicSine(_)
+            | NativeFunction::HyperbolicCosine(_)
+            | NativeFunction::HyperbolicTangent(_)
+            | NativeFunction::HyperbolicArcSine(_)
+            | NativeFunction::HyperbolicArcCosine(_)
+            | NativeFunction::HyperbolicArcTangent(_)
+            | NativeFunction::Truncate(_)
+            | NativeFunction::Round(_)
+            | NativeFunction::RoundEven(_)
+            | NativeFunction::FractionalIntegralParts(_)
+            | NativeFunction::IsNan(_)
+            | NativeFunction::IsInfinite(_)
+            | NativeFunction::FloatBitsToInt(_)
+            | NativeFunction::FloatBitsToUInt(_)
+            | NativeFunction::IntBitsToFloat(_)
+            | NativeFunction::UIntBitsToFloat(_) => !self.is_legacy(),
+            NativeFunction::PackUnorm2x16
+            | NativeFunction::PackSnorm2x16
+            | NativeFunction::PackHalf2x16
+            | NativeFunction::UnpackUnorm2x16
+            | NativeFunction::UnpackSnorm2x16
+            | NativeFunction::UnpackHalf2x16 => matches!(self, Self::Glsl450 | Self::GlslEs300),
+            NativeFunction::FusedMultiplyAdd(_)
+            | NativeFunction::SignificandExponent(_)
+            | NativeFunction::LoadExponent(_)
+            | NativeFunction::PackUnorm4x8
+            | NativeFunction::PackSnorm4x8
+            | NativeFunction::PackDouble2x32
+            | NativeFunction::UnpackUnorm4x8
+            | NativeFunction::UnpackSnorm4x8
+            | NativeFunction::UnpackDouble2x32
+            | NativeFunction::BitfieldExtract(_)
+            | NativeFunction::BitfieldInsert(_)
+            | NativeFunction::BitfieldReverse(_)
+            | NativeFunction::BitCount(_)
+            | NativeFunction::FindLsb(_)
+            | NativeFunction::FindMsb(_) => matches!(self, Self::Glsl450),
+            NativeFunction::TextureGather(_) => {
+                function.accepts_sampler() && matches!(self, Self::Glsl450)
+            }
+            NativeFunction::Texture(_)
+            | NativeFunction::TextureLod(_)
+            | NativeFunction::TextureGrad(_)
+            | NativeFunction::TextureOffset(_)
+            | NativeFunction::TexelFetch(_)
+            | NativeFunction::TextureSize(_)
+            | NativeFunction::TextureProj(_)
+            | NativeFunction::TextureProjLod(_) => function.accepts_sampler() && !self.is_legacy(),
+            _ => true,
+        }
+    }
+
+    /// Is `operation` available for the target. Legacy targets reserve the integer modulo and
+    /// the bitwise operators
+    pub const fn supports_operation(&self, operation: &NativeOperation) -> bool {
+        match operation {
+            NativeOperation::Modulo(_)
+            | NativeOperation::BitAnd(_)
+            | NativeOperation::BitOr(_)
+            | NativeOperation::BitXor(_)
+            | NativeOperation::BitNot(_)
+            | NativeOperation::LeftShift(_)
+            | NativeOperation::RightShift(_) => !self.is_legacy(),
+            _ => true,
+        }
+    }
+}
+
+impl Display for GlslVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                GlslVersion::Glsl110 => "GLSL 110",
+                GlslVersion::Glsl330 => "GLSL 330 core",
+                GlslVersion::Glsl450 => "GLSL 450 core",
+                GlslVersion::GlslEs100 => "GLSL ES 100",
+                GlslVersion::GlslEs300 => "GLSL ES 300 es",
+            }
+        )
+    }
+}
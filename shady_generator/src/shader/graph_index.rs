@@ -0,0 +1,303 @@
+use crate::error::ShadyError;
+use crate::{Connection, ConnectionTo, Handle, Node, Shader};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::sync::OnceLock;
+
+/// Reverse adjacency index of the shader graph: the endpoints connected to every constant,
+/// property and node, by element id
+#[derive(Debug, Clone, Default)]
+pub(super) struct ConsumerIndex {
+    consumers: HashMap<String, BTreeSet<(ConnectionTo, Connection)>>,
+}
+
+impl ConsumerIndex {
+    pub fn insert(&mut self, to: ConnectionTo, from: Connection) {
+        self.consumers
+            .entry(from.source_id().clone())
+            .or_default()
+            .insert((to, from));
+    }
+
+    pub fn remove(&mut self, to: ConnectionTo, from: Connection) {
+        let id = from.source_id();
+        if let Some(consumers) = self.consumers.get_mut(id) {
+            let id = id.clone();
+            if !consumers.remove(&(to, from)) {
+                log::warn!("Missing consumer index entry for {}", id);
+            }
+            if consumers.is_empty() {
+                self.consumers.remove(&id);
+            }
+        }
+    }
+
+    /// Removes and returns the endpoints connected to the element `id`
+    pub fn take(&mut self, id: &str) -> BTreeSet<(ConnectionTo, Connection)> {
+        self.consumers.remove(id).unwrap_or_default()
+    }
+
+    pub fn get(&self, id: &str) -> impl Iterator<Item = &(ConnectionTo, Connection)> {
+        self.consumers.get(id).into_iter().flatten()
+    }
+}
+
+impl Shader {
+    /// Builds the consumer index from the node fields and output properties connections
+    fn build_consumer_index(&self) -> ConsumerIndex {
+        let mut index = ConsumerIndex::default();
+        for (node_id, node) in &self.nodes {
+            for (field_name, connection) in node.connections() {
+                let to = ConnectionTo::Node {
+                    id: node_id.clone(),
+                    field_name: field_name.clone(),
+                };
+                index.insert(to, connection.clone());
+            }
+        }
+        for (property_id, property) in &self.output_properties {
+            if let Some(connection) = &property.connection {
+                let to = ConnectionTo::OutputProperty {
+                    id: property_id.clone(),
+                };
+                index.insert(to, connection.clone());
+            }
+        }
+        index
+    }
+
+    /// Rebuilds the consumer index, see `Shader::consumer_index`
+    pub(super) fn index_consumers(&mut self) {
+        self.consumers = OnceLock::from(self.build_consumer_index());
+    }
+
+    /// Consumer index, built on first use so shaders deserialized without `Shader::repair` are
+    /// indexed as well
+    pub(super) fn consumer_index(&self) -> &ConsumerIndex {
+        self.consumers.get_or_init(|| self.build_consumer_index())
+    }
+
+    pub(super) fn consumer_index_mut(&mut self) -> &mut ConsumerIndex {
+        self.consumer_index();
+        self.consumers.get_mut().expect("Consumer index is built")
+    }
+
+    /// Node fields and output properties connected to the constant, property or node `id`, with
+    /// their connection, sorted by endpoint
+    pub fn consumers(&self, id: &str) -> Vec<(&ConnectionTo, &Connection)> {
+        self.consumer_index()
+            .get(id)
+            .map(|(to, from)| (to, from))
+            .collect()
+    }
+
+    /// Node fields and output properties connected to `connection`, a node output field for
+    /// instance, sorted
+    pub fn connection_consumers(&self, connection: &Connection) -> Vec<&ConnectionTo> {
+        self.consumer_index()
+            .get(connection.source_id())
+            .filter(|(_, from)| from == connection)
+            .map(|(to, _)| to)
+            .collect()
+    }
+
+    /// Handles of the `roots` and every node they depend on, directly or not, ignoring missing
+    /// nodes
+    fn upstream_handles(&self, roots: Vec<Handle<Node>>) -> Vec<Handle<Node>> {
+        let graph = self.dependency_graph();
+        let mut visited = vec![false; self.nodes.capacity()];
+        let mut res = Vec::new();
+        let mut queue = VecDeque::from(roots);
+        while let Some(handle) = queue.pop_front() {
+            if visited[handle.index()] {
+                continue;
+            }
+            visited[handle.index()] = true;
+            res.push(handle);
+            queue.extend(graph.dependencies(handle));
+        }
+        res
+    }
+
+    fn sorted_node_ids(&self, handles: impl Iterator<Item = Handle<Node>>) -> Vec<String> {
+        let mut ids: Vec<String> = handles
+            .map(|handle| self.nodes[handle].unique_id().clone())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// Ids of the nodes the node `id` depends on, directly or not, sorted
+    pub fn upstream_nodes(&self, id: &str) -> Result<Vec<String>, ShadyError> {
+        let handle = self.node_handle(id)?;
+        let upstream = self.upstream_handles(vec![handle]);
+        Ok(self.sorted_node_ids(upstream.into_iter().skip(1)))
+    }
+
+    /// Constant and input property connections feeding the node `id` and its dependencies,
+    /// sorted
+    pub fn roots(&self, id: &str) -> Result<Vec<Connection>, ShadyError> {
+        let handle = self.node_handle(id)?;
+        let mut roots = BTreeSet::new();
+        for handle in self.upstream_handles(vec![handle]) {
+            for (_, connection) in self.nodes[handle].connections() {
+                if matches!(
+                    connection,
+                    Connection::Constant { .. } | Connection::InputProperty { .. }
+                ) {
+                    roots.insert(connection.clone());
+                }
+            }
+        }
+        Ok(roots.into_iter().collect())
+    }
+
+    /// Ids of the nodes contributing to the output property `id`, sorted
+    pub fn contributing_nodes(&self, id: &str) -> Result<Vec<String>, ShadyError> {
+        let property = self
+            .output_properties
+            .get_by_name(id)
+            .ok_or_else(|| ShadyError::MissingOutputProperty(id.to_string()))?;
+        let roots = match &property.connection {
+            Some(
+                Connection::ComplexOutputNode { id, .. } | Connection::SingleOutputNode { id },
+            ) => vec![self.node_handle(id)?],
+            Some(Connection::Constant { .. } | Connection::InputProperty { .. }) | None => {
+                Vec::new()
+            }
+        };
+        Ok(self.sorted_node_ids(self.upstream_handles(roots).into_iter()))
+    }
+
+    /// Ids of the nodes and output properties using the element `id`, directly or not
+    fn downstream(&self, id: &str) -> (BTreeSet<&String>, BTreeSet<&String>) {
+        let mut nodes = BTreeSet::new();
+        let mut output_properties = BTreeSet::new();
+        let mut queue = VecDeque::from([id]);
+        while let Some(id) = queue.pop_front() {
+            for (to, _) in self.consumer_index().get(id) {
+                match to {
+                    ConnectionTo::Node { id, .. } => {
+                        if nodes.insert(id) {
+                            queue.push_back(id);
+                        }
+                    }
+                    ConnectionTo::OutputProperty { id } => {
+                        output_properties.insert(id);
+                    }
+                }
+            }
+        }
+        (nodes, output_properties)
+    }
+
+    /// Ids of the nodes using the constant, property or node `id`, directly or not, sorted
+    pub fn downstream_nodes(&self, id: &str) -> Vec<String> {
+        self.downstream(id).0.into_iter().cloned().collect()
+    }
+
+    /// Ids of the output properties using the constant, property or node `id`, directly or not,
+    /// sorted
+    pub fn downstream_output_properties(&self, id: &str) -> Vec<String> {
+        self.downstream(id).1.into_iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shader::test_helpers::{input_property, node_field, node_output, output_property};
+    use crate::shader::to_glsl::tests::init_example_shader_1;
+
+    fn assert_index_is_consistent(shader: &mut Shader) {
+        let mut ids: Vec<String> = shader.consumer_index().consumers.keys().cloned().collect();
+        ids.sort();
+        let consumers: Vec<Vec<_>> = ids
+            .iter()
+            .map(|id| shader.consumer_index().get(id).cloned().collect())
+            .collect();
+        shader.index_consumers();
+        let mut rebuilt_ids: Vec<String> =
+            shader.consumer_index().consumers.keys().cloned().collect();
+        rebuilt_ids.sort();
+        assert_eq!(ids, rebuilt_ids);
+        for (id, consumers) in ids.iter().zip(consumers) {
+            let rebuilt: Vec<_> = shader.consumer_index().get(id).cloned().collect();
+            assert_eq!(consumers, rebuilt);
+        }
+    }
+
+    #[test]
+    fn finds_consumers() {
+        let shader = init_example_shader_1();
+        let consumers: Vec<&ConnectionTo> = shader
+            .consumers("a")
+            .into_iter()
+            .map(|(to, _)| to)
+            .collect();
+        assert_eq!(
+            consumers,
+            vec![
+                &node_field("b", "y"),
+                &node_field("c", "x"),
+                &output_property("o_1"),
+            ]
+        );
+        assert_eq!(
+            shader.connection_consumers(&node_output("d")),
+            vec![&output_property("o_3")]
+        );
+        assert!(shader.consumers("o_3").is_empty());
+    }
+
+    #[test]
+    fn queries_graph() {
+        let shader = init_example_shader_1();
+        assert_eq!(shader.upstream_nodes("d").unwrap(), vec!["a", "b", "c"]);
+        assert!(shader.upstream_nodes("a").unwrap().is_empty());
+        assert_eq!(shader.roots("d").unwrap(), vec![input_property("i")]);
+        assert_eq!(
+            shader.contributing_nodes("o_2").unwrap(),
+            vec!["a", "b", "c"]
+        );
+        assert_eq!(shader.downstream_nodes("b"), vec!["c", "d"]);
+        assert_eq!(shader.downstream_nodes("i"), vec!["a", "b", "c", "d"]);
+        assert_eq!(shader.downstream_output_properties("b"), vec!["o_2", "o_3"]);
+        assert!(matches!(
+            shader.upstream_nodes("missing"),
+            Err(ShadyError::MissingNode(_))
+        ));
+    }
+
+    #[test]
+    fn index_follows_changes() {
+        let mut shader = init_example_shader_1();
+        assert_index_is_consistent(&mut shader);
+        shader.disconnect(node_field("c", "x")).unwrap();
+        assert_index_is_consistent(&mut shader);
+        let (_, severed) = shader.remove_node("b").unwrap();
+        assert_eq!(severed.len(), 4);
+        assert!(shader.consumers("b").is_empty());
+        assert_index_is_consistent(&mut shader);
+        shader.remove_output_property("o_1");
+        assert_index_is_consistent(&mut shader);
+        shader.remove_input_property("i").unwrap();
+        assert!(shader.consumers("i").is_empty());
+        assert_index_is_consistent(&mut shader);
+        assert_eq!(
+            shader.downstream_output_properties("a"),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn deserialized_shaders_are_indexed() {
+        let data = serde_yaml::to_string(&init_example_shader_1()).unwrap();
+        let mut shader: Shader = serde_yaml::from_str(&data).unwrap();
+        let (_, severed) = shader.remove_node("b").unwrap();
+        assert_eq!(severed.len(), 4);
+        assert!(shader.consumers("b").is_empty());
+        let node = shader.nodes.get_by_name("c").unwrap();
+        assert!(node.connections().iter().all(|(field, _)| *field != "y"));
+        assert_index_is_consistent(&mut shader);
+    }
+}
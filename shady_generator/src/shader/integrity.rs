@@ -0,0 +1,232 @@
+use crate::{
+    Diagnostic, DiagnosticKind, Handle, Node, OutputProperty, Shader, ShaderElement, SubGraph,
+};
+
+/// Handling of broken connections when loading a save file
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LoadMode {
+    /// Any broken connection, or other error level diagnostic, fails the loading with a
+    /// `ShadyError::BrokenSave`. Warnings such as reconciled node outputs are reported
+    Strict,
+    /// Broken connections are dropped and reported
+    Lenient,
+}
+
+impl Shader {
+    /// Recomputes the nodes input fields and output from their operation, then checks every
+    /// connection endpoint and type.
+    ///
+    /// Broken connections are removed and reported, the sub-graphs are repaired the same way
+    pub fn repair(&mut self) -> Vec<Diagnostic> {
+        let mut report = Vec::new();
+        let mut nodes: Vec<(String, Handle<Node>)> = self
+            .nodes
+            .entries()
+            .map(|(handle, id, _)| (id.clone(), handle))
+            .collect();
+        nodes.sort();
+        for (id, handle) in &nodes {
+            let node = &mut self.nodes[*handle];
+            let saved = node.output().clone();
+            for (field, connection) in node.reconcile_operation() {
+                report.push(Diagnostic::new(
+                    ShaderElement::Node(id.clone()),
+                    Some(field),
+                    DiagnosticKind::UnknownField(connection),
+                ));
+            }
+            if *node.output() != saved {
+                log::warn!("Replacing saved output of node {}", id);
+                report.push(Diagnostic::new(
+                    ShaderElement::Node(id.clone()),
+                    None,
+                    DiagnosticKind::ReconciledOutput {
+                        output: node.output().clone(),
+                        saved,
+                    },
+                ));
+            }
+        }
+        for (id, handle) in nodes {
+            let broken: Vec<(String, DiagnosticKind)> = self.nodes[handle]
+                .input_fields()
+                .into_iter()
+                .filter_map(|(field_name, field)| {
+                    let kind =
+                        self.check_connection(field.connection.as_ref()?, &field.accepted_types())?;
+                    Some((field_name, kind))
+                })
+                .collect();
+            let node = &mut self.nodes[handle];
+            for (field_name, kind) in broken {
+                log::warn!(
+                    "Removing broken connection of node {} field {}",
+                    id,
+                    field_name
+                );
+                node.disconnect_field(&field_name).unwrap();
+                report.push(Diagnostic::new(
+                    ShaderElement::Node(id.clone()),
+                    Some(field_name),
+                    kind,
+                ));
+            }
+        }
+        let mut output: Vec<(String, Handle<OutputProperty>)> = self
+            .output_properties
+            .entries()
+            .map(|(handle, id, _)| (id.clone(), handle))
+            .collect();
+        output.sort();
+        for (id, handle) in output {
+            let property = &self.output_properties[handle];
+            let kind = property
+                .connection
+                .as_ref()
+                .and_then(|c| self.check_connection(c, &[property.native_type]));
+            if let Some(kind) = kind {
+                log::warn!("Removing broken connection of output property {}", id);
+                self.output_properties[handle].disconnect();
+                report.push(Diagnostic::new(
+                    ShaderElement::OutputProperty(id),
+                    None,
+                    kind,
+                ));
+            }
+        }
+        self.index_consumers();
+        let mut sub_graphs: Vec<(String, Handle<SubGraph>)> = self
+            .sub_graphs
+            .entries()
+            .map(|(handle, name, _)| (name.clone(), handle))
+            .collect();
+        sub_graphs.sort();
+        for (name, handle) in sub_graphs {
+            let diagnostics = self.sub_graphs[handle].graph_mut().repair();
+            report.extend(diagnostics.into_iter().map(|d| d.in_sub_graph(&name)));
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ShadyError;
+    use crate::node_operation::NodeOperation;
+    use crate::shader::test_helpers::{connect, node_output, output_property};
+    use crate::shader::to_glsl::tests::init_example_shader_1;
+    use crate::tests::test_library;
+    use crate::{Connection, NativeType, NonScalarNativeType, Output, SaveFormat};
+
+    const BROKEN_SAVE: &str = "test/broken_shader.yaml";
+
+    #[test]
+    fn valid_shader_needs_no_repair() {
+        let mut shader = init_example_shader_1();
+        assert!(shader.repair().is_empty());
+    }
+
+    #[test]
+    fn strict_load_fails_with_broken_save() {
+        match Shader::load_with_mode(BROKEN_SAVE, LoadMode::Strict) {
+            Err(ShadyError::BrokenSave(report)) => assert_eq!(report.len(), 5),
+            res => panic!("Expected BrokenSave, got {:?}", res.map(|(_, r)| r)),
+        }
+    }
+
+    #[test]
+    fn strict_load_accepts_stale_node_outputs() {
+        let mut shader = Shader::default();
+        shader
+            .add_output_property(OutputProperty::new(NativeType::Vec2, "o"))
+            .unwrap();
+        shader
+            .create_node(Node::new_with_custom_id(
+                "V",
+                "v",
+                NodeOperation::TypeConstruction(NonScalarNativeType::Vec2),
+            ))
+            .unwrap();
+        connect(&mut shader, node_output("v"), output_property("vec2_o"));
+        let mut value = serde_yaml::to_value(shader).unwrap();
+        value["nodes"]["v"]["output"] =
+            serde_yaml::to_value(Output::NativeType(NativeType::Bool)).unwrap();
+        let data = serde_yaml::to_string(&value).unwrap();
+        let (_, report) =
+            Shader::from_str_with_mode(&data, SaveFormat::Yaml, LoadMode::Strict).unwrap();
+        assert_eq!(report.len(), 1);
+        assert!(matches!(
+            report[0].kind,
+            DiagnosticKind::ReconciledOutput {
+                saved: Output::NativeType(NativeType::Bool),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn default_load_is_lenient() {
+        let shader = Shader::load(BROKEN_SAVE).unwrap();
+        assert!(shader
+            .validate(&test_library())
+            .iter()
+            .all(|d| !d.is_error()));
+    }
+
+    #[test]
+    fn lenient_load_drops_broken_connections() {
+        let (shader, report) = Shader::load_with_mode(BROKEN_SAVE, LoadMode::Lenient).unwrap();
+        let node = |id: &str| ShaderElement::Node(id.to_string());
+        assert_eq!(
+            report,
+            vec![
+                Diagnostic::new(
+                    node("a"),
+                    Some("z".to_string()),
+                    DiagnosticKind::UnknownField(Connection::InputProperty {
+                        id: "i".to_string()
+                    })
+                ),
+                Diagnostic::new(
+                    node("a"),
+                    None,
+                    DiagnosticKind::ReconciledOutput {
+                        saved: Output::NativeType(NativeType::Float),
+                        output: Output::NativeType(NativeType::Vec2),
+                    }
+                ),
+                Diagnostic::new(
+                    node("a"),
+                    Some("x".to_string()),
+                    DiagnosticKind::TypeMismatch {
+                        connection: Connection::InputProperty {
+                            id: "v".to_string()
+                        },
+                        input_type: NativeType::Vec2,
+                        expected_types: vec![NativeType::Float],
+                    }
+                ),
+                Diagnostic::new(
+                    node("b"),
+                    Some("x".to_string()),
+                    DiagnosticKind::DanglingConnection(Connection::SingleOutputNode {
+                        id: "missing".to_string()
+                    })
+                ),
+                Diagnostic::new(
+                    ShaderElement::OutputProperty("o".to_string()),
+                    None,
+                    DiagnosticKind::DanglingConnection(Connection::Constant {
+                        id: "MISSING".to_string()
+                    })
+                ),
+            ]
+        );
+        assert!(shader
+            .validate(&test_library())
+            .iter()
+            .all(|d| !d.is_error()));
+        assert!(shader.to_glsl(&test_library()).is_ok());
+    }
+}
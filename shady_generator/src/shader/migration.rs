@@ -0,0 +1,177 @@
+use crate::error::ShadyError;
+use serde_yaml::{Mapping, Value};
+
+/// Current version of the save file format
+pub const SAVE_FORMAT_VERSION: u32 = 2;
+const FORMAT_VERSION_KEY: &str = "format_version";
+
+type Migration = fn(&mut Mapping) -> Result<(), ShadyError>;
+
+/// Migrations upgrading a save document from version `n` to `n + 1`, indexed by `n`
+const MIGRATIONS: [Migration; SAVE_FORMAT_VERSION as usize] = [migrate_v0_to_v1, migrate_v1_to_v2];
+
+/// Version 0 saves have no `format_version`, no GLSL version and precision fields, and a
+/// `max_processing_depth` no longer used by the node generation.
+/// OpenGL ES saves target GLSL ES 3.00, the others keep the default GLSL version
+// Every migration shares the `Migration` signature to be stored in `MIGRATIONS`
+#[allow(clippy::unnecessary_wraps)]
+fn migrate_v0_to_v1(document: &mut Mapping) -> Result<(), ShadyError> {
+    document.remove(&Value::from("max_processing_depth"));
+    let glsl_version = Value::from("glsl_version");
+    if !document.contains_key(&glsl_version)
+        && document.get(&Value::from("library")) == Some(&Value::from("OpenGlEs"))
+    {
+        document.insert(glsl_version, Value::from("GlslEs300"));
+    }
+    if !document.contains_key(&Value::from("default_precisions")) {
+        document.insert(
+            Value::from("default_precisions"),
+            Value::Mapping(Mapping::new()),
+        );
+    }
+    Ok(())
+}
+
+/// Version 1 swizzles only apply to float vectors and have no component type, in the shader
+/// nodes and in the sub-graphs
+fn migrate_v1_to_v2(document: &mut Mapping) -> Result<(), ShadyError> {
+    if let Some(Value::Mapping(nodes)) = document.get_mut(&Value::from("nodes")) {
+        for (_, node) in nodes.iter_mut() {
+            let swizzle = node
+                .get_mut("operation")
+                .and_then(|operation| operation.get_mut("NonScalarSwizzle"))
+                .and_then(Value::as_mapping_mut);
+            for (_, fields) in swizzle.into_iter().flat_map(Mapping::iter_mut) {
+                let previous = std::mem::take(fields);
+                *fields = Value::Sequence(vec![Value::from("Float"), previous]);
+            }
+        }
+    }
+    if let Some(Value::Mapping(sub_graphs)) = document.get_mut(&Value::from("sub_graphs")) {
+        for (_, sub_graph) in sub_graphs.iter_mut() {
+            if let Some(Value::Mapping(graph)) = sub_graph.get_mut("graph") {
+                migrate_v1_to_v2(graph)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Upgrades a save `document` step by step to the `SAVE_FORMAT_VERSION`
+pub(super) fn migrate(mut document: Value) -> Result<Value, ShadyError> {
+    let mapping = document
+        .as_mapping_mut()
+        .ok_or_else(|| ShadyError::InvalidSaveFormat("expected a mapping".to_string()))?;
+    let version = match mapping.get(&Value::from(FORMAT_VERSION_KEY)) {
+        None => 0,
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| {
+                ShadyError::InvalidSaveFormat(format!("invalid {} {:?}", FORMAT_VERSION_KEY, v))
+            })?,
+    };
+    if version > SAVE_FORMAT_VERSION {
+        return Err(ShadyError::UnsupportedSaveVersion {
+            version,
+            supported: SAVE_FORMAT_VERSION,
+        });
+    }
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        log::info!("Migrating save file from version {} to {}", from, from + 1);
+        migration(mapping)?;
+    }
+    mapping.insert(
+        Value::from(FORMAT_VERSION_KEY),
+        Value::from(SAVE_FORMAT_VERSION),
+    );
+    Ok(document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::test_library;
+    use crate::{GlslVersion, GraphicLibrary, LoadMode, Shader};
+
+    fn load_fixture(version: u32) -> Shader {
+        let path = format!("test/migrations/shader_example_1_v{}.yaml", version);
+        let (shader, report) = Shader::load_with_mode(path, LoadMode::Strict).unwrap();
+        assert!(report.is_empty());
+        shader
+    }
+
+    #[test]
+    fn migrates_every_version() {
+        let current = load_fixture(SAVE_FORMAT_VERSION);
+        for version in 0..SAVE_FORMAT_VERSION {
+            let shader = load_fixture(version);
+            assert_eq!(shader.format_version(), SAVE_FORMAT_VERSION);
+            assert_eq!(shader.glsl_version, GlslVersion::default());
+            assert_eq!(
+                shader.to_glsl(&test_library()).unwrap(),
+                current.to_glsl(&test_library()).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn migrates_es_glsl_version() {
+        let (shader, report) = Shader::load_with_mode(
+            "test/migrations/shader_example_1_es_v0.yaml",
+            LoadMode::Strict,
+        )
+        .unwrap();
+        assert!(report.is_empty());
+        assert_eq!(shader.library, GraphicLibrary::OpenGlEs);
+        assert_eq!(shader.glsl_version, GlslVersion::GlslEs300);
+        assert!(shader
+            .to_glsl(&test_library())
+            .unwrap()
+            .starts_with(GlslVersion::GlslEs300.header()));
+    }
+
+    #[test]
+    fn migrates_swizzle_component_type() {
+        let swizzle = "{ operation: { NonScalarSwizzle: { Vec3ToVec2: [X, Z] } } }";
+        let document = serde_yaml::from_str(&format!(
+            "{}: 1\nnodes: {{ a: {} }}\nsub_graphs: {{ s: {{ graph: {{ nodes: {{ b: {} }} }} }} }}",
+            FORMAT_VERSION_KEY, swizzle, swizzle
+        ))
+        .unwrap();
+        let document = migrate(document).unwrap();
+        let expected: Value = serde_yaml::from_str("[Float, [X, Z]]").unwrap();
+        assert_eq!(
+            document["nodes"]["a"]["operation"]["NonScalarSwizzle"]["Vec3ToVec2"],
+            expected
+        );
+        assert_eq!(
+            document["sub_graphs"]["s"]["graph"]["nodes"]["b"]["operation"]["NonScalarSwizzle"]
+                ["Vec3ToVec2"],
+            expected
+        );
+    }
+
+    #[test]
+    fn rejects_future_version() {
+        let document = serde_yaml::from_str(&format!(
+            "{}: {}\nname: Future",
+            FORMAT_VERSION_KEY,
+            SAVE_FORMAT_VERSION + 1
+        ))
+        .unwrap();
+        assert!(matches!(
+            migrate(document),
+            Err(ShadyError::UnsupportedSaveVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_version() {
+        let document = serde_yaml::from_str(&format!("{}: latest", FORMAT_VERSION_KEY)).unwrap();
+        assert!(matches!(
+            migrate(document),
+            Err(ShadyError::InvalidSaveFormat(_))
+        ));
+    }
+}
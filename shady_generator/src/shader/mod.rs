@@ -1,126 +1,308 @@
-pub use {constant::*, property::*, shader_type::*, to_glsl::*};
+pub use {
+    constant::*, glsl_version::*, integrity::*, migration::SAVE_FORMAT_VERSION, naming::*,
+    precision::*, property::*, save_format::*, shader_type::*, sub_graph::*, to_glsl::*,
+    validation::*,
+};
 
 mod constant;
+mod glsl_version;
+mod graph_index;
+mod integrity;
+mod migration;
+mod naming;
+mod node_generation;
 mod precision;
 mod property;
+mod ron_document;
+mod save_format;
 mod shader_type;
+mod sub_graph;
+#[cfg(test)]
+mod test_helpers;
 mod to_glsl;
+mod to_wgsl;
+mod validation;
 
-use crate::shader::precision::ShaderPrecision;
+use crate::node_operation::{FunctionLibrary, NodeOperation};
 use crate::ShadyError::{DuplicateConstant, DuplicateInputProperty, DuplicateOutputProperty};
 use crate::{
-    ordered_map, Connection, ConnectionAttempt, ConnectionMessage, ConnectionResponse,
-    ConnectionTo, GraphicLibrary, NativeType, Node, OutputFields, ShadyError,
+    check_identifier, Connection, ConnectionAttempt, ConnectionMessage, ConnectionResponse,
+    ConnectionTo, GraphicLibrary, Handle, IdGenerator, IterMut, NativeType, Node, OutputFields,
+    SeveredConnections, ShadyError, SlotMap,
 };
+use graph_index::ConsumerIndex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::fs::{read_to_string, OpenOptions};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::fs::OpenOptions;
 use std::io::Write;
 use std::path::Path;
+use std::sync::OnceLock;
 
-const DEFAULT_MAX_DEPTH: usize = 256;
 const EXPORT_HEADER: &str =
     "Generated by shady-rs -- https://github.com/ManevilleF/shady-rs made by @ManevilleF";
 
 const SAVE_HEADER: &str =
     "Save file for shady-rs -- https://github.com/ManevilleF/shady-rs made by @ManevilleF";
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Shader {
+    format_version: u32,
     pub name: String,
     #[serde(default)]
     pub library: GraphicLibrary,
     #[serde(default)]
+    pub glsl_version: GlslVersion,
+    #[serde(default)]
     pub shader_type: ShaderType,
+    /// Default precision statements, only applied to OpenGL ES targets
+    #[serde(default)]
     pub default_precisions: HashMap<NativeType, ShaderPrecision>,
-    #[serde(serialize_with = "ordered_map")]
-    constants: HashMap<String, Constant>,
-    #[serde(serialize_with = "ordered_map")]
-    input_properties: HashMap<String, InputProperty>,
-    #[serde(serialize_with = "ordered_map")]
-    output_properties: HashMap<String, OutputProperty>,
-    #[serde(serialize_with = "ordered_map")]
-    nodes: HashMap<String, Node>,
-    pub max_processing_depth: usize,
+    /// Source of the node ids and property references
+    #[serde(default)]
+    pub id_generator: IdGenerator,
+    /// Naming of the variables in the generated code
+    #[serde(default)]
+    pub naming_strategy: NamingStrategy,
+    constants: SlotMap<Constant>,
+    input_properties: SlotMap<InputProperty>,
+    output_properties: SlotMap<OutputProperty>,
+    /// Output property written to the vertex position, see `Shader::set_vertex_position`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    vertex_position: Option<String>,
+    nodes: SlotMap<Node>,
+    /// Reusable graphs instantiated by the nodes and generated as functions, by name
+    #[serde(default, skip_serializing_if = "SlotMap::is_empty")]
+    sub_graphs: SlotMap<SubGraph>,
+    /// Reverse adjacency index, built on first use as deserialized shaders aren't indexed, see
+    /// `Shader::consumer_index`
+    #[serde(skip)]
+    consumers: OnceLock<ConsumerIndex>,
 }
 
 impl Shader {
     pub fn create_node(&mut self, node: Node) -> Result<&Node, ShadyError> {
         let id = node.unique_id().clone();
-        if self.nodes.contains_key(&id) {
+        if self.nodes.contains_name(&id) {
             return Err(ShadyError::DuplicateNode(id));
         }
-        self.nodes.insert(id.clone(), node);
-        Ok(self.get_node(&id).unwrap())
+        self.check_new_identifier(&id)?;
+        for (field_name, connection) in node.connections() {
+            let to = ConnectionTo::Node {
+                id: id.clone(),
+                field_name: field_name.clone(),
+            };
+            self.consumer_index_mut().insert(to, connection.clone());
+        }
+        let (handle, _) = self.nodes.insert(id, node);
+        Ok(&self.nodes[handle])
+    }
+
+    /// Identifiers of every constant, property, node and sub-graph, as used in the generated code
+    fn identifiers(&self) -> impl Iterator<Item = &String> {
+        self.constants
+            .keys()
+            .chain(self.input_properties.keys())
+            .chain(self.output_properties.keys())
+            .chain(self.nodes.keys())
+            .chain(self.sub_graphs.keys())
+    }
+
+    /// Checks that `identifier` is valid and not used by any other shader element
+    fn check_new_identifier(&self, identifier: &str) -> Result<(), ShadyError> {
+        check_identifier(identifier)?;
+        if self.identifiers().any(|i| i == identifier) {
+            return Err(ShadyError::IdentifierConflict(identifier.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Checks that every constant, property, node and sub-graph identifier is valid and unique
+    /// across the shader elements
+    pub fn check_identifiers(&self) -> Result<(), ShadyError> {
+        let mut identifiers: Vec<&String> = self.identifiers().collect();
+        identifiers.sort();
+        if let Some(pair) = identifiers.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(ShadyError::IdentifierConflict(pair[0].clone()));
+        }
+        identifiers
+            .into_iter()
+            .try_for_each(|i| check_identifier(i))
+    }
+
+    /// Generates an id from the `id_generator`, skipping the identifiers already used by a shader
+    /// element and the ids suffixing a property reference
+    pub fn generate_id(&mut self) -> String {
+        loop {
+            let id = self.id_generator.next_id();
+            if !self.is_id_used(&id) {
+                return id;
+            }
+            log::debug!("Skipping already used id {}", id);
+        }
+    }
+
+    fn is_id_used(&self, id: &str) -> bool {
+        let suffix = format!("_{}", id);
+        self.identifiers().any(|identifier| identifier == id)
+            || self
+                .input_properties
+                .keys()
+                .chain(self.output_properties.keys())
+                .any(|reference| reference.ends_with(&suffix))
+    }
+
+    /// Instantiates a node with an id from the `id_generator`, to add with `Shader::create_node`
+    pub fn new_node(&mut self, name: &str, operation: NodeOperation) -> Node {
+        let id = self.generate_id();
+        Node::new_with_custom_id(name, &id, operation)
+    }
+
+    /// Instantiates an input property with an id from the `id_generator`, to add with
+    /// `Shader::add_input_property`
+    pub fn new_input_property(&mut self, native_type: NativeType, uniform: bool) -> InputProperty {
+        let id = self.generate_id();
+        InputProperty::new(native_type, uniform, &id)
     }
 
-    pub fn remove_node(&mut self, id: &str) -> Option<Node> {
-        match self.nodes.remove(id) {
+    /// Instantiates an output property with an id from the `id_generator`, to add with
+    /// `Shader::add_output_property`
+    pub fn new_output_property(&mut self, native_type: NativeType) -> OutputProperty {
+        let id = self.generate_id();
+        OutputProperty::new(native_type, &id)
+    }
+
+    /// Removes the node with `id` and disconnects its inputs and every field or output property
+    /// using it. The node is returned with the severed connections
+    pub fn remove_node(&mut self, id: &str) -> Option<(Node, SeveredConnections)> {
+        match self.nodes.remove_by_name(id) {
             None => {
                 log::error!("Could not find node with id {} to remove", id);
                 None
             }
-            Some(n) => Some(n),
+            Some(mut n) => {
+                let mut severed: SeveredConnections = n
+                    .disconnect_matching(|_| true)
+                    .into_iter()
+                    .map(|(field_name, c)| {
+                        let to = ConnectionTo::Node {
+                            id: id.to_string(),
+                            field_name,
+                        };
+                        self.consumer_index_mut().remove(to.clone(), c.clone());
+                        (to, c)
+                    })
+                    .collect();
+                severed.extend(self.disconnect_consumers(id, |c| match c {
+                    Connection::ComplexOutputNode { id: node_id, .. }
+                    | Connection::SingleOutputNode { id: node_id } => node_id == id,
+                    Connection::Constant { .. } | Connection::InputProperty { .. } => false,
+                }));
+                severed.sort();
+                Some((n, severed))
+            }
         }
     }
 
-    fn get_node(&self, id: &str) -> Result<&Node, ShadyError> {
-        self.nodes
-            .get(id)
-            .ok_or_else(|| ShadyError::MissingNode(id.to_string()))
+    /// Disconnects every node field and output property connected to the element `id` with a
+    /// connection matching `predicate`, found through the consumer index
+    fn disconnect_consumers(
+        &mut self,
+        id: &str,
+        predicate: impl Fn(&Connection) -> bool,
+    ) -> SeveredConnections {
+        let (matching, others): (Vec<_>, Vec<_>) = self
+            .consumer_index_mut()
+            .take(id)
+            .into_iter()
+            .partition(|(_, c)| predicate(c));
+        for (to, c) in others {
+            self.consumer_index_mut().insert(to, c);
+        }
+        let mut severed = Vec::new();
+        for (to, connection) in matching {
+            let removed = match &to {
+                ConnectionTo::Node { id, field_name } => self
+                    .nodes
+                    .get_mut_by_name(id)
+                    .and_then(|node| node.disconnect_field(field_name).ok().flatten()),
+                ConnectionTo::OutputProperty { id } => self
+                    .output_properties
+                    .get_mut_by_name(id)
+                    .and_then(OutputProperty::disconnect),
+            };
+            if let Some(c) = removed {
+                severed.push((to, c));
+            } else {
+                log::warn!("Indexed connection {:?} to {:?} not found", connection, to);
+            }
+        }
+        severed
     }
 
-    pub const fn nodes(&self) -> &HashMap<String, Node> {
+    /// Nodes by id. The `SlotMap` iterates `(id, node)` pairs like the former
+    /// `HashMap<String, Node>`, and is also indexed by `Handle<Node>`
+    pub const fn nodes(&self) -> &SlotMap<Node> {
         &self.nodes
     }
 
-    fn get_constant(&self, id: &str) -> Result<&Constant, ShadyError> {
-        self.constants
-            .get(id)
-            .ok_or_else(|| ShadyError::MissingConstant(id.to_string()))
-    }
-
-    pub const fn constants(&self) -> &HashMap<String, Constant> {
+    /// Constants by id, in a `SlotMap` replacing the former `HashMap<String, Constant>`
+    pub const fn constants(&self) -> &SlotMap<Constant> {
         &self.constants
     }
 
-    pub fn constants_mut(&mut self) -> &mut HashMap<String, Constant> {
-        &mut self.constants
+    /// Constants by id with mutable values. Constants are added and removed through
+    /// `Shader::add_constant` and `Shader::remove_constant`, which keep their consumers in sync
+    pub fn constants_mut(&mut self) -> IterMut<'_, Constant> {
+        self.constants.iter_mut()
     }
 
-    fn get_input_property(&self, id: &str) -> Result<&InputProperty, ShadyError> {
-        self.input_properties
-            .get(id)
-            .ok_or_else(|| ShadyError::MissingInputProperty(id.to_string()))
+    pub const fn input_properties(&self) -> &SlotMap<InputProperty> {
+        &self.input_properties
     }
 
-    pub const fn input_properties(&self) -> &HashMap<String, InputProperty> {
-        &self.input_properties
+    pub const fn output_properties(&self) -> &SlotMap<OutputProperty> {
+        &self.output_properties
     }
 
-    fn get_output_property(&self, id: &str) -> Result<&OutputProperty, ShadyError> {
-        self.output_properties
-            .get(id)
-            .ok_or_else(|| ShadyError::MissingOutputProperty(id.to_string()))
+    /// Id of the output property written to the vertex position
+    pub fn vertex_position(&self) -> Option<&str> {
+        self.vertex_position.as_deref()
     }
 
-    pub const fn output_properties(&self) -> &HashMap<String, OutputProperty> {
-        &self.output_properties
+    /// Designates the `vec4` output property `id` as the vertex position, declared as the
+    /// `@builtin(position)` member of the WGSL vertex shader output
+    pub fn set_vertex_position(&mut self, id: Option<String>) -> Result<(), ShadyError> {
+        if let Some(id) = &id {
+            let property = self
+                .output_properties
+                .get_by_name(id)
+                .ok_or_else(|| ShadyError::MissingOutputProperty(id.clone()))?;
+            if property.native_type != NativeType::Vec4 {
+                return Err(ShadyError::WrongNativeType {
+                    input_type: property.native_type,
+                    expected_types: vec![NativeType::Vec4],
+                });
+            }
+        }
+        self.vertex_position = id;
+        Ok(())
     }
 
     fn get_node_mut(&mut self, id: &str) -> Result<&mut Node, ShadyError> {
         self.nodes
-            .get_mut(id)
+            .get_mut_by_name(id)
             .ok_or_else(|| ShadyError::MissingNode(id.to_string()))
     }
 
     pub fn add_constant(&mut self, constant: Constant) -> Result<&Constant, ShadyError> {
         let id = constant.key();
-        if self.constants.contains_key(&id) {
+        if self.constants.contains_name(&id) {
             return Err(DuplicateConstant(id));
         }
-        self.constants.insert(id.clone(), constant);
-        Ok(self.get_constant(&id).unwrap())
+        self.check_new_identifier(&id)?;
+        let (handle, _) = self.constants.insert(id, constant);
+        Ok(&self.constants[handle])
     }
 
     pub fn add_input_property(
@@ -128,11 +310,12 @@ impl Shader {
         property: InputProperty,
     ) -> Result<&InputProperty, ShadyError> {
         let id = property.reference.clone();
-        if self.input_properties.contains_key(&id) {
+        if self.input_properties.contains_name(&id) {
             return Err(DuplicateInputProperty(id));
         }
-        self.input_properties.insert(id.clone(), property);
-        Ok(self.get_input_property(&id).unwrap())
+        self.check_new_identifier(&id)?;
+        let (handle, _) = self.input_properties.insert(id, property);
+        Ok(&self.input_properties[handle])
     }
 
     pub fn add_output_property(
@@ -140,118 +323,231 @@ impl Shader {
         property: OutputProperty,
     ) -> Result<&OutputProperty, ShadyError> {
         let id = property.reference.clone();
-        if self.output_properties.contains_key(&id) {
+        if self.output_properties.contains_name(&id) {
             return Err(DuplicateOutputProperty(id));
         }
-        self.output_properties.insert(id.clone(), property);
-        Ok(self.get_output_property(&id).unwrap())
+        self.check_new_identifier(&id)?;
+        if let Some(connection) = &property.connection {
+            let to = ConnectionTo::OutputProperty { id: id.clone() };
+            self.consumer_index_mut().insert(to, connection.clone());
+        }
+        let (handle, _) = self.output_properties.insert(id, property);
+        Ok(&self.output_properties[handle])
     }
 
-    pub fn remove_constant(&mut self, id: &str) -> Option<Constant> {
-        match self.constants.remove(id) {
+    /// Removes the constant with `id` and disconnects every field or output property using it.
+    /// The constant is returned with the severed connections
+    pub fn remove_constant(&mut self, id: &str) -> Option<(Constant, SeveredConnections)> {
+        match self.constants.remove_by_name(id) {
             None => {
                 log::error!("Could not find constant with id {} to remove", id);
                 None
             }
-            Some(n) => Some(n),
+            Some(n) => Some((
+                n,
+                self.disconnect_consumers(id, |c| matches!(c, Connection::Constant { .. })),
+            )),
         }
     }
-    pub fn remove_input_property(&mut self, id: &str) -> Option<InputProperty> {
-        match self.input_properties.remove(id) {
+
+    /// Removes the input property with `id` and disconnects every field or output property using
+    /// it. The property is returned with the severed connections
+    pub fn remove_input_property(
+        &mut self,
+        id: &str,
+    ) -> Option<(InputProperty, SeveredConnections)> {
+        match self.input_properties.remove_by_name(id) {
             None => {
                 log::error!("Could not find input property with id {} to remove", id);
                 None
             }
-            Some(n) => Some(n),
+            Some(n) => Some((
+                n,
+                self.disconnect_consumers(id, |c| matches!(c, Connection::InputProperty { .. })),
+            )),
         }
     }
 
     pub fn remove_output_property(&mut self, id: &str) -> Option<OutputProperty> {
-        match self.output_properties.remove(id) {
+        match self.output_properties.remove_by_name(id) {
             None => {
                 log::error!("Could not find output property with id {} to remove", id);
                 None
             }
-            Some(n) => Some(n),
+            Some(n) => {
+                if let Some(c) = &n.connection {
+                    let to = ConnectionTo::OutputProperty { id: id.to_string() };
+                    self.consumer_index_mut().remove(to, c.clone());
+                }
+                if self.vertex_position.as_deref() == Some(id) {
+                    self.vertex_position = None;
+                }
+                Some(n)
+            }
         }
     }
 
-    pub fn connect(
-        &mut self,
-        connection_attempt: ConnectionAttempt,
-    ) -> Result<ConnectionResponse, ShadyError> {
-        let glsl_type = match &connection_attempt.connection_from {
+    /// Path of node ids from `from` to `to` following node dependencies, each node depending on
+    /// the next one.
+    ///
+    /// Only the dependencies of the visited nodes are resolved, the search doesn't build the
+    /// whole `DependencyGraph`
+    fn dependency_path(&self, from: &str, to: &str) -> Option<Vec<String>> {
+        let from = self.nodes.handle(from)?;
+        let to = self.nodes.handle(to)?;
+        // Visited nodes, with the node they were reached from
+        let mut parents: HashMap<Handle<Node>, Option<Handle<Node>>> = HashMap::new();
+        parents.insert(from, None);
+        let mut queue = VecDeque::from([from]);
+        let mut dependencies = Vec::new();
+        while let Some(handle) = queue.pop_front() {
+            if handle == to {
+                let mut path = vec![handle];
+                while let Some(Some(parent)) = path.last().and_then(|h| parents.get(h)) {
+                    path.push(*parent);
+                }
+                return Some(
+                    path.into_iter()
+                        .rev()
+                        .map(|h| self.nodes[h].unique_id().clone())
+                        .collect(),
+                );
+            }
+            // Explore the lowest ids first
+            dependencies.clear();
+            dependencies.extend(self.nodes[handle].node_dependencies());
+            dependencies.sort_unstable();
+            dependencies.dedup();
+            for dependency in dependencies.iter().filter_map(|id| self.nodes.handle(id)) {
+                if let Entry::Vacant(entry) = parents.entry(dependency) {
+                    entry.insert(Some(handle));
+                    queue.push_back(dependency);
+                }
+            }
+        }
+        None
+    }
+
+    /// Native type of the element `connection` comes from
+    fn connection_type(&self, connection: &Connection) -> Result<NativeType, ShadyError> {
+        Ok(match connection {
             Connection::InputProperty { id } => {
                 self.input_properties
-                    .get(id)
+                    .get_by_name(id)
                     .ok_or_else(|| ShadyError::MissingInputProperty(id.clone()))?
                     .native_type
             }
             Connection::Constant { id } => self
                 .constants
-                .get(id)
+                .get_by_name(id)
                 .ok_or_else(|| ShadyError::MissingConstant(id.clone()))?
                 .native_type(),
             Connection::ComplexOutputNode { id, field_name } => {
                 let from_node = self
                     .nodes
-                    .get(id)
+                    .get_by_name(id)
                     .ok_or_else(|| ShadyError::MissingNode(id.clone()))?;
                 from_node.get_output_field(field_name)?
             }
             Connection::SingleOutputNode { id } => {
                 let from_node = self
                     .nodes
-                    .get(id)
+                    .get_by_name(id)
                     .ok_or_else(|| ShadyError::MissingNode(id.clone()))?;
                 match from_node.output_fields() {
                     OutputFields::SingleOutput(t) => t,
                     OutputFields::Fields(_) => return Err(ShadyError::ComplexOutput(id.clone())),
                 }
             }
-        };
+        })
+    }
+
+    /// Connects `connection_from` to `connection_to`, refusing connections closing a node loop
+    /// with a `ShadyError::CyclicConnection`
+    pub fn connect(
+        &mut self,
+        connection_attempt: ConnectionAttempt,
+    ) -> Result<ConnectionResponse, ShadyError> {
+        if let (
+            Connection::ComplexOutputNode { id: from, .. }
+            | Connection::SingleOutputNode { id: from },
+            ConnectionTo::Node { id: to, .. },
+        ) = (
+            &connection_attempt.connection_from,
+            &connection_attempt.connection_to,
+        ) {
+            if from != to {
+                if let Some(path) = self.dependency_path(from, to) {
+                    let mut cycle = vec![to.clone()];
+                    cycle.extend(path);
+                    return Err(ShadyError::CyclicConnection(cycle));
+                }
+            }
+        }
+        self.apply_connection(connection_attempt)
+    }
+
+    fn apply_connection(
+        &mut self,
+        connection_attempt: ConnectionAttempt,
+    ) -> Result<ConnectionResponse, ShadyError> {
+        let glsl_type = self.connection_type(&connection_attempt.connection_from)?;
         let connection_message = ConnectionMessage {
             connection: connection_attempt.connection_from.clone(),
             native_type: glsl_type,
         };
-        match connection_attempt.connection_to {
+        let previous = match &connection_attempt.connection_to {
             ConnectionTo::Node {
                 id,
                 field_name: field,
             } => {
-                let to_node = self.get_node_mut(&id)?;
-                to_node.connect_input(&field, connection_message)
+                let to_node = self.get_node_mut(id)?;
+                to_node.connect_input(field, connection_message)?
             }
             ConnectionTo::OutputProperty { id } => {
                 let property = self
                     .output_properties
-                    .get_mut(&id)
-                    .ok_or(ShadyError::MissingInputProperty(id))?;
-                property.connect_input(connection_message)
+                    .get_mut_by_name(id)
+                    .ok_or_else(|| ShadyError::MissingInputProperty(id.clone()))?;
+                property.connect_input(connection_message)?
             }
+        };
+        if let Some(previous) = &previous {
+            self.consumer_index_mut()
+                .remove(connection_attempt.connection_to.clone(), previous.clone());
         }
+        self.consumer_index_mut().insert(
+            connection_attempt.connection_to,
+            connection_attempt.connection_from,
+        );
+        Ok(previous)
     }
 
     pub fn disconnect(
         &mut self,
         connection_to: ConnectionTo,
     ) -> Result<Option<Connection>, ShadyError> {
-        match connection_to {
+        let removed = match &connection_to {
             ConnectionTo::Node {
                 id,
                 field_name: field,
             } => {
-                let to_node = self.get_node_mut(&id)?;
-                to_node.disconnect_field(&field)
+                let to_node = self.get_node_mut(id)?;
+                to_node.disconnect_field(field)?
             }
             ConnectionTo::OutputProperty { id } => {
                 let property = self
                     .output_properties
-                    .get_mut(&id)
-                    .ok_or(ShadyError::MissingInputProperty(id))?;
-                Ok(property.disconnect())
+                    .get_mut_by_name(id)
+                    .ok_or_else(|| ShadyError::MissingInputProperty(id.clone()))?;
+                property.disconnect()
             }
+        };
+        if let Some(removed) = &removed {
+            self.consumer_index_mut()
+                .remove(connection_to, removed.clone());
         }
+        Ok(removed)
     }
 
     pub fn new(name: String) -> Self {
@@ -261,16 +557,16 @@ impl Shader {
         }
     }
 
+    /// Saves the shader to `file_path` with the format matching its extension, falling back to
+    /// YAML for a missing or unknown extension. Use `Shader::save_to_as` to pick the format
     pub fn save_to<P: AsRef<Path>>(&self, file_path: P) -> Result<(), ShadyError> {
-        let mut file = OpenOptions::new()
-            .create(true)
-            .truncate(true)
-            .write(true)
-            .open(file_path)?;
-        let data = serde_yaml::to_string(&self)?;
-        let data = format!("# {}\n{}", SAVE_HEADER, data);
-        file.write_all(data.as_bytes())?;
-        Ok(())
+        let format = SaveFormat::from_path(&file_path).unwrap_or_default();
+        self.save_to_as(file_path, format)
+    }
+
+    /// Save file format version, always the current `SAVE_FORMAT_VERSION` once loaded
+    pub const fn format_version(&self) -> u32 {
+        self.format_version
     }
 
     pub fn safe_name(&self) -> String {
@@ -278,55 +574,314 @@ impl Shader {
     }
 
     pub fn shader_file_name(&self) -> String {
-        format!(
-            "{}.{}",
-            self.safe_name(),
-            match self.shader_type {
-                ShaderType::Vertex => "vert",
-                ShaderType::Fragment => "frag",
-            }
-        )
+        match self.library {
+            GraphicLibrary::OpenGl | GraphicLibrary::OpenGlEs => format!(
+                "{}.{}",
+                self.safe_name(),
+                match self.shader_type {
+                    ShaderType::Vertex => "vert",
+                    ShaderType::Fragment => "frag",
+                }
+            ),
+            GraphicLibrary::WebGPU => format!("{}.{}", self.safe_name(), "wgsl"),
+        }
     }
 
     pub fn save_file_name(&self) -> String {
-        format!("{}.yaml", self.safe_name(),)
+        format!("{}.{}", self.safe_name(), SaveFormat::default().extension())
     }
 
     pub fn save(&self) -> Result<(), ShadyError> {
         self.save_to(self.save_file_name())
     }
 
-    pub fn export_glsl_to<P: AsRef<Path>>(&self, file_path: P) -> Result<(), ShadyError> {
+    /// Exports the GLSL code of the shader to `file_path`, see `Shader::to_glsl`
+    pub fn export_glsl_to<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        functions: &FunctionLibrary,
+    ) -> Result<(), ShadyError> {
         let mut file = OpenOptions::new()
             .create(true)
             .write(true)
             .truncate(true)
             .open(file_path)?;
-        let data = self.to_glsl()?;
+        let data = self.to_glsl(functions)?;
         let data = format!("// {}\n{}", EXPORT_HEADER, data);
         file.write_all(data.as_bytes())?;
         Ok(())
     }
 
+    /// Exports the WGSL code of the shader to `file_path`, see `Shader::to_wgsl`
+    pub fn export_wgsl_to<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        functions: &FunctionLibrary,
+    ) -> Result<(), ShadyError> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(file_path)?;
+        let data = self.to_wgsl(functions)?;
+        let data = format!("// {}\n{}", EXPORT_HEADER, data);
+        file.write_all(data.as_bytes())?;
+        Ok(())
+    }
+
+    /// Generates the shader code for the target `library`, with custom functions declared from
+    /// `functions`
+    pub fn to_shader_code(&self, functions: &FunctionLibrary) -> Result<String, ShadyError> {
+        match self.library {
+            GraphicLibrary::OpenGl | GraphicLibrary::OpenGlEs => self.to_glsl(functions),
+            GraphicLibrary::WebGPU => self.to_wgsl(functions),
+        }
+    }
+
+    /// Exports the shader code to `file_path`, using the backend matching the target `library`
+    pub fn export_to<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        functions: &FunctionLibrary,
+    ) -> Result<(), ShadyError> {
+        match self.library {
+            GraphicLibrary::OpenGl | GraphicLibrary::OpenGlEs => {
+                self.export_glsl_to(file_path, functions)
+            }
+            GraphicLibrary::WebGPU => self.export_wgsl_to(file_path, functions),
+        }
+    }
+
+    /// Loads a save file, dropping and logging broken connections. Use
+    /// `Shader::load_with_mode` with `LoadMode::Strict` to fail on them instead
     pub fn load<P: AsRef<Path>>(file_path: P) -> Result<Self, ShadyError> {
-        let val = read_to_string(file_path)?;
-        let res = serde_yaml::from_str(&val)?;
-        Ok(res)
+        let (shader, report) = Self::load_with_mode(file_path, LoadMode::Lenient)?;
+        for diagnostic in report {
+            log::warn!("{}", diagnostic);
+        }
+        Ok(shader)
     }
 }
 
 impl Default for Shader {
     fn default() -> Self {
         Self {
+            format_version: SAVE_FORMAT_VERSION,
             name: "MyShader".to_string(),
             library: Default::default(),
+            glsl_version: Default::default(),
             shader_type: Default::default(),
             default_precisions: Default::default(),
+            id_generator: Default::default(),
+            naming_strategy: Default::default(),
             constants: Default::default(),
             input_properties: Default::default(),
             output_properties: Default::default(),
+            vertex_position: None,
             nodes: Default::default(),
-            max_processing_depth: DEFAULT_MAX_DEPTH,
+            sub_graphs: Default::default(),
+            consumers: Default::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shader::to_glsl::tests::init_example_shader_1;
+    use crate::tests::test_library;
+    use crate::{IdentifierIssue, NonScalarNativeType};
+
+    #[test]
+    fn refuses_cyclic_connection() {
+        let mut shader = init_example_shader_1();
+        let res = shader.connect(ConnectionAttempt {
+            connection_from: Connection::SingleOutputNode {
+                id: "d".to_string(),
+            },
+            connection_to: ConnectionTo::Node {
+                id: "a".to_string(),
+                field_name: "y".to_string(),
+            },
+        });
+        match res {
+            Err(ShadyError::CyclicConnection(cycle)) => {
+                assert_eq!(cycle, vec!["a", "d", "b", "a"]);
+            }
+            res => panic!("Expected CyclicConnection, got {:?}", res),
+        }
+        // The graph is left untouched
+        assert!(shader.to_glsl(&test_library()).is_ok());
+    }
+
+    #[test]
+    fn accepts_acyclic_connection() {
+        let mut shader = init_example_shader_1();
+        shader
+            .connect(ConnectionAttempt {
+                connection_from: Connection::SingleOutputNode {
+                    id: "a".to_string(),
+                },
+                connection_to: ConnectionTo::Node {
+                    id: "d".to_string(),
+                    field_name: "x".to_string(),
+                },
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn generated_ids_skip_sub_graph_names() {
+        let mut shader = Shader::default();
+        let next_id = shader.id_generator.clone().next_id();
+        shader.add_sub_graph(SubGraph::new(&next_id)).unwrap();
+        let node = shader.new_node(
+            "Vec",
+            NodeOperation::TypeConstruction(NonScalarNativeType::Vec2),
+        );
+        assert_ne!(node.unique_id(), &next_id);
+        assert!(shader.create_node(node).is_ok());
+    }
+
+    #[test]
+    fn identifiers_are_unique_across_elements() {
+        let mut shader = init_example_shader_1();
+        assert!(shader
+            .add_output_property(OutputProperty::new(NativeType::Float, "x"))
+            .is_ok());
+        let mut property = OutputProperty::new(NativeType::Float, "x");
+        property.reference = "a".to_string();
+        assert!(matches!(
+            shader.add_output_property(property),
+            Err(ShadyError::IdentifierConflict(id)) if id == "a"
+        ));
+        assert!(matches!(
+            shader.create_node(Node::new_with_custom_id(
+                "Main",
+                "main",
+                NodeOperation::TypeConstruction(NonScalarNativeType::Vec2),
+            )),
+            Err(ShadyError::InvalidIdentifier {
+                issue: IdentifierIssue::ReservedWord,
+                ..
+            })
+        ));
+        let constant = shader
+            .add_constant(Constant {
+                name: "1st value".to_string(),
+                value: ConstantValue::Float(1.0),
+            })
+            .unwrap();
+        assert_eq!(constant.key(), "v_1ST_VALUE");
+        assert!(shader.check_identifiers().is_ok());
+    }
+
+    #[test]
+    fn generated_ids_skip_used_ones() {
+        let mut shader = Shader::default();
+        shader
+            .create_node(Node::new_with_custom_id(
+                "A",
+                "v0",
+                NodeOperation::TypeConstruction(NonScalarNativeType::Vec2),
+            ))
+            .unwrap();
+        shader
+            .add_input_property(InputProperty::new(NativeType::Float, true, "v1"))
+            .unwrap();
+        let node = shader.new_node(
+            "B",
+            NodeOperation::TypeConstruction(NonScalarNativeType::Vec2),
+        );
+        assert_eq!(node.unique_id(), "v2");
+        let property = shader.new_output_property(NativeType::Vec4);
+        assert_eq!(property.reference, "vec4_v3");
+    }
+
+    #[test]
+    fn seeded_shaders_generate_same_ids() {
+        let ids = || {
+            let mut shader = Shader {
+                id_generator: IdGenerator::seeded(7),
+                ..Shader::default()
+            };
+            (0..5).map(|_| shader.generate_id()).collect::<Vec<_>>()
+        };
+        assert_eq!(ids(), ids());
+    }
+
+    #[test]
+    fn removing_node_severs_connections() {
+        let mut shader = init_example_shader_1();
+        let (node, severed) = shader.remove_node("b").unwrap();
+        assert_eq!(node.unique_id(), "b");
+        let node_field = |id: &str, field_name: &str| ConnectionTo::Node {
+            id: id.to_string(),
+            field_name: field_name.to_string(),
+        };
+        let b = Connection::SingleOutputNode {
+            id: "b".to_string(),
+        };
+        assert_eq!(
+            severed,
+            vec![
+                (
+                    node_field("b", "x"),
+                    Connection::InputProperty {
+                        id: "i".to_string()
+                    }
+                ),
+                (
+                    node_field("b", "y"),
+                    Connection::SingleOutputNode {
+                        id: "a".to_string()
+                    }
+                ),
+                (node_field("c", "y"), b.clone()),
+                (node_field("d", "x"), b),
+            ]
+        );
+        assert!(shader.to_glsl(&test_library()).is_ok());
+    }
+
+    #[test]
+    fn removing_input_property_severs_connections() {
+        let mut shader = init_example_shader_1();
+        let (property, severed) = shader.remove_input_property("i").unwrap();
+        assert_eq!(property.reference, "i");
+        let connection_tos: Vec<_> = severed.into_iter().map(|(to, _)| to).collect();
+        assert_eq!(
+            connection_tos,
+            vec![
+                ConnectionTo::Node {
+                    id: "a".to_string(),
+                    field_name: "x".to_string(),
+                },
+                ConnectionTo::Node {
+                    id: "b".to_string(),
+                    field_name: "x".to_string(),
+                },
+            ]
+        );
+        assert!(shader
+            .nodes()
+            .values()
+            .flat_map(Node::connections)
+            .all(|(_, c)| !matches!(c, Connection::InputProperty { .. })));
+    }
+
+    #[test]
+    fn refuses_same_node_connection() {
+        let mut shader = init_example_shader_1();
+        let res = shader.connect(ConnectionAttempt {
+            connection_from: Connection::SingleOutputNode {
+                id: "a".to_string(),
+            },
+            connection_to: ConnectionTo::Node {
+                id: "a".to_string(),
+                field_name: "y".to_string(),
+            },
+        });
+        assert!(matches!(res, Err(ShadyError::SameNodeConnection(_))));
+    }
+}
@@ -0,0 +1,236 @@
+use crate::node_operation::FunctionLibrary;
+use crate::{
+    builtin_function_names, sanitize_identifier, Connection, GraphicLibrary, Shader, ShadyError,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::sync::OnceLock;
+
+/// Naming of the variables in the generated shader code
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub enum NamingStrategy {
+    /// Variables are named after the node unique ids and the property references
+    Id,
+    /// Variables are named after the node and property names, sanitized and made unique
+    Readable,
+}
+
+impl Default for NamingStrategy {
+    fn default() -> Self {
+        Self::Id
+    }
+}
+
+impl NamingStrategy {
+    /// All enum variants
+    pub const VARIANTS: &'static [Self] = &[Self::Id, Self::Readable];
+}
+
+impl Display for NamingStrategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                NamingStrategy::Id => "Id",
+                NamingStrategy::Readable => "Readable",
+            }
+        )
+    }
+}
+
+/// Converts `name` to a snake case identifier, see `sanitize_identifier`
+fn sanitize(name: &str) -> String {
+    let mut res = String::with_capacity(name.len());
+    let mut previous_lowercase = false;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() && previous_lowercase {
+            res.push('_');
+        }
+        previous_lowercase = c.is_ascii_lowercase() || c.is_ascii_digit();
+        res.push(c.to_ascii_lowercase());
+    }
+    sanitize_identifier(&res)
+}
+
+impl Shader {
+    /// Readable variable names of the input properties, output properties and nodes, by
+    /// reference or node id.
+    ///
+    /// Names are derived from the element names, sanitized, and suffixed with `_1`, `_2`... when
+    /// already used by another element, a constant, a sub-graph, or a function they would shadow:
+    /// a built-in function or a custom function of `functions` declared for `library`
+    pub fn readable_names(
+        &self,
+        functions: &FunctionLibrary,
+        library: GraphicLibrary,
+    ) -> Result<HashMap<String, String>, ShadyError> {
+        let custom_functions = self
+            .nodes
+            .values()
+            .filter_map(|node| node.custom_function_name().map(String::as_str));
+        let mut used: HashSet<String> = self
+            .constants
+            .keys()
+            .chain(self.sub_graphs.keys())
+            .cloned()
+            .chain(builtin_function_names().map(ToString::to_string))
+            .chain(functions.declaration_names(custom_functions, library)?)
+            .collect();
+        let mut elements: Vec<(&String, &String)> = Vec::new();
+        for properties in [
+            sorted(self.input_properties.iter().map(|(k, p)| (k, &p.name))),
+            sorted(self.output_properties.iter().map(|(k, p)| (k, &p.name))),
+            sorted(self.nodes.iter().map(|(k, n)| (k, n.name()))),
+        ] {
+            elements.extend(properties);
+        }
+        let mut names = HashMap::with_capacity(elements.len());
+        for (id, name) in elements {
+            let base = sanitize(name);
+            let mut candidate = base.clone();
+            let mut index = 0;
+            while used.contains(&candidate) {
+                index += 1;
+                candidate = format!("{}_{}", base, index);
+            }
+            used.insert(candidate.clone());
+            names.insert(id.clone(), candidate);
+        }
+        Ok(names)
+    }
+
+    /// Copy of the shader with its node ids and property references replaced by `names`, used to
+    /// generate the code with the `NamingStrategy::Readable` strategy
+    pub(super) fn renamed(&self, names: &HashMap<String, String>) -> Self {
+        let rename = |id: &String| names.get(id).unwrap_or(id).clone();
+        let rename_connection = |connection: &Connection| match connection {
+            Connection::Constant { .. } => connection.clone(),
+            Connection::InputProperty { id } => Connection::InputProperty { id: rename(id) },
+            Connection::SingleOutputNode { id } => Connection::SingleOutputNode { id: rename(id) },
+            Connection::ComplexOutputNode { id, field_name } => Connection::ComplexOutputNode {
+                id: rename(id),
+                field_name: field_name.clone(),
+            },
+        };
+        let mut shader = Self {
+            format_version: self.format_version,
+            name: self.name.clone(),
+            library: self.library,
+            glsl_version: self.glsl_version,
+            shader_type: self.shader_type,
+            default_precisions: self.default_precisions.clone(),
+            id_generator: self.id_generator.clone(),
+            naming_strategy: NamingStrategy::Id,
+            constants: self.constants.clone(),
+            input_properties: self
+                .input_properties
+                .iter()
+                .map(|(id, property)| {
+                    let mut property = property.clone();
+                    property.reference = rename(id);
+                    (property.reference.clone(), property)
+                })
+                .collect(),
+            output_properties: self
+                .output_properties
+                .iter()
+                .map(|(id, property)| {
+                    let mut property = property.clone();
+                    property.reference = rename(id);
+                    property.connection = property.connection.as_ref().map(rename_connection);
+                    (property.reference.clone(), property)
+                })
+                .collect(),
+            vertex_position: self.vertex_position.as_ref().map(rename),
+            nodes: self
+                .nodes
+                .iter()
+                .map(|(id, node)| {
+                    let node = node.renamed(rename(id), rename_connection);
+                    (node.unique_id().clone(), node)
+                })
+                .collect(),
+            sub_graphs: self.sub_graphs.clone(),
+            consumers: OnceLock::new(),
+        };
+        shader.index_consumers();
+        shader
+    }
+}
+
+fn sorted<'a>(
+    elements: impl Iterator<Item = (&'a String, &'a String)>,
+) -> Vec<(&'a String, &'a String)> {
+    let mut res: Vec<_> = elements.collect();
+    res.sort();
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_operation::{NativeOperation, NodeOperation};
+    use crate::shader::to_glsl::tests::init_example_shader_1;
+    use crate::tests::test_library;
+    use crate::{NativeType, Node};
+
+    #[test]
+    fn sanitizes_names() {
+        assert_eq!(sanitize("UV Scaled"), "uv_scaled");
+        assert_eq!(sanitize("MyNode2Color"), "my_node2_color");
+        assert_eq!(sanitize("  Mix -- Color! "), "mix_color");
+        assert_eq!(sanitize("1st value"), "v_1st_value");
+        assert_eq!(sanitize("Gl_Position"), "v_gl_position");
+        assert_eq!(sanitize("été"), "t");
+        assert_eq!(sanitize("Float"), "v_float");
+        assert_eq!(sanitize("?"), "v");
+    }
+
+    #[test]
+    fn readable_names_are_unique() {
+        let shader = init_example_shader_1();
+        let names = shader
+            .readable_names(&test_library(), GraphicLibrary::OpenGl)
+            .unwrap();
+        let values: HashSet<&String> = names.values().collect();
+        assert_eq!(values.len(), names.len());
+        assert_eq!(names["a"], "a");
+        assert_eq!(names["b"], "b");
+        assert_eq!(names["i"], "i");
+    }
+
+    #[test]
+    fn readable_names_avoid_reserved_words() {
+        let mut shader = Shader::default();
+        for name in ["float", "Float", "float 1"] {
+            let mut property = shader.new_input_property(crate::NativeType::Float, true);
+            property.name = name.to_string();
+            shader.add_input_property(property).unwrap();
+        }
+        let mut names: Vec<String> = shader
+            .readable_names(&test_library(), GraphicLibrary::OpenGl)
+            .unwrap()
+            .into_values()
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["float_1", "v_float", "v_float_1"]);
+    }
+
+    #[test]
+    fn readable_names_avoid_function_names() {
+        let mut shader = init_example_shader_1();
+        for (name, id) in [("mix", "m"), ("Test Func", "t")] {
+            let operation = NodeOperation::NativeOperation(NativeOperation::Add(NativeType::Float));
+            shader
+                .create_node(Node::new_with_custom_id(name, id, operation))
+                .unwrap();
+        }
+        let names = shader
+            .readable_names(&test_library(), GraphicLibrary::OpenGl)
+            .unwrap();
+        assert_eq!(names["m"], "v_mix");
+        assert_eq!(names["t"], "test_func_1");
+    }
+}
@@ -0,0 +1,213 @@
+use super::sub_graph::SubGraphGeneration;
+use crate::error::ShadyError;
+use crate::node_operation::FunctionLibrary;
+use crate::{CodeWriter, Connection, GraphicLibrary, Handle, Node, Shader};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Default)]
+pub(super) struct NodeGeneration {
+    pub ordered_nodes: Vec<Handle<Node>>,
+}
+
+/// Node dependencies resolved once to handles, indexed by node handle slot
+#[derive(Debug, Clone, Default)]
+pub(super) struct DependencyGraph {
+    /// Dependencies of every node, sorted by descending id to be popped in ascending order
+    dependencies: Vec<Vec<Handle<Node>>>,
+    /// First dependency of every node missing from the shader
+    missing: Vec<Option<String>>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(super) enum VisitState {
+    InProgress,
+    Done,
+}
+
+impl NodeGeneration {
+    /// Iterates the ordered nodes
+    pub fn nodes<'a>(&'a self, shader: &'a Shader) -> impl Iterator<Item = &'a Node> {
+        self.ordered_nodes
+            .iter()
+            .map(|handle| &shader.nodes[*handle])
+    }
+
+    /// Writes the GLSL code of every node, one per line, in dependency order
+    pub fn write_glsl<W: Write>(
+        &self,
+        shader: &Shader,
+        writer: &mut CodeWriter<W>,
+    ) -> Result<(), ShadyError> {
+        for node in self.nodes(shader) {
+            writer.line(node.to_glsl(shader.glsl_version, shader.shader_type))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the WGSL code of every node, one per line, in dependency order
+    pub fn write_wgsl<W: Write>(
+        &self,
+        shader: &Shader,
+        writer: &mut CodeWriter<W>,
+    ) -> Result<(), ShadyError> {
+        for node in self.nodes(shader) {
+            writer.line(node.to_wgsl(shader.shader_type)?)?;
+        }
+        Ok(())
+    }
+}
+
+impl DependencyGraph {
+    /// Dependencies of `node` found in the shader
+    pub fn dependencies(&self, node: Handle<Node>) -> &[Handle<Node>] {
+        &self.dependencies[node.index()]
+    }
+
+    /// Dependencies of `node`, failing if one of them is missing from the shader
+    pub fn checked_dependencies(&self, node: Handle<Node>) -> Result<&[Handle<Node>], ShadyError> {
+        self.missing[node.index()].as_ref().map_or_else(
+            || Ok(self.dependencies(node)),
+            |id| Err(ShadyError::MissingNode(id.clone())),
+        )
+    }
+}
+
+impl Shader {
+    /// Writes the function declarations written for `library` required by the `nodes` and
+    /// `sub_graphs`: the custom functions of `functions` and the functions they call, see
+    /// `FunctionLibrary::declarations`, then the sub-graph functions, all separated by an empty
+    /// line
+    pub(super) fn write_function_declarations<W: Write>(
+        &self,
+        writer: &mut CodeWriter<W>,
+        nodes: &NodeGeneration,
+        sub_graphs: &SubGraphGeneration,
+        functions: &FunctionLibrary,
+        library: GraphicLibrary,
+    ) -> Result<(), ShadyError> {
+        let custom_functions = nodes
+            .nodes(self)
+            .chain(sub_graphs.nodes(self))
+            .filter_map(|node| node.custom_function_name().map(String::as_str));
+        let mut declarations: Vec<String> = functions
+            .declarations(custom_functions, library)?
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        let sub_graph_functions = sub_graphs.functions(self, library)?;
+        // Generated sub-graph functions are separated from the main function
+        let separated = !sub_graph_functions.is_empty();
+        declarations.extend(sub_graph_functions);
+        for (index, declaration) in declarations.iter().enumerate() {
+            if index > 0 {
+                writer.empty_line()?;
+            }
+            writer.line(declaration)?;
+        }
+        if declarations.is_empty() || separated {
+            writer.empty_line()?;
+        }
+        Ok(())
+    }
+
+    /// Ids of the nodes directly connected to output properties, sorted and deduplicated
+    pub(super) fn output_node_ids(&self) -> Vec<&String> {
+        let mut ids: Vec<&String> =
+            self.output_properties
+                .values()
+                .filter_map(|p| match p.connection.as_ref()? {
+                    Connection::ComplexOutputNode { id, .. }
+                    | Connection::SingleOutputNode { id } => Some(id),
+                    Connection::Constant { .. } | Connection::InputProperty { .. } => None,
+                })
+                .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    pub(super) fn node_handle(&self, id: &str) -> Result<Handle<Node>, ShadyError> {
+        self.nodes
+            .handle(id)
+            .ok_or_else(|| ShadyError::MissingNode(id.to_string()))
+    }
+
+    /// Resolves the dependencies of every node to handles, once for the whole graph so the
+    /// traversals don't look up ids
+    pub(super) fn dependency_graph(&self) -> DependencyGraph {
+        let mut graph = DependencyGraph {
+            dependencies: vec![Vec::new(); self.nodes.capacity()],
+            missing: vec![None; self.nodes.capacity()],
+        };
+        for (handle, _, node) in self.nodes.entries() {
+            let dependencies = &mut graph.dependencies[handle.index()];
+            for id in node.node_dependencies() {
+                match self.nodes.handle(id) {
+                    Some(dependency) => dependencies.push(dependency),
+                    None => {
+                        graph.missing[handle.index()].get_or_insert_with(|| id.clone());
+                    }
+                }
+            }
+            dependencies.sort_unstable_by(|a, b| {
+                self.nodes[*b].unique_id().cmp(self.nodes[*a].unique_id())
+            });
+            dependencies.dedup();
+        }
+        graph
+    }
+
+    /// Sorts the nodes connected to output properties and all their dependencies so every node
+    /// is declared after the nodes it depends on.
+    ///
+    /// The nodes are sorted through an iterative depth first search on the `DependencyGraph`,
+    /// linear in the graph size. A dependency cycle returns a `ShadyError::NodeLoopDetected` with the cycle
+    /// path, each node depending on the next one and the last node being the first one again.
+    pub(super) fn nodes_generation(&self) -> Result<NodeGeneration, ShadyError> {
+        let mut res = NodeGeneration::default();
+        let graph = self.dependency_graph();
+        let mut visited: Vec<Option<VisitState>> = vec![None; self.nodes.capacity()];
+
+        for root in self.output_node_ids() {
+            let root = self.node_handle(root)?;
+            if visited[root.index()].is_some() {
+                continue;
+            }
+            log::trace!("Processing output node {:?}", root);
+            // Nodes being visited, with their count of dependencies left to visit
+            let mut stack = vec![(root, graph.checked_dependencies(root)?.len())];
+            visited[root.index()] = Some(VisitState::InProgress);
+            while let Some((node, remaining)) = stack.last_mut() {
+                if *remaining > 0 {
+                    *remaining -= 1;
+                    let dependency = graph.dependencies(*node)[*remaining];
+                    match visited[dependency.index()] {
+                        Some(VisitState::Done) => (),
+                        Some(VisitState::InProgress) => {
+                            let cycle: Vec<String> = stack
+                                .iter()
+                                .map(|(handle, _)| *handle)
+                                .skip_while(|handle| *handle != dependency)
+                                .chain(std::iter::once(dependency))
+                                .map(|handle| self.nodes[handle].unique_id().clone())
+                                .collect();
+                            return Err(ShadyError::NodeLoopDetected(cycle));
+                        }
+                        None => {
+                            log::trace!("Processing node {:?}", dependency);
+                            let dependencies = graph.checked_dependencies(dependency)?;
+                            visited[dependency.index()] = Some(VisitState::InProgress);
+                            stack.push((dependency, dependencies.len()));
+                        }
+                    }
+                } else {
+                    visited[node.index()] = Some(VisitState::Done);
+                    res.ordered_nodes.push(*node);
+                    stack.pop();
+                }
+            }
+        }
+        log::trace!("Node Generation: {:?}", res);
+        Ok(res)
+    }
+}
@@ -1,8 +1,12 @@
-use crate::NativeType;
+use crate::{GlslVersion, NativeType, ScalarNativeType, ShaderType};
 use serde::{Deserialize, Serialize};
 
+/// Macro resolving to the highest available fragment precision, defined in fragment shaders
+/// using the `High` precision
+pub const HIGH_FRAGMENT_PRECISION_MACRO: &str = "SHADY_HIGHP";
+
 /// Shader Precision
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub enum ShaderPrecision {
     /// High Precision
     /// Requires `GL_FRAGMENT_PRECISION_HIGH` macro set to 1
@@ -20,11 +24,79 @@ impl Default for ShaderPrecision {
 }
 
 impl ShaderPrecision {
-    pub fn to_glsl(&self, glsl_type: NativeType) -> String {
-        match self {
-            Self::High => format!("precision highp {};", glsl_type),
-            Self::Medium => format!("precision mediump {};", glsl_type),
-            Self::Low => format!("precision lowp {};", glsl_type),
+    /// All enum variants
+    pub const VARIANTS: &'static [Self] = &[Self::High, Self::Medium, Self::Low];
+
+    /// Can `glsl_type` declarations have a precision qualifier
+    pub const fn is_qualifiable(glsl_type: NativeType) -> bool {
+        !matches!(
+            glsl_type.component_type(),
+            Some(ScalarNativeType::Bool | ScalarNativeType::Double)
+        )
+    }
+
+    /// Can `glsl_type` be used in a default precision statement
+    pub const fn has_default_precision(glsl_type: NativeType) -> bool {
+        matches!(glsl_type, NativeType::Int | NativeType::Float) || glsl_type.is_sampler()
+    }
+
+    /// Does `glsl_type` have a predefined default precision in GLSL ES, only `sampler2D` and
+    /// `samplerCube` do among samplers
+    pub const fn has_predefined_precision(glsl_type: NativeType) -> bool {
+        !glsl_type.is_sampler()
+            || matches!(glsl_type, NativeType::Sampler2d | NativeType::SamplerCube)
+    }
+
+    /// Precision qualifier keyword. In fragment shaders the `High` precision is guarded by
+    /// `GL_FRAGMENT_PRECISION_HIGH` through the `HIGH_FRAGMENT_PRECISION_MACRO`
+    pub const fn glsl_qualifier(&self, shader_type: ShaderType) -> &'static str {
+        match (self, shader_type) {
+            (Self::High, ShaderType::Fragment) => HIGH_FRAGMENT_PRECISION_MACRO,
+            (Self::High, ShaderType::Vertex) => "highp",
+            (Self::Medium, _) => "mediump",
+            (Self::Low, _) => "lowp",
+        }
+    }
+
+    /// Default precision statement for `glsl_type`
+    pub fn to_glsl(&self, glsl_type: NativeType, shader_type: ShaderType) -> String {
+        format!(
+            "precision {} {};",
+            self.glsl_qualifier(shader_type),
+            glsl_type
+        )
+    }
+
+    /// Definition of the `HIGH_FRAGMENT_PRECISION_MACRO`
+    pub fn high_fragment_precision_definition() -> String {
+        formatdoc! {"
+            #ifdef GL_FRAGMENT_PRECISION_HIGH
+            #define {macro_name} highp
+            #else
+            #define {macro_name} mediump
+            #endif",
+            macro_name = HIGH_FRAGMENT_PRECISION_MACRO
+        }
+    }
+}
+
+/// Precision qualifier of a `glsl_type` declaration followed by a space, only ES targets have
+/// precision qualifiers
+pub fn glsl_precision_qualifier(
+    precision: Option<ShaderPrecision>,
+    glsl_type: NativeType,
+    version: GlslVersion,
+    shader_type: ShaderType,
+) -> String {
+    match precision {
+        Some(precision) if version.is_es() => {
+            if ShaderPrecision::is_qualifiable(glsl_type) {
+                format!("{} ", precision.glsl_qualifier(shader_type))
+            } else {
+                log::warn!("Type {} can't have a precision qualifier", glsl_type);
+                String::new()
+            }
         }
+        _ => String::new(),
     }
 }
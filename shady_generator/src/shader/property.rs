@@ -1,7 +1,10 @@
 use crate::error::ShadyError;
-use crate::generate_unique_id;
-use crate::NativeType;
+use crate::shader::precision::glsl_precision_qualifier;
 use crate::{Connection, ConnectionMessage, ConnectionResponse};
+use crate::{
+    GlslVersion, GraphicLibrary, NativeType, SamplerNativeType, ScalarNativeType, ShaderPrecision,
+    ShaderType,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -11,6 +14,9 @@ pub struct InputProperty {
     pub native_type: NativeType,
     // TODO: handle constants
     pub uniform: bool,
+    /// Precision qualifier, only applied to OpenGL ES targets
+    #[serde(default)]
+    pub precision: Option<ShaderPrecision>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -18,40 +24,168 @@ pub struct OutputProperty {
     pub name: String,
     pub reference: String,
     pub native_type: NativeType,
+    /// Precision qualifier, only applied to OpenGL ES targets
+    #[serde(default)]
+    pub precision: Option<ShaderPrecision>,
     pub(crate) connection: Option<Connection>,
 }
 
 impl InputProperty {
-    pub fn new(glsl_type: NativeType, uniform: bool) -> Self {
+    /// Instantiates a property referenced by its type and `id`. Use `Shader::new_input_property`
+    /// to get an id from the shader `IdGenerator`
+    pub fn new(glsl_type: NativeType, uniform: bool, id: &str) -> Self {
         let name = glsl_type.get_glsl_type().to_string();
         Self {
-            reference: format!("{}_{}", name, generate_unique_id()),
+            reference: format!("{}_{}", name, id),
             name,
             native_type: glsl_type,
             uniform,
+            precision: None,
         }
     }
 
-    pub fn glsl_declaration(&self) -> String {
-        format!(
-            "{} {} {}; // {}",
-            if self.uniform { "uniform" } else { "in" },
+    /// GLSL declaration of the property for the targeted `version` and `shader_type`.
+    ///
+    /// Integer and double fragment shader inputs are declared `flat`
+    pub fn glsl_declaration(
+        &self,
+        version: GlslVersion,
+        shader_type: ShaderType,
+    ) -> Result<String, ShadyError> {
+        let interpolation = if self.uniform {
+            ""
+        } else {
+            glsl_interpolation(
+                self.native_type,
+                version,
+                shader_type == ShaderType::Fragment,
+            )?
+        };
+        Ok(format!(
+            "{}{} {}{} {}; // {}",
+            interpolation,
+            if self.uniform {
+                "uniform"
+            } else if version.is_legacy() {
+                match shader_type {
+                    ShaderType::Vertex => "attribute",
+                    ShaderType::Fragment => "varying",
+                }
+            } else {
+                "in"
+            },
+            glsl_precision_qualifier(self.precision, self.native_type, version, shader_type),
             self.native_type.get_glsl_type(),
             self.reference,
             self.name
-        )
+        ))
+    }
+
+    /// WGSL resource declaration of a uniform property, samplers are declared as a texture and
+    /// a `sampler` binding suffixed with `_sampler`, a `sampler_comparison` for shadow samplers.
+    ///
+    /// Boolean types are not host-shareable and can't be declared as uniforms.
+    pub fn wgsl_uniform_declaration(&self, group: u32, binding: u32) -> Result<String, ShadyError> {
+        if self.native_type.component_type() == Some(ScalarNativeType::Bool) {
+            return Err(ShadyError::UnsupportedNativeType {
+                native_type: self.native_type,
+                target: format!("{} uniform", GraphicLibrary::WebGPU),
+            });
+        }
+        let wgsl_type = self.native_type.get_wgsl_type()?;
+        Ok(if self.native_type.is_sampler() {
+            format!(
+                "@group({0}) @binding({1}) var {2}: {3}; // {4}\n\
+                @group({0}) @binding({5}) var {2}_sampler: {6}; // {4}",
+                group,
+                binding,
+                self.reference,
+                wgsl_type,
+                self.name,
+                binding + 1,
+                if self
+                    .native_type
+                    .sampler_type()
+                    .is_some_and(SamplerNativeType::is_shadow)
+                {
+                    "sampler_comparison"
+                } else {
+                    "sampler"
+                }
+            )
+        } else {
+            format!(
+                "@group({}) @binding({}) var<uniform> {}: {}; // {}",
+                group, binding, self.reference, wgsl_type, self.name
+            )
+        })
+    }
+
+    /// WGSL entry point parameter declaration of a non uniform property
+    pub fn wgsl_parameter_declaration(&self, location: u32) -> Result<String, ShadyError> {
+        Ok(format!(
+            "{} {}: {}, // {}",
+            wgsl_location(self.native_type, location)?,
+            self.reference,
+            self.native_type.get_wgsl_type()?,
+            self.name
+        ))
     }
 
     // TODO Add default OpenGL/ES properties (must match version)
 }
 
+/// GLSL interpolation qualifier of a stage input or output, `interpolated` between the vertex and
+/// fragment stages. Booleans can't be passed between stages, nor integers on legacy targets
+fn glsl_interpolation(
+    native_type: NativeType,
+    version: GlslVersion,
+    interpolated: bool,
+) -> Result<&'static str, ShadyError> {
+    if native_type.component_type() == Some(ScalarNativeType::Bool)
+        || (version.is_legacy() && native_type.is_integer())
+    {
+        return Err(ShadyError::UnsupportedNativeType {
+            native_type,
+            target: format!("{} stage input/output", version),
+        });
+    }
+    Ok(
+        if interpolated
+            && !version.is_legacy()
+            && (native_type.is_integer() || native_type.is_double())
+        {
+            "flat "
+        } else {
+            ""
+        },
+    )
+}
+
+fn wgsl_location(native_type: NativeType, location: u32) -> Result<String, ShadyError> {
+    if native_type.component_type() == Some(ScalarNativeType::Bool) || native_type.is_sampler() {
+        return Err(ShadyError::UnsupportedNativeType {
+            native_type,
+            target: format!("{} stage input/output", GraphicLibrary::WebGPU),
+        });
+    }
+    Ok(if native_type.is_integer() {
+        format!("@location({}) @interpolate(flat)", location)
+    } else {
+        format!("@location({})", location)
+    })
+}
+
 impl OutputProperty {
-    pub fn new(native_type: NativeType) -> Self {
+    /// Instantiates a property referenced by its type and `id`. Use `Shader::new_output_property`
+    /// to get an id from the shader `IdGenerator`
+    pub fn new(native_type: NativeType, id: &str) -> Self {
         let name = native_type.get_glsl_type().to_string();
         Self {
-            reference: format!("{}_{}", name, generate_unique_id()),
+            reference: format!("{}_{}", name, id),
             name,
             native_type,
+            precision: None,
             connection: None,
         }
     }
@@ -73,18 +207,108 @@ impl OutputProperty {
         self.connection.take()
     }
 
-    pub fn glsl_declaration(&self) -> String {
+    /// GLSL declaration of the property for the targeted `version` and `shader_type`.
+    ///
+    /// Legacy fragment shaders have no user defined outputs, the property is then defined as an
+    /// alias of `gl_FragData[index]`. GLSL ES 3.00 fragment shaders with more than one of the
+    /// `count` outputs bind each one to its `index` location. Integer and double vertex outputs
+    /// are declared `flat`
+    pub fn glsl_declaration(
+        &self,
+        version: GlslVersion,
+        shader_type: ShaderType,
+        index: usize,
+        count: usize,
+    ) -> Result<String, ShadyError> {
+        let interpolation =
+            glsl_interpolation(self.native_type, version, shader_type == ShaderType::Vertex)?;
+        if !version.is_legacy() {
+            let layout = if version == GlslVersion::GlslEs300
+                && shader_type == ShaderType::Fragment
+                && count > 1
+            {
+                format!("layout(location = {}) ", index)
+            } else {
+                String::new()
+            };
+            return Ok(format!(
+                "{}{}out {}{} {}; // {}",
+                layout,
+                interpolation,
+                glsl_precision_qualifier(self.precision, self.native_type, version, shader_type),
+                self.native_type.get_glsl_type(),
+                self.reference,
+                self.name
+            ));
+        }
+        match shader_type {
+            ShaderType::Vertex => Ok(format!(
+                "varying {}{} {}; // {}",
+                glsl_precision_qualifier(self.precision, self.native_type, version, shader_type),
+                self.native_type.get_glsl_type(),
+                self.reference,
+                self.name
+            )),
+            ShaderType::Fragment => {
+                if self.native_type != NativeType::Vec4 {
+                    return Err(ShadyError::UnsupportedNativeType {
+                        native_type: self.native_type,
+                        target: format!("{} fragment output", version),
+                    });
+                }
+                Ok(format!(
+                    "#define {} gl_FragData[{}] // {}",
+                    self.reference, index, self.name
+                ))
+            }
+        }
+    }
+
+    /// GLSL assignment of the property for the targeted `version`
+    pub fn to_glsl(&self, version: GlslVersion) -> String {
         format!(
-            "out {} {}; // {}",
-            self.native_type.get_glsl_type(),
+            "{} = {}; // {}",
             self.reference,
+            match &self.connection {
+                None => {
+                    log::warn!(
+                        "No connection set for output property {} ({}). Using default value",
+                        self.name,
+                        self.reference
+                    );
+                    self.native_type.default_glsl_value(version)
+                }
+                Some(connection) => connection.glsl_call(),
+            },
             self.name
         )
     }
 
-    pub fn to_glsl(&self) -> String {
+    /// WGSL field declaration in the entry point output `struct`
+    pub fn wgsl_field_declaration(&self, location: u32) -> Result<String, ShadyError> {
+        Ok(format!(
+            "{} {}: {}, // {}",
+            wgsl_location(self.native_type, location)?,
+            self.reference,
+            self.native_type.get_wgsl_type()?,
+            self.name
+        ))
+    }
+
+    /// WGSL `@builtin(position)` field declaration in the vertex entry point output `struct`
+    pub fn wgsl_position_declaration(&self) -> Result<String, ShadyError> {
+        Ok(format!(
+            "@builtin(position) {}: {}, // {}",
+            self.reference,
+            self.native_type.get_wgsl_type()?,
+            self.name
+        ))
+    }
+
+    /// WGSL assignment of the property in the `output` entry point variable
+    pub fn to_wgsl(&self) -> String {
         format!(
-            "{} = {}; // {}",
+            "output.{} = {}; // {}",
             self.reference,
             match &self.connection {
                 None => {
@@ -93,7 +317,7 @@ impl OutputProperty {
                         self.name,
                         self.reference
                     );
-                    self.native_type.default_glsl_value().to_string()
+                    self.native_type.default_wgsl_value()
                 }
                 Some(connection) => connection.glsl_call(),
             },
@@ -120,11 +344,89 @@ mod tests {
             let property = InputProperty {
                 name: "Property".to_string(),
                 reference: "ref".to_string(),
-                native_type: NativeType::Bool,
+                native_type: NativeType::IVec2,
                 uniform: false,
+                precision: None,
             };
-            let res = property.glsl_declaration();
-            assert_eq!(&res, "in bool ref; // Property");
+            let res = property
+                .glsl_declaration(GlslVersion::default(), ShaderType::Vertex)
+                .unwrap();
+            assert_eq!(&res, "in ivec2 ref; // Property");
+        }
+
+        #[test]
+        fn prints_flat_to_glsl() {
+            for (native_type, version, expected) in [
+                (
+                    NativeType::Int,
+                    GlslVersion::Glsl330,
+                    "flat in int int_a; // int",
+                ),
+                (
+                    NativeType::UVec2,
+                    GlslVersion::Glsl450,
+                    "flat in uvec2 uvec2_a; // uvec2",
+                ),
+                (
+                    NativeType::DVec3,
+                    GlslVersion::Glsl450,
+                    "flat in dvec3 dvec3_a; // dvec3",
+                ),
+                (
+                    NativeType::IVec4,
+                    GlslVersion::GlslEs300,
+                    "flat in ivec4 ivec4_a; // ivec4",
+                ),
+                (
+                    NativeType::Vec2,
+                    GlslVersion::Glsl450,
+                    "in vec2 vec2_a; // vec2",
+                ),
+            ] {
+                let property = InputProperty::new(native_type, false, "a");
+                let res = property
+                    .glsl_declaration(version, ShaderType::Fragment)
+                    .unwrap();
+                assert_eq!(res, expected);
+            }
+        }
+
+        #[test]
+        fn rejects_non_float_stage_inputs_in_glsl() {
+            for (native_type, version) in [
+                (NativeType::Bool, GlslVersion::Glsl450),
+                (NativeType::BVec2, GlslVersion::GlslEs300),
+                (NativeType::Int, GlslVersion::Glsl110),
+                (NativeType::UVec3, GlslVersion::GlslEs100),
+                (NativeType::Bool, GlslVersion::GlslEs100),
+            ] {
+                for shader_type in [ShaderType::Vertex, ShaderType::Fragment] {
+                    let property = InputProperty::new(native_type, false, "a");
+                    assert!(matches!(
+                        property.glsl_declaration(version, shader_type),
+                        Err(ShadyError::UnsupportedNativeType { .. })
+                    ));
+                }
+            }
+        }
+
+        #[test]
+        fn prints_legacy_to_glsl() {
+            let property = InputProperty {
+                name: "Property".to_string(),
+                reference: "ref".to_string(),
+                native_type: NativeType::Vec2,
+                uniform: false,
+                precision: None,
+            };
+            let res = property
+                .glsl_declaration(GlslVersion::GlslEs100, ShaderType::Vertex)
+                .unwrap();
+            assert_eq!(&res, "attribute vec2 ref; // Property");
+            let res = property
+                .glsl_declaration(GlslVersion::Glsl110, ShaderType::Fragment)
+                .unwrap();
+            assert_eq!(&res, "varying vec2 ref; // Property");
         }
 
         #[test]
@@ -134,10 +436,92 @@ mod tests {
                 reference: "ref".to_string(),
                 native_type: NativeType::Bool,
                 uniform: true,
+                precision: None,
             };
-            let res = property.glsl_declaration();
+            let res = property
+                .glsl_declaration(GlslVersion::GlslEs100, ShaderType::Vertex)
+                .unwrap();
             assert_eq!(&res, "uniform bool ref; // Property");
         }
+
+        #[test]
+        fn prints_precision_to_glsl() {
+            let property = InputProperty {
+                name: "Property".to_string(),
+                reference: "ref".to_string(),
+                native_type: NativeType::Vec3,
+                uniform: true,
+                precision: Some(ShaderPrecision::Low),
+            };
+            let res = property
+                .glsl_declaration(GlslVersion::GlslEs100, ShaderType::Fragment)
+                .unwrap();
+            assert_eq!(&res, "uniform lowp vec3 ref; // Property");
+            let res = property
+                .glsl_declaration(GlslVersion::Glsl450, ShaderType::Fragment)
+                .unwrap();
+            assert_eq!(&res, "uniform vec3 ref; // Property");
+        }
+
+        #[test]
+        fn prints_to_wgsl() {
+            let property = InputProperty {
+                name: "Property".to_string(),
+                reference: "ref".to_string(),
+                native_type: NativeType::IVec2,
+                uniform: false,
+                precision: None,
+            };
+            let res = property.wgsl_parameter_declaration(1).unwrap();
+            assert_eq!(
+                &res,
+                "@location(1) @interpolate(flat) ref: vec2<i32>, // Property"
+            );
+        }
+
+        #[test]
+        fn prints_uniform_to_wgsl() {
+            let property = InputProperty {
+                name: "Property".to_string(),
+                reference: "ref".to_string(),
+                native_type: NativeType::Vec4,
+                uniform: true,
+                precision: None,
+            };
+            let res = property.wgsl_uniform_declaration(0, 2).unwrap();
+            assert_eq!(
+                &res,
+                "@group(0) @binding(2) var<uniform> ref: vec4<f32>; // Property"
+            );
+        }
+
+        #[test]
+        fn rejects_bool_uniform_in_wgsl() {
+            for native_type in [NativeType::Bool, NativeType::BVec3] {
+                let property = InputProperty::new(native_type, true, "a");
+                assert!(matches!(
+                    property.wgsl_uniform_declaration(0, 0),
+                    Err(ShadyError::UnsupportedNativeType { .. })
+                ));
+            }
+        }
+
+        #[test]
+        fn prints_sampler_to_wgsl() {
+            let property = InputProperty {
+                name: "Property".to_string(),
+                reference: "ref".to_string(),
+                native_type: NativeType::Sampler2d,
+                uniform: true,
+                precision: None,
+            };
+            let res = property.wgsl_uniform_declaration(0, 0).unwrap();
+            assert_eq!(
+                res,
+                "@group(0) @binding(0) var ref: texture_2d<f32>; // Property\n\
+                @group(0) @binding(1) var ref_sampler: sampler; // Property"
+            );
+        }
     }
 
     mod output {
@@ -148,11 +532,151 @@ mod tests {
             let property = OutputProperty {
                 name: "Property".to_string(),
                 reference: "ref".to_string(),
-                native_type: NativeType::Bool,
+                native_type: NativeType::Vec3,
+                precision: None,
+                connection: None,
+            };
+            let res = property
+                .glsl_declaration(GlslVersion::default(), ShaderType::Vertex, 0, 1)
+                .unwrap();
+            assert_eq!(&res, "out vec3 ref; // Property");
+        }
+
+        #[test]
+        fn prints_flat_to_glsl() {
+            let declaration = |native_type, version, shader_type| {
+                OutputProperty::new(native_type, "o")
+                    .glsl_declaration(version, shader_type, 1, 2)
+                    .unwrap()
+            };
+            for (native_type, version, expected) in [
+                (
+                    NativeType::IVec3,
+                    GlslVersion::Glsl330,
+                    "flat out ivec3 ivec3_o; // ivec3",
+                ),
+                (
+                    NativeType::Double,
+                    GlslVersion::Glsl450,
+                    "flat out double double_o; // double",
+                ),
+                (
+                    NativeType::UInt,
+                    GlslVersion::Glsl330,
+                    "flat out uint uint_o; // uint",
+                ),
+                (
+                    NativeType::DVec2,
+                    GlslVersion::Glsl450,
+                    "flat out dvec2 dvec2_o; // dvec2",
+                ),
+                (
+                    NativeType::UVec3,
+                    GlslVersion::GlslEs300,
+                    "flat out uvec3 uvec3_o; // uvec3",
+                ),
+            ] {
+                assert_eq!(
+                    declaration(native_type, version, ShaderType::Vertex),
+                    expected
+                );
+            }
+            assert_eq!(
+                declaration(
+                    NativeType::IVec2,
+                    GlslVersion::Glsl450,
+                    ShaderType::Fragment
+                ),
+                "out ivec2 ivec2_o; // ivec2"
+            );
+        }
+
+        #[test]
+        fn prints_es_locations_to_glsl() {
+            for (native_type, index, expected) in [
+                (
+                    NativeType::Vec4,
+                    0,
+                    "layout(location = 0) out vec4 vec4_o; // vec4",
+                ),
+                (
+                    NativeType::IVec2,
+                    1,
+                    "layout(location = 1) out ivec2 ivec2_o; // ivec2",
+                ),
+            ] {
+                let property = OutputProperty::new(native_type, "o");
+                let res = property
+                    .glsl_declaration(GlslVersion::GlslEs300, ShaderType::Fragment, index, 2)
+                    .unwrap();
+                assert_eq!(res, expected);
+            }
+            let property = OutputProperty::new(NativeType::Vec4, "o");
+            let res = property
+                .glsl_declaration(GlslVersion::GlslEs300, ShaderType::Fragment, 0, 1)
+                .unwrap();
+            assert_eq!(res, "out vec4 vec4_o; // vec4");
+            let res = property
+                .glsl_declaration(GlslVersion::GlslEs300, ShaderType::Vertex, 1, 2)
+                .unwrap();
+            assert_eq!(res, "out vec4 vec4_o; // vec4");
+        }
+
+        #[test]
+        fn rejects_non_float_stage_outputs_in_glsl() {
+            for (native_type, version, shader_type) in [
+                (NativeType::Bool, GlslVersion::Glsl450, ShaderType::Vertex),
+                (
+                    NativeType::BVec4,
+                    GlslVersion::GlslEs300,
+                    ShaderType::Fragment,
+                ),
+                (NativeType::IVec2, GlslVersion::Glsl110, ShaderType::Vertex),
+                (NativeType::UInt, GlslVersion::GlslEs100, ShaderType::Vertex),
+            ] {
+                let property = OutputProperty::new(native_type, "o");
+                assert!(matches!(
+                    property.glsl_declaration(version, shader_type, 0, 1),
+                    Err(ShadyError::UnsupportedNativeType { .. })
+                ));
+            }
+        }
+
+        #[test]
+        fn prints_legacy_to_glsl() {
+            let mut property = OutputProperty {
+                name: "Property".to_string(),
+                reference: "ref".to_string(),
+                native_type: NativeType::Vec4,
+                precision: None,
+                connection: None,
+            };
+            let res = property
+                .glsl_declaration(GlslVersion::Glsl110, ShaderType::Vertex, 0, 2)
+                .unwrap();
+            assert_eq!(&res, "varying vec4 ref; // Property");
+            let res = property
+                .glsl_declaration(GlslVersion::GlslEs100, ShaderType::Fragment, 1, 2)
+                .unwrap();
+            assert_eq!(&res, "#define ref gl_FragData[1] // Property");
+            property.native_type = NativeType::Float;
+            assert!(property
+                .glsl_declaration(GlslVersion::GlslEs100, ShaderType::Fragment, 0, 2)
+                .is_err());
+        }
+
+        #[test]
+        fn prints_to_wgsl() {
+            let property = OutputProperty {
+                name: "Property".to_string(),
+                reference: "ref".to_string(),
+                native_type: NativeType::Float,
+                precision: None,
                 connection: None,
             };
-            let res = property.glsl_declaration();
-            assert_eq!(&res, "out bool ref; // Property");
+            let res = property.wgsl_field_declaration(0).unwrap();
+            assert_eq!(&res, "@location(0) ref: f32, // Property");
+            assert_eq!(&property.to_wgsl(), "output.ref = 0.0; // Property");
         }
     }
 }
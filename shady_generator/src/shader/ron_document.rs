@@ -0,0 +1,422 @@
+use crate::error::ShadyError;
+use serde_yaml::{Mapping, Number, Value};
+
+/// Parses RON `data` to a generic document which can be migrated like YAML and JSON saves.
+///
+/// `ron::Value` and `deserialize_any` drop enum variant names, so the document is read here:
+/// named values (`Variant(..)`) and identifiers are converted to the YAML externally tagged enum
+/// representation, unnamed tuples to sequences and unnamed structs to mappings. Structs must be
+/// serialized without their names, as `Shader::to_string_as` does
+pub(super) fn parse(data: &str) -> Result<Value, ShadyError> {
+    let mut parser = Parser { data, position: 0 };
+    parser.skip_attributes()?;
+    let value = parser.value()?;
+    parser.skip_whitespace()?;
+    if parser.position < data.len() {
+        return Err(parser.error("trailing characters"));
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    data: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> ShadyError {
+        let line = self.data[..self.position].matches('\n').count() + 1;
+        ShadyError::InvalidSaveFormat(format!("RON {} at line {}", message, line))
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.data[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ShadyError> {
+        self.skip_whitespace()?;
+        if self.next() == Some(expected) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", expected)))
+        }
+    }
+
+    /// Skips whitespace, line comments and nested block comments
+    fn skip_whitespace(&mut self) -> Result<(), ShadyError> {
+        loop {
+            let rest = self.rest();
+            if rest.starts_with("//") {
+                self.position += rest.find('\n').unwrap_or(rest.len());
+            } else if rest.starts_with("/*") {
+                let mut depth = 0_usize;
+                loop {
+                    let rest = self.rest();
+                    if rest.starts_with("/*") {
+                        depth += 1;
+                        self.position += 2;
+                    } else if rest.starts_with("*/") {
+                        depth -= 1;
+                        self.position += 2;
+                        if depth == 0 {
+                            break;
+                        }
+                    } else if self.next().is_none() {
+                        return Err(self.error("unterminated comment"));
+                    }
+                }
+            } else if self.peek().is_some_and(char::is_whitespace) {
+                self.next();
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Skips the `#![enable(..)]` extension attributes
+    fn skip_attributes(&mut self) -> Result<(), ShadyError> {
+        self.skip_whitespace()?;
+        while self.rest().starts_with("#!") {
+            let end = self
+                .rest()
+                .find(']')
+                .ok_or_else(|| self.error("unterminated attribute"))?;
+            self.position += end + 1;
+            self.skip_whitespace()?;
+        }
+        Ok(())
+    }
+
+    fn identifier(&mut self) -> Option<&'a str> {
+        let rest = self.rest();
+        let start = rest.strip_prefix("r#").map_or(0, |_| 2);
+        let length = rest[start..]
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len() - start);
+        let identifier = &rest[start..start + length];
+        if identifier.is_empty() || identifier.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        self.position += start + length;
+        Some(identifier)
+    }
+
+    /// Is the parser before a `field:` struct field, the position is left unchanged
+    fn at_struct_field(&mut self) -> Result<bool, ShadyError> {
+        let position = self.position;
+        self.skip_whitespace()?;
+        let is_field = self.identifier().is_some() && {
+            self.skip_whitespace()?;
+            self.rest().starts_with(':') && !self.rest().starts_with("::")
+        };
+        self.position = position;
+        Ok(is_field)
+    }
+
+    fn value(&mut self) -> Result<Value, ShadyError> {
+        self.skip_whitespace()?;
+        match self.peek() {
+            Some('[') => {
+                self.next();
+                Ok(Value::Sequence(self.elements(']')?))
+            }
+            Some('{') => {
+                self.next();
+                self.map()
+            }
+            Some('(') => {
+                self.next();
+                self.parenthesized()
+            }
+            Some('"') => self.string().map(Value::String),
+            Some('\'') => self.character().map(Value::String),
+            Some(c) if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => self.number(),
+            Some('r') if self.rest().starts_with("r\"") || self.rest().starts_with("r#\"") => {
+                self.next();
+                self.raw_string().map(Value::String)
+            }
+            Some(_) => {
+                let identifier = self
+                    .identifier()
+                    .ok_or_else(|| self.error("unexpected character"))?;
+                self.named(identifier)
+            }
+            None => Err(self.error("unexpected end of document")),
+        }
+    }
+
+    /// Value starting with `identifier`: booleans, options, special floats and enum variants
+    fn named(&mut self, identifier: &str) -> Result<Value, ShadyError> {
+        let position = self.position;
+        self.skip_whitespace()?;
+        let has_content = self.rest().starts_with('(');
+        if !has_content {
+            self.position = position;
+        }
+        Ok(match (identifier, has_content) {
+            ("true", false) => Value::Bool(true),
+            ("false", false) => Value::Bool(false),
+            ("None", false) => Value::Null,
+            ("inf", false) => Value::Number(Number::from(f64::INFINITY)),
+            ("NaN", false) => Value::Number(Number::from(f64::NAN)),
+            ("Some", true) => {
+                self.next();
+                let value = self.value()?;
+                self.expect(')')?;
+                value
+            }
+            (_, false) => Value::String(identifier.to_string()),
+            (_, true) => {
+                self.next();
+                let content = self.parenthesized()?;
+                let mut variant = Mapping::new();
+                variant.insert(Value::String(identifier.to_string()), content);
+                Value::Mapping(variant)
+            }
+        })
+    }
+
+    /// Content of parentheses after the opening one: a struct, a tuple or a single value
+    fn parenthesized(&mut self) -> Result<Value, ShadyError> {
+        if self.at_struct_field()? {
+            let mut fields = Mapping::new();
+            loop {
+                self.skip_whitespace()?;
+                if self.rest().starts_with(')') {
+                    self.next();
+                    return Ok(Value::Mapping(fields));
+                }
+                let field = self
+                    .identifier()
+                    .ok_or_else(|| self.error("expected a field name"))?;
+                self.expect(':')?;
+                fields.insert(Value::String(field.to_string()), self.value()?);
+                self.separator(')')?;
+            }
+        }
+        let mut elements = self.elements(')')?;
+        Ok(match elements.len() {
+            0 => Value::Null,
+            1 => elements.remove(0),
+            _ => Value::Sequence(elements),
+        })
+    }
+
+    /// Comma separated values up to the `closing` delimiter, which is consumed
+    fn elements(&mut self, closing: char) -> Result<Vec<Value>, ShadyError> {
+        let mut elements = Vec::new();
+        loop {
+            self.skip_whitespace()?;
+            if self.peek() == Some(closing) {
+                self.next();
+                return Ok(elements);
+            }
+            elements.push(self.value()?);
+            self.separator(closing)?;
+        }
+    }
+
+    fn map(&mut self) -> Result<Value, ShadyError> {
+        let mut entries = Mapping::new();
+        loop {
+            self.skip_whitespace()?;
+            if self.peek() == Some('}') {
+                self.next();
+                return Ok(Value::Mapping(entries));
+            }
+            let key = self.value()?;
+            self.expect(':')?;
+            entries.insert(key, self.value()?);
+            self.separator('}')?;
+        }
+    }
+
+    /// Consumes the `,` between elements, which may be omitted before the `closing` delimiter
+    fn separator(&mut self, closing: char) -> Result<(), ShadyError> {
+        self.skip_whitespace()?;
+        match self.peek() {
+            Some(',') => {
+                self.next();
+                Ok(())
+            }
+            Some(c) if c == closing => Ok(()),
+            _ => Err(self.error(&format!("expected `,` or `{}`", closing))),
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, ShadyError> {
+        let rest = self.rest();
+        let mut length = 0;
+        let mut previous = ' ';
+        for c in rest.chars() {
+            let exponent_sign = (c == '+' || c == '-')
+                && (length == 0 || (matches!(previous, 'e' | 'E') && !rest.starts_with("0x")));
+            if !(c.is_ascii_alphanumeric() || c == '_' || c == '.' || exponent_sign) {
+                break;
+            }
+            previous = c;
+            length += c.len_utf8();
+        }
+        self.position += length;
+        let token = rest[..length].replace('_', "");
+        let (negative, digits) = token.strip_prefix('-').map_or_else(
+            || (false, token.trim_start_matches('+')),
+            |digits| (true, digits),
+        );
+        let radix = match digits.get(..2) {
+            Some("0x") => Some(16),
+            Some("0o") => Some(8),
+            Some("0b") => Some(2),
+            _ => None,
+        };
+        let number = match (radix, digits) {
+            (Some(radix), _) => i64::from_str_radix(&digits[2..], radix)
+                .ok()
+                .map(|n| Number::from(if negative { -n } else { n })),
+            (None, "inf") => Some(Number::from(if negative {
+                f64::NEG_INFINITY
+            } else {
+                f64::INFINITY
+            })),
+            (None, _) if digits.contains(['.', 'e', 'E']) || digits == "NaN" => {
+                token.parse::<f64>().ok().map(Number::from)
+            }
+            (None, _) if negative => token.parse::<i64>().ok().map(Number::from),
+            (None, _) => digits.parse::<u64>().ok().map(Number::from),
+        };
+        number
+            .map(Value::Number)
+            .ok_or_else(|| self.error(&format!("invalid number `{}`", token)))
+    }
+
+    fn escaped(&mut self) -> Result<char, ShadyError> {
+        Ok(match self.next() {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('0') => '\0',
+            Some('u') => {
+                self.expect('{')?;
+                let end = self
+                    .rest()
+                    .find('}')
+                    .ok_or_else(|| self.error("unterminated unicode escape"))?;
+                let code = u32::from_str_radix(&self.rest()[..end], 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| self.error("invalid unicode escape"))?;
+                self.position += end + 1;
+                code
+            }
+            Some('x') => {
+                let code = self
+                    .rest()
+                    .get(..2)
+                    .and_then(|code| u8::from_str_radix(code, 16).ok())
+                    .ok_or_else(|| self.error("invalid byte escape"))?;
+                self.position += 2;
+                char::from(code)
+            }
+            Some(c) => c,
+            None => return Err(self.error("unterminated escape")),
+        })
+    }
+
+    fn string(&mut self) -> Result<String, ShadyError> {
+        self.next();
+        let mut string = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(string),
+                Some('\\') => string.push(self.escaped()?),
+                Some(c) => string.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn raw_string(&mut self) -> Result<String, ShadyError> {
+        let hashes = self.rest().len() - self.rest().trim_start_matches('#').len();
+        self.position += hashes;
+        self.expect('"')?;
+        let terminator = format!("\"{}", "#".repeat(hashes));
+        let end = self
+            .rest()
+            .find(&terminator)
+            .ok_or_else(|| self.error("unterminated raw string"))?;
+        let string = self.rest()[..end].to_string();
+        self.position += end + terminator.len();
+        Ok(string)
+    }
+
+    fn character(&mut self) -> Result<String, ShadyError> {
+        self.next();
+        let c = match self.next() {
+            Some('\\') => self.escaped()?,
+            Some(c) => c,
+            None => return Err(self.error("unterminated character")),
+        };
+        if self.next() != Some('\'') {
+            return Err(self.error("unterminated character"));
+        }
+        Ok(c.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_enum_variants() {
+        let document = parse(
+            "#![enable(implicit_some)]
+            // Header
+            (
+                unit: Float,
+                newtype: NativeFunction(Sine(Vec2)),
+                tuple: Vec3ToVec2(Float, (X, Z)),
+                structure: Variant(a: 1, b: [-2, 3.5e1]),
+                option: Some(\"some \\\"text\\\"\"),
+                none: None,
+                map: {Float: High, \"key\": r#\"raw \"string\"\"#},
+                /* nested /* block */ comment */
+                float: -inf,
+            )",
+        )
+        .unwrap();
+        let expected: Value = serde_yaml::from_str(
+            "unit: Float
+newtype: { NativeFunction: { Sine: Vec2 } }
+tuple: { Vec3ToVec2: [Float, [X, Z]] }
+structure: { Variant: { a: 1, b: [-2, 35.0] } }
+option: some \"text\"
+none: ~
+map: { Float: High, key: raw \"string\" }
+float: -.inf",
+        )
+        .unwrap();
+        assert_eq!(document, expected);
+    }
+
+    #[test]
+    fn rejects_invalid_documents() {
+        for data in ["(a: 1", "(a: 1) b", "[1 2]", "\"text", "(a: 0xZ)"] {
+            assert!(
+                matches!(parse(data), Err(ShadyError::InvalidSaveFormat(_))),
+                "{}",
+                data
+            );
+        }
+    }
+}
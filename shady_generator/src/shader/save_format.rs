@@ -0,0 +1,245 @@
+use crate::error::ShadyError;
+use crate::shader::migration::migrate;
+use crate::shader::ron_document;
+use crate::shader::SAVE_HEADER;
+use crate::{Diagnostic, LoadMode, Shader};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::fs::{read_to_string, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+/// Serialization format of shader save files
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub enum SaveFormat {
+    /// YAML format, default
+    Yaml,
+    /// JSON format
+    Json,
+    /// Rusty Object Notation format
+    Ron,
+}
+
+impl Default for SaveFormat {
+    fn default() -> Self {
+        Self::Yaml
+    }
+}
+
+impl SaveFormat {
+    /// All enum variants
+    pub const VARIANTS: &'static [Self] = &[Self::Yaml, Self::Json, Self::Ron];
+
+    /// All the supported save file extensions
+    pub const EXTENSIONS: &'static [&'static str] = &["yaml", "yml", "json", "ron"];
+
+    /// Default file extension of the format
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Self::Yaml => "yaml",
+            Self::Json => "json",
+            Self::Ron => "ron",
+        }
+    }
+
+    /// Retrieves the format matching the file `extension`
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "yaml" | "yml" => Some(Self::Yaml),
+            "json" => Some(Self::Json),
+            "ron" => Some(Self::Ron),
+            _ => None,
+        }
+    }
+
+    /// Retrieves the format matching the extension of `file_path`
+    pub fn from_path<P: AsRef<Path>>(file_path: P) -> Result<Self, ShadyError> {
+        let path = file_path.as_ref();
+        path.extension()
+            .and_then(|e| e.to_str())
+            .and_then(Self::from_extension)
+            .ok_or_else(|| ShadyError::UnknownSaveFormat(path.display().to_string()))
+    }
+}
+
+impl Display for SaveFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                SaveFormat::Yaml => "YAML",
+                SaveFormat::Json => "JSON",
+                SaveFormat::Ron => "RON",
+            }
+        )
+    }
+}
+
+impl Shader {
+    /// Serializes the shader in the given `format`, YAML and RON data start with a header comment
+    pub fn to_string_as(&self, format: SaveFormat) -> Result<String, ShadyError> {
+        Ok(match format {
+            SaveFormat::Yaml => format!("# {}\n{}", SAVE_HEADER, serde_yaml::to_string(self)?),
+            SaveFormat::Json => serde_json::to_string_pretty(self)?,
+            SaveFormat::Ron => format!(
+                "// {}\n{}",
+                SAVE_HEADER,
+                ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::new())?
+            ),
+        })
+    }
+
+    /// Deserializes a shader from `data` in the given `format`, failing on broken connections.
+    /// See `Shader::from_str_with_mode`
+    pub fn from_str_as(data: &str, format: SaveFormat) -> Result<Self, ShadyError> {
+        Self::from_str_with_mode(data, format, LoadMode::Strict).map(|(shader, _)| shader)
+    }
+
+    /// Deserializes a shader from `data` in the given `format`, migrating it to the current
+    /// `SAVE_FORMAT_VERSION`, then reconciling the nodes with their operation and checking every
+    /// connection through `Shader::repair`.
+    ///
+    /// The shader is returned with the repair report, which only holds warnings in
+    /// `LoadMode::Strict`
+    pub fn from_str_with_mode(
+        data: &str,
+        format: SaveFormat,
+        mode: LoadMode,
+    ) -> Result<(Self, Vec<Diagnostic>), ShadyError> {
+        let mut shader: Self = match format {
+            SaveFormat::Yaml => serde_yaml::from_value(migrate(serde_yaml::from_str(data)?)?)?,
+            SaveFormat::Json => serde_yaml::from_value(migrate(serde_json::from_str(data)?)?)?,
+            SaveFormat::Ron => serde_yaml::from_value(migrate(ron_document::parse(data)?)?)?,
+        };
+        let report = shader.repair();
+        if mode == LoadMode::Strict && report.iter().any(Diagnostic::is_error) {
+            return Err(ShadyError::BrokenSave(report));
+        }
+        Ok((shader, report))
+    }
+
+    /// Saves the shader to `file_path` in the given `format`
+    pub fn save_to_as<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        format: SaveFormat,
+    ) -> Result<(), ShadyError> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .open(file_path)?;
+        let data = self.to_string_as(format)?;
+        file.write_all(data.as_bytes())?;
+        Ok(())
+    }
+
+    /// Loads a save file in the given `format`. See `Shader::from_str_with_mode`
+    pub fn load_as<P: AsRef<Path>>(
+        file_path: P,
+        format: SaveFormat,
+        mode: LoadMode,
+    ) -> Result<(Self, Vec<Diagnostic>), ShadyError> {
+        let val = read_to_string(file_path)?;
+        Self::from_str_with_mode(&val, format, mode)
+    }
+
+    /// Loads a save file with the format matching its extension, falling back to YAML like
+    /// `Shader::save_to`. See `Shader::from_str_with_mode`
+    pub fn load_with_mode<P: AsRef<Path>>(
+        file_path: P,
+        mode: LoadMode,
+    ) -> Result<(Self, Vec<Diagnostic>), ShadyError> {
+        let format = SaveFormat::from_path(&file_path).unwrap_or_default();
+        Self::load_as(file_path, format, mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shader::to_glsl::tests::init_example_shader_1;
+    use crate::tests::test_library;
+    use crate::{NativeType, ShaderPrecision, SAVE_FORMAT_VERSION};
+
+    #[test]
+    fn finds_format_from_path() {
+        assert_eq!(
+            SaveFormat::from_path("shader.yml").unwrap(),
+            SaveFormat::Yaml
+        );
+        assert_eq!(
+            SaveFormat::from_path("dir/shader.JSON").unwrap(),
+            SaveFormat::Json
+        );
+        assert_eq!(
+            SaveFormat::from_path("shader.ron").unwrap(),
+            SaveFormat::Ron
+        );
+        assert!(matches!(
+            SaveFormat::from_path("shader.txt"),
+            Err(ShadyError::UnknownSaveFormat(_))
+        ));
+    }
+
+    #[test]
+    fn every_format_round_trips() {
+        let mut shader = init_example_shader_1();
+        shader
+            .default_precisions
+            .insert(NativeType::Float, ShaderPrecision::High);
+        for format in SaveFormat::VARIANTS {
+            let data = shader.to_string_as(*format).unwrap();
+            let loaded = Shader::from_str_as(&data, *format).unwrap();
+            assert_eq!(loaded.to_string_as(*format).unwrap(), data);
+            assert_eq!(
+                loaded.to_glsl(&test_library()).unwrap(),
+                shader.to_glsl(&test_library()).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn saves_unknown_extensions_as_yaml() {
+        let shader = init_example_shader_1();
+        let path = std::env::temp_dir().join("shady_unknown_extension.shader");
+        shader.save_to(&path).unwrap();
+        let data = read_to_string(&path).unwrap();
+        assert_eq!(data, shader.to_string_as(SaveFormat::Yaml).unwrap());
+        let (loaded, _) = Shader::load_with_mode(&path, LoadMode::Strict).unwrap();
+        assert_eq!(
+            loaded.to_glsl(&test_library()).unwrap(),
+            shader.to_glsl(&test_library()).unwrap()
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn migrates_json_documents() {
+        let shader = init_example_shader_1();
+        let mut document: serde_json::Value =
+            serde_json::from_str(&shader.to_string_as(SaveFormat::Json).unwrap()).unwrap();
+        let document_map = document.as_object_mut().unwrap();
+        document_map.remove("format_version");
+        document_map.insert("max_processing_depth".to_string(), 256.into());
+        let loaded = Shader::from_str_as(&document.to_string(), SaveFormat::Json).unwrap();
+        assert_eq!(
+            loaded.to_glsl(&test_library()).unwrap(),
+            shader.to_glsl(&test_library()).unwrap()
+        );
+    }
+
+    #[test]
+    fn migrates_ron_documents() {
+        let (shader, report) =
+            Shader::load_with_mode("test/migrations/shader_example_1_v1.ron", LoadMode::Strict)
+                .unwrap();
+        assert!(report.is_empty());
+        assert_eq!(shader.format_version(), SAVE_FORMAT_VERSION);
+        assert_eq!(
+            shader.to_glsl(&test_library()).unwrap(),
+            init_example_shader_1().to_glsl(&test_library()).unwrap()
+        );
+    }
+}
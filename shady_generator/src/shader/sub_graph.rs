@@ -0,0 +1,777 @@
+use super::node_generation::{NodeGeneration, VisitState};
+use crate::error::ShadyError;
+use crate::node_operation::NodeOperation;
+use crate::{
+    CodeWriter, Connection, CustomOutput, GraphicLibrary, Handle, Input, InputField, InputProperty,
+    NativeType, Node, Output, OutputProperty, Shader, SlotMap,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+
+/// Reusable shader graph, instantiated as a node by the shader owning it and generated once as a
+/// function.
+///
+/// The graph input properties are the function parameters and its output properties the function
+/// results: a single output property is returned as is, several output properties are returned in
+/// a `struct`. The graph nodes may instantiate the other sub-graphs of the owning shader.
+///
+/// The target settings of the graph, like its library or GLSL version, are ignored in favor of
+/// the owning shader ones, and the graph variables are always named after their ids
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubGraph {
+    name: String,
+    graph: Shader,
+}
+
+/// Sub-graphs used by a code generation, directly or not, with their ordered nodes
+#[derive(Debug, Default)]
+pub(super) struct SubGraphGeneration {
+    /// Every sub-graph is after the sub-graphs it instantiates
+    ordered_sub_graphs: Vec<(Handle<SubGraph>, NodeGeneration)>,
+}
+
+impl SubGraph {
+    /// Instantiates an empty sub-graph, `name` being the identifier of its generated function
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            graph: Shader::new(name.to_string()),
+        }
+    }
+
+    /// Retrieves the sub-graph name, also the name of its generated function
+    pub const fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Retrieves the sub-graph graph
+    pub const fn graph(&self) -> &Shader {
+        &self.graph
+    }
+
+    /// Retrieves the sub-graph graph to edit. Nodes instantiating the sub-graph must be recreated
+    /// through `SubGraph::operation` when its input or output properties change
+    pub const fn graph_mut(&mut self) -> &mut Shader {
+        &mut self.graph
+    }
+
+    /// Name of the `struct` returned by the generated function for several output properties
+    pub fn struct_name(&self) -> String {
+        format!("{}_output", self.name)
+    }
+
+    /// Input properties, sorted by reference
+    fn parameters(&self) -> Vec<&InputProperty> {
+        let mut parameters: Vec<_> = self.graph.input_properties.iter().collect();
+        parameters.sort_by_key(|(k, _)| *k);
+        parameters.into_iter().map(|(_, p)| p).collect()
+    }
+
+    /// Output properties, sorted by reference
+    fn results(&self) -> Vec<&OutputProperty> {
+        let mut results: Vec<_> = self.graph.output_properties.iter().collect();
+        results.sort_by_key(|(k, _)| *k);
+        results.into_iter().map(|(_, p)| p).collect()
+    }
+
+    fn output(&self) -> Result<Output, ShadyError> {
+        let results = self.results();
+        match results.as_slice() {
+            [] => Err(ShadyError::EmptySubGraph(self.name.clone())),
+            [result] => Ok(Output::NativeType(result.native_type)),
+            _ => Ok(Output::CustomType(CustomOutput {
+                struct_name: self.struct_name(),
+                fields: results
+                    .into_iter()
+                    .map(|p| (p.reference.clone(), p.native_type))
+                    .collect(),
+            })),
+        }
+    }
+
+    /// Operation instantiating the sub-graph as a node.
+    ///
+    /// The node input fields are the graph input properties and its output fields the graph
+    /// output properties, named after their references. A single output property makes a single
+    /// output node
+    pub fn operation(&self) -> Result<NodeOperation, ShadyError> {
+        Ok(NodeOperation::SubGraph {
+            name: self.name.clone(),
+            input: Input {
+                fields: self
+                    .parameters()
+                    .into_iter()
+                    .map(|p| (p.reference.clone(), InputField::new(p.native_type)))
+                    .collect(),
+            },
+            output: self.output()?,
+        })
+    }
+
+    /// Names of the sub-graphs instantiated by the graph nodes
+    fn instantiated_sub_graphs(&self) -> impl Iterator<Item = &String> {
+        self.graph.nodes.values().filter_map(Node::sub_graph_name)
+    }
+
+    /// Returned value of the function, from the output property connections
+    fn return_value(&self, default_value: impl Fn(&NativeType) -> String) -> String {
+        let values: Vec<String> = self
+            .results()
+            .into_iter()
+            .map(|property| {
+                property.connection.as_ref().map_or_else(
+                    || {
+                        log::warn!(
+                            "No connection set for output property {} of sub-graph {}. Using default value",
+                            property.reference,
+                            self.name
+                        );
+                        default_value(&property.native_type)
+                    },
+                    Connection::glsl_call,
+                )
+            })
+            .collect();
+        if values.len() == 1 {
+            values.join("")
+        } else {
+            format!("{}({})", self.struct_name(), values.join(", "))
+        }
+    }
+
+    fn write_glsl_function<W: Write>(
+        &self,
+        writer: &mut CodeWriter<W>,
+        nodes: &NodeGeneration,
+        shader: &Shader,
+    ) -> Result<(), ShadyError> {
+        let parameters: Vec<String> = self
+            .parameters()
+            .into_iter()
+            .map(|p| format!("{} {}", p.native_type.get_glsl_type(), p.reference))
+            .collect();
+        writer.line(format_args!(
+            "{} {}({}) {{",
+            self.output()?.glsl_type(),
+            self.name,
+            parameters.join(", ")
+        ))?;
+        writer.indent();
+        let mut constants: Vec<_> = self.graph.constants.iter().collect();
+        constants.sort_by_key(|(k, _)| *k);
+        for (_, constant) in constants {
+            writer.line(constant.glsl_declaration(shader.glsl_version)?)?;
+        }
+        for node in nodes.nodes(&self.graph) {
+            writer.line(node.to_glsl(shader.glsl_version, shader.shader_type))?;
+        }
+        let value = self.return_value(|t| t.default_glsl_value(shader.glsl_version));
+        writer.line(format_args!("return {};", value))?;
+        writer.dedent();
+        write!(writer, "}}")?;
+        Ok(())
+    }
+
+    fn write_wgsl_function<W: Write>(
+        &self,
+        writer: &mut CodeWriter<W>,
+        nodes: &NodeGeneration,
+        shader: &Shader,
+    ) -> Result<(), ShadyError> {
+        let parameters = self
+            .parameters()
+            .into_iter()
+            .map(|p| {
+                Ok(format!(
+                    "{}: {}",
+                    p.reference,
+                    p.native_type.get_wgsl_type()?
+                ))
+            })
+            .collect::<Result<Vec<String>, ShadyError>>()?;
+        writer.line(format_args!(
+            "fn {}({}) -> {} {{",
+            self.name,
+            parameters.join(", "),
+            self.output()?.wgsl_type()?
+        ))?;
+        writer.indent();
+        let mut constants: Vec<_> = self.graph.constants.iter().collect();
+        constants.sort_by_key(|(k, _)| *k);
+        for (_, constant) in constants {
+            writer.line(constant.wgsl_declaration()?)?;
+        }
+        for node in nodes.nodes(&self.graph) {
+            writer.line(node.to_wgsl(shader.shader_type)?)?;
+        }
+        let value = self.return_value(NativeType::default_wgsl_value);
+        writer.line(format_args!("return {};", value))?;
+        writer.dedent();
+        write!(writer, "}}")?;
+        Ok(())
+    }
+}
+
+impl SubGraphGeneration {
+    /// Iterates the graph of every generated sub-graph
+    pub fn graphs<'a>(&'a self, shader: &'a Shader) -> impl Iterator<Item = &'a Shader> {
+        self.ordered_sub_graphs
+            .iter()
+            .map(|(handle, _)| &shader.sub_graphs[*handle].graph)
+    }
+
+    /// Iterates the ordered nodes of every sub-graph
+    pub fn nodes<'a>(&'a self, shader: &'a Shader) -> impl Iterator<Item = &'a Node> {
+        self.ordered_sub_graphs
+            .iter()
+            .flat_map(|(handle, nodes)| nodes.nodes(&shader.sub_graphs[*handle].graph))
+    }
+
+    /// Iterates the ordered nodes of every sub-graph along with the sub-graph they belong to
+    pub fn graph_nodes<'a>(
+        &'a self,
+        shader: &'a Shader,
+    ) -> impl Iterator<Item = (&'a Shader, &'a Node)> {
+        self.ordered_sub_graphs.iter().flat_map(|(handle, nodes)| {
+            let graph = &shader.sub_graphs[*handle].graph;
+            nodes.nodes(graph).map(move |node| (graph, node))
+        })
+    }
+
+    /// Function declarations of the sub-graphs written for `library`, every sub-graph being
+    /// declared after the sub-graphs it instantiates
+    pub fn functions(
+        &self,
+        shader: &Shader,
+        library: GraphicLibrary,
+    ) -> Result<Vec<String>, ShadyError> {
+        self.ordered_sub_graphs
+            .iter()
+            .map(|(handle, nodes)| {
+                let sub_graph = &shader.sub_graphs[*handle];
+                let mut declaration = String::new();
+                let mut writer = CodeWriter::new(&mut declaration);
+                match library {
+                    GraphicLibrary::OpenGl | GraphicLibrary::OpenGlEs => {
+                        sub_graph.write_glsl_function(&mut writer, nodes, shader)?;
+                    }
+                    GraphicLibrary::WebGPU => {
+                        sub_graph.write_wgsl_function(&mut writer, nodes, shader)?;
+                    }
+                }
+                Ok(declaration)
+            })
+            .collect()
+    }
+}
+
+impl Shader {
+    pub const fn sub_graphs(&self) -> &SlotMap<SubGraph> {
+        &self.sub_graphs
+    }
+
+    /// Adds a sub-graph, to instantiate as a node through `SubGraph::operation`.
+    ///
+    /// The sub-graph may instantiate sub-graphs added before or after it, but sub-graphs
+    /// instantiating each other are refused with a `ShadyError::SubGraphLoopDetected`. To edit an
+    /// added sub-graph, remove it with `Shader::remove_sub_graph` then add it back
+    pub fn add_sub_graph(&mut self, sub_graph: SubGraph) -> Result<&SubGraph, ShadyError> {
+        let name = sub_graph.name.clone();
+        if self.sub_graphs.contains_name(&name) {
+            return Err(ShadyError::DuplicateSubGraph(name));
+        }
+        self.check_new_identifier(&name)?;
+        if let Some(cycle) = self.instantiation_cycle(&sub_graph) {
+            return Err(ShadyError::SubGraphLoopDetected(cycle));
+        }
+        let (handle, _) = self.sub_graphs.insert(name, sub_graph);
+        Ok(&self.sub_graphs[handle])
+    }
+
+    /// Removes the sub-graph with `name`. The nodes instantiating it are kept and fail the code
+    /// generation until a matching sub-graph is added
+    pub fn remove_sub_graph(&mut self, name: &str) -> Option<SubGraph> {
+        let res = self.sub_graphs.remove_by_name(name);
+        if res.is_none() {
+            log::error!("Could not find sub-graph with name {} to remove", name);
+        }
+        res
+    }
+
+    /// Path of sub-graph names from `sub_graph` back to itself through the instantiated
+    /// sub-graphs, each sub-graph instantiating the next one
+    fn instantiation_cycle(&self, sub_graph: &SubGraph) -> Option<Vec<String>> {
+        let target = &sub_graph.name;
+        // Visited sub-graphs, with the sub-graph they were reached from
+        let mut parents: HashMap<&String, &String> = HashMap::new();
+        let mut queue = VecDeque::new();
+        for name in sub_graph.instantiated_sub_graphs() {
+            if !parents.contains_key(name) {
+                parents.insert(name, target);
+                queue.push_back(name);
+            }
+        }
+        while let Some(name) = queue.pop_front() {
+            if name == target {
+                let mut path = vec![target.clone()];
+                let mut parent = parents[target];
+                while parent != target {
+                    path.push(parent.clone());
+                    parent = parents[parent];
+                }
+                path.push(target.clone());
+                path.reverse();
+                return Some(path);
+            }
+            let Some(instantiated) = self.sub_graphs.get_by_name(name) else {
+                continue;
+            };
+            for dependency in instantiated.instantiated_sub_graphs() {
+                if !parents.contains_key(dependency) {
+                    parents.insert(dependency, name);
+                    queue.push_back(dependency);
+                }
+            }
+        }
+        None
+    }
+
+    /// Handles of the sub-graphs instantiated by `nodes`, sorted by name, checking every node
+    /// matches the current ports of its sub-graph
+    fn instantiated_sub_graphs<'a>(
+        &self,
+        nodes: impl Iterator<Item = &'a Node>,
+    ) -> Result<Vec<Handle<SubGraph>>, ShadyError> {
+        let mut res = Vec::new();
+        for node in nodes {
+            let Some(name) = node.sub_graph_name() else {
+                continue;
+            };
+            let handle = self
+                .sub_graphs
+                .handle(name)
+                .ok_or_else(|| ShadyError::MissingSubGraph(name.clone()))?;
+            let expected = Node::new_with_custom_id(
+                node.name(),
+                node.unique_id(),
+                self.sub_graphs[handle].operation()?,
+            );
+            if !same_signature(node, &expected) {
+                return Err(ShadyError::SubGraphMismatch {
+                    node: node.unique_id().clone(),
+                    sub_graph: name.clone(),
+                });
+            }
+            res.push((name, handle));
+        }
+        res.sort_unstable();
+        res.dedup();
+        Ok(res.into_iter().map(|(_, handle)| handle).collect())
+    }
+
+    /// Sorts the sub-graphs instantiated by the `nodes`, directly or through other sub-graphs, so
+    /// every sub-graph is declared after the sub-graphs it instantiates.
+    ///
+    /// Sub-graphs instantiating each other return a `ShadyError::SubGraphLoopDetected` with the
+    /// cycle path
+    pub(super) fn sub_graphs_generation(
+        &self,
+        nodes: &NodeGeneration,
+    ) -> Result<SubGraphGeneration, ShadyError> {
+        let mut res = SubGraphGeneration::default();
+        let mut visited = vec![None; self.sub_graphs.capacity()];
+        let mut path = Vec::new();
+        for handle in self.instantiated_sub_graphs(nodes.nodes(self))? {
+            self.visit_sub_graph(handle, &mut visited, &mut path, &mut res)?;
+        }
+        Ok(res)
+    }
+
+    fn visit_sub_graph(
+        &self,
+        handle: Handle<SubGraph>,
+        visited: &mut [Option<VisitState>],
+        path: &mut Vec<Handle<SubGraph>>,
+        res: &mut SubGraphGeneration,
+    ) -> Result<(), ShadyError> {
+        match visited[handle.index()] {
+            Some(VisitState::Done) => return Ok(()),
+            Some(VisitState::InProgress) => {
+                let cycle = path
+                    .iter()
+                    .skip_while(|h| **h != handle)
+                    .chain(std::iter::once(&handle))
+                    .map(|h| self.sub_graphs[*h].name.clone())
+                    .collect();
+                return Err(ShadyError::SubGraphLoopDetected(cycle));
+            }
+            None => (),
+        }
+        log::trace!("Processing sub-graph {:?}", handle);
+        visited[handle.index()] = Some(VisitState::InProgress);
+        path.push(handle);
+        let graph = &self.sub_graphs[handle].graph;
+        self.check_sub_graph_identifiers(graph)?;
+        let nodes = graph.nodes_generation()?;
+        for dependency in self.instantiated_sub_graphs(nodes.nodes(graph))? {
+            self.visit_sub_graph(dependency, visited, path, res)?;
+        }
+        path.pop();
+        visited[handle.index()] = Some(VisitState::Done);
+        res.ordered_sub_graphs.push((handle, nodes));
+        Ok(())
+    }
+
+    /// Checks the identifiers of a sub-graph `graph`, which can't hide a sub-graph function
+    fn check_sub_graph_identifiers(&self, graph: &Self) -> Result<(), ShadyError> {
+        graph.check_identifiers()?;
+        if let Some(id) = graph
+            .identifiers()
+            .find(|id| self.sub_graphs.contains_name(id))
+        {
+            return Err(ShadyError::IdentifierConflict(id.clone()));
+        }
+        Ok(())
+    }
+}
+
+/// Whether `node` has the input fields, output fields and output type of `expected`
+fn same_signature(node: &Node, expected: &Node) -> bool {
+    let input = |node: &Node| -> Vec<(String, NativeType)> {
+        node.input_fields()
+            .into_iter()
+            .map(|(k, f)| (k, f.glsl_type()))
+            .collect()
+    };
+    input(node) == input(expected)
+        && node.output_fields().field_names() == expected.output_fields().field_names()
+        && node.output().glsl_type() == expected.output().glsl_type()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_operation::NativeOperation;
+    use crate::shader::test_helpers::{
+        connect, input_property, node_field, node_output, output_property,
+    };
+    use crate::tests::test_library;
+    use crate::{Constant, ConstantValue, GlslVersion, SaveFormat};
+
+    /// Sub-graph with a float input and output property
+    fn init_port_sub_graph(name: &str) -> SubGraph {
+        let mut sub_graph = SubGraph::new(name);
+        let graph = sub_graph.graph_mut();
+        graph
+            .add_input_property(InputProperty::new(NativeType::Float, false, "in"))
+            .unwrap();
+        graph
+            .add_output_property(OutputProperty::new(NativeType::Float, "out"))
+            .unwrap();
+        sub_graph
+    }
+
+    /// `half_sum(a, b)`, returning `(a + b) * HALF`
+    fn init_half_sum() -> SubGraph {
+        let mut sub_graph = SubGraph::new("half_sum");
+        let graph = sub_graph.graph_mut();
+        for id in ["a", "b"] {
+            graph
+                .add_input_property(InputProperty::new(NativeType::Float, false, id))
+                .unwrap();
+        }
+        graph
+            .add_output_property(OutputProperty::new(NativeType::Float, "res"))
+            .unwrap();
+        graph
+            .add_constant(Constant {
+                name: "half".to_string(),
+                value: ConstantValue::Float(0.5),
+            })
+            .unwrap();
+        for (name, id, operation) in [
+            ("Sum", "sum", NativeOperation::Add(NativeType::Float)),
+            ("Scaled", "scaled", NativeOperation::Mul(NativeType::Float)),
+        ] {
+            graph
+                .create_node(Node::new_with_custom_id(
+                    name,
+                    id,
+                    NodeOperation::NativeOperation(operation),
+                ))
+                .unwrap();
+        }
+        connect(graph, input_property("float_a"), node_field("sum", "a"));
+        connect(graph, input_property("float_b"), node_field("sum", "b"));
+        connect(graph, node_output("sum"), node_field("scaled", "a"));
+        let half = Connection::Constant {
+            id: "HALF".to_string(),
+        };
+        connect(graph, half, node_field("scaled", "b"));
+        connect(graph, node_output("scaled"), output_property("float_res"));
+        sub_graph
+    }
+
+    /// `sum_and_half(x, y)`, returning both `x + y` and `half_sum(x, y)`
+    fn init_sum_and_half(half_sum: &SubGraph) -> SubGraph {
+        let mut sub_graph = SubGraph::new("sum_and_half");
+        let graph = sub_graph.graph_mut();
+        for id in ["x", "y"] {
+            graph
+                .add_input_property(InputProperty::new(NativeType::Float, false, id))
+                .unwrap();
+        }
+        for id in ["sum", "half"] {
+            graph
+                .add_output_property(OutputProperty::new(NativeType::Float, id))
+                .unwrap();
+        }
+        graph
+            .create_node(Node::new_with_custom_id(
+                "Half",
+                "h",
+                half_sum.operation().unwrap(),
+            ))
+            .unwrap();
+        graph
+            .create_node(Node::new_with_custom_id(
+                "Sum",
+                "s",
+                NodeOperation::NativeOperation(NativeOperation::Add(NativeType::Float)),
+            ))
+            .unwrap();
+        for (from, field) in [("float_x", "float_a"), ("float_y", "float_b")] {
+            connect(graph, input_property(from), node_field("h", field));
+        }
+        for (from, field) in [("float_x", "a"), ("float_y", "b")] {
+            connect(graph, input_property(from), node_field("s", field));
+        }
+        connect(graph, node_output("h"), output_property("float_half"));
+        connect(graph, node_output("s"), output_property("float_sum"));
+        sub_graph
+    }
+
+    fn init_sub_graph_shader() -> Shader {
+        let mut shader = Shader::new("Sub Graphs".to_string());
+        let half_sum = init_half_sum();
+        let sum_and_half = init_sum_and_half(&half_sum);
+        let operation = sum_and_half.operation().unwrap();
+        shader.add_sub_graph(sum_and_half).unwrap();
+        shader.add_sub_graph(half_sum).unwrap();
+        shader
+            .add_input_property(InputProperty::new(NativeType::Float, true, "i"))
+            .unwrap();
+        shader
+            .add_output_property(OutputProperty::new(NativeType::Float, "o"))
+            .unwrap();
+        shader
+            .create_node(Node::new_with_custom_id("Split", "n", operation))
+            .unwrap();
+        for field in ["float_x", "float_y"] {
+            connect(
+                &mut shader,
+                input_property("float_i"),
+                node_field("n", field),
+            );
+        }
+        let from = Connection::ComplexOutputNode {
+            id: "n".to_string(),
+            field_name: "float_half".to_string(),
+        };
+        connect(&mut shader, from, output_property("float_o"));
+        shader
+    }
+
+    #[test]
+    fn generates_sub_graph_functions() {
+        let shader = init_sub_graph_shader();
+        assert_eq!(
+            shader.to_glsl(&test_library()).unwrap(),
+            formatdoc! {"
+            #version 450 core
+            // Constants
+
+            // Properties
+            uniform float float_i; // float
+            out float float_o; // float
+
+            // Struct Declarations
+            struct sum_and_half_output {{
+              float float_half;
+              float float_sum;
+            }};
+
+            // Function declarations
+            float half_sum(float float_a, float float_b) {{
+                const float HALF = 0.5;
+                float sum = float_a + float_b; // Sum Node
+                float scaled = sum * HALF; // Scaled Node
+                return scaled;
+            }}
+
+            sum_and_half_output sum_and_half(float float_x, float float_y) {{
+                float h = half_sum(float_x, float_y); // Half Node
+                float s = float_x + float_y; // Sum Node
+                return sum_and_half_output(h, s);
+            }}
+
+            // Main Function
+            void main() {{
+                sum_and_half_output n = sum_and_half(float_i, float_i); // Split Node
+                
+                // Output properties
+                float_o = n.float_half; // float
+                
+            }}
+            "}
+        );
+    }
+
+    #[test]
+    fn generates_wgsl_sub_graph_functions() {
+        let mut shader = init_sub_graph_shader();
+        shader.library = GraphicLibrary::WebGPU;
+        let code = shader.to_wgsl(&test_library()).unwrap();
+        assert!(code.contains(indoc! {"
+            fn half_sum(float_a: f32, float_b: f32) -> f32 {
+                const HALF: f32 = 0.5;
+                let sum: f32 = float_a + float_b; // Sum Node
+                let scaled: f32 = sum * HALF; // Scaled Node
+                return scaled;
+            }
+
+            fn sum_and_half(float_x: f32, float_y: f32) -> sum_and_half_output {
+                let h: f32 = half_sum(float_x, float_y); // Half Node
+                let s: f32 = float_x + float_y; // Sum Node
+                return sum_and_half_output(h, s);
+            }
+        "}));
+        assert!(code.contains("let n: sum_and_half_output = sum_and_half(float_i, float_i);"));
+    }
+
+    #[test]
+    fn refuses_sub_graph_loops() {
+        let mut first = init_port_sub_graph("first");
+        let mut second = init_port_sub_graph("second");
+        let first_operation = first.operation().unwrap();
+        let second_operation = second.operation().unwrap();
+        for (sub_graph, name, operation) in [
+            (&mut first, "Second", second_operation),
+            (&mut second, "First", first_operation.clone()),
+        ] {
+            let graph = sub_graph.graph_mut();
+            graph
+                .create_node(Node::new_with_custom_id(name, "n", operation))
+                .unwrap();
+            connect(graph, node_output("n"), output_property("float_out"));
+        }
+        let mut shader = Shader::default();
+        shader.add_sub_graph(first).unwrap();
+        assert!(matches!(
+            shader.add_sub_graph(second.clone()),
+            Err(ShadyError::SubGraphLoopDetected(cycle)) if cycle == ["second", "first", "second"]
+        ));
+        let mut looping = init_port_sub_graph("looping");
+        let operation = looping.operation().unwrap();
+        looping
+            .graph_mut()
+            .create_node(Node::new_with_custom_id("Looping", "n", operation))
+            .unwrap();
+        assert!(matches!(
+            shader.add_sub_graph(looping),
+            Err(ShadyError::SubGraphLoopDetected(cycle)) if cycle == ["looping", "looping"]
+        ));
+
+        // Loops from a save file are detected by the code generation
+        shader.sub_graphs.insert("second".to_string(), second);
+        shader
+            .add_output_property(OutputProperty::new(NativeType::Float, "o"))
+            .unwrap();
+        shader
+            .create_node(Node::new_with_custom_id("First", "n", first_operation))
+            .unwrap();
+        connect(&mut shader, node_output("n"), output_property("float_o"));
+        assert!(matches!(
+            shader.to_glsl(&test_library()),
+            Err(ShadyError::SubGraphLoopDetected(cycle)) if cycle == ["first", "second", "first"]
+        ));
+    }
+
+    #[test]
+    fn checks_sub_graph_instances() {
+        let mut shader = init_sub_graph_shader();
+        assert!(matches!(
+            shader.add_sub_graph(SubGraph::new("half_sum")),
+            Err(ShadyError::DuplicateSubGraph(_))
+        ));
+        assert!(matches!(
+            shader.add_sub_graph(SubGraph::new("n")),
+            Err(ShadyError::IdentifierConflict(_))
+        ));
+        assert!(matches!(
+            SubGraph::new("empty").operation(),
+            Err(ShadyError::EmptySubGraph(_))
+        ));
+
+        let mut half_sum = shader.remove_sub_graph("half_sum").unwrap();
+        assert!(matches!(
+            shader.to_glsl(&test_library()),
+            Err(ShadyError::MissingSubGraph(name)) if name == "half_sum"
+        ));
+        half_sum
+            .graph_mut()
+            .add_input_property(InputProperty::new(NativeType::Float, false, "c"))
+            .unwrap();
+        shader.add_sub_graph(half_sum).unwrap();
+        assert!(matches!(
+            shader.to_glsl(&test_library()),
+            Err(ShadyError::SubGraphMismatch { node, sub_graph })
+                if node == "h" && sub_graph == "half_sum"
+        ));
+    }
+
+    #[test]
+    fn checks_sub_graph_glsl_types() {
+        let mut shader = init_sub_graph_shader();
+        shader.library = GraphicLibrary::OpenGlEs;
+        shader.glsl_version = GlslVersion::GlslEs100;
+        assert!(shader.to_glsl(&test_library()).is_ok());
+        let mut half_sum = shader.remove_sub_graph("half_sum").unwrap();
+        half_sum
+            .graph_mut()
+            .add_constant(Constant {
+                name: "count".to_string(),
+                value: ConstantValue::UInt(2),
+            })
+            .unwrap();
+        shader.add_sub_graph(half_sum).unwrap();
+        assert!(matches!(
+            shader.to_glsl(&test_library()),
+            Err(ShadyError::UnsupportedNativeType {
+                native_type: NativeType::UInt,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn sub_graphs_round_trip() {
+        let shader = init_sub_graph_shader();
+        for format in SaveFormat::VARIANTS {
+            let data = shader.to_string_as(*format).unwrap();
+            let loaded = Shader::from_str_as(&data, *format).unwrap();
+            assert_eq!(
+                loaded.to_glsl(&test_library()).unwrap(),
+                shader.to_glsl(&test_library()).unwrap()
+            );
+            let half_sum = loaded.sub_graphs().get_by_name("half_sum").unwrap();
+            assert_eq!(
+                half_sum.graph().downstream_nodes("float_a"),
+                ["scaled", "sum"]
+            );
+        }
+        let data = Shader::default().to_string_as(SaveFormat::Yaml).unwrap();
+        assert!(!data.contains("sub_graphs"));
+    }
+}
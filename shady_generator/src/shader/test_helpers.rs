@@ -0,0 +1,29 @@
+use crate::{Connection, ConnectionAttempt, ConnectionTo, Shader};
+
+pub fn connect(graph: &mut Shader, from: Connection, to: ConnectionTo) {
+    graph
+        .connect(ConnectionAttempt {
+            connection_from: from,
+            connection_to: to,
+        })
+        .unwrap();
+}
+
+pub fn node_output(id: &str) -> Connection {
+    Connection::SingleOutputNode { id: id.to_string() }
+}
+
+pub fn node_field(id: &str, field_name: &str) -> ConnectionTo {
+    ConnectionTo::Node {
+        id: id.to_string(),
+        field_name: field_name.to_string(),
+    }
+}
+
+pub fn input_property(id: &str) -> Connection {
+    Connection::InputProperty { id: id.to_string() }
+}
+
+pub fn output_property(id: &str) -> ConnectionTo {
+    ConnectionTo::OutputProperty { id: id.to_string() }
+}
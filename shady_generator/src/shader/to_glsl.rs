@@ -1,203 +1,287 @@
+use super::node_generation::NodeGeneration;
+use super::sub_graph::SubGraphGeneration;
 use crate::error::ShadyError;
-use crate::{Connection, Shader};
-use std::collections::HashMap;
-
-#[derive(Debug, Clone, Default)]
-struct NodeGeneration {
-    pub node_data: HashMap<String, String>,
-    pub ordered_nodes: Vec<String>,
-}
-
-impl NodeGeneration {
-    fn to_glsl(&self) -> String {
-        let mut buffer = String::new();
-        for id in &self.ordered_nodes {
-            buffer = format!(
-                "{}{}\n    ",
-                buffer,
-                self.node_data.get(id).unwrap().clone()
-            );
-        }
-        buffer
-    }
-}
+use crate::node_operation::FunctionLibrary;
+use crate::{CodeWriter, NamingStrategy, NativeType, Shader, ShaderPrecision, ShaderType};
+use std::collections::HashSet;
+use std::fmt::Write;
 
 impl Shader {
-    fn get_constants_declarations(&self) -> String {
-        let mut declarations = String::new();
-        let mut constants: Vec<(String, String)> = self
-            .constants
-            .iter()
-            .map(|(k, v)| (k.clone(), v.glsl_declaration()))
-            .collect();
-        constants.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
-        for (_, value) in constants {
-            declarations = format!("{}{}\n", declarations, value);
+    fn write_constants_declarations<W: Write>(
+        &self,
+        writer: &mut CodeWriter<W>,
+    ) -> Result<(), ShadyError> {
+        let mut constants: Vec<(&String, _)> = self.constants.iter().collect();
+        constants.sort_by_key(|(k, _)| *k);
+        for (_, constant) in constants {
+            writer.line(constant.glsl_declaration(self.glsl_version)?)?;
         }
-        declarations
+        Ok(())
     }
 
-    fn get_property_declarations(&self) -> String {
-        let mut property_declarations = String::new();
-        let mut input: Vec<(String, String)> = self
-            .input_properties
-            .iter()
-            .map(|(k, v)| (k.clone(), v.glsl_declaration()))
-            .collect();
-        input.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
-        for (_, value) in input {
-            property_declarations = format!("{}{}\n", property_declarations, value);
+    fn write_property_declarations<W: Write>(
+        &self,
+        writer: &mut CodeWriter<W>,
+    ) -> Result<(), ShadyError> {
+        let mut input: Vec<_> = self.input_properties.iter().collect();
+        input.sort_by_key(|(k, _)| *k);
+        for (_, property) in input {
+            writer.line(property.glsl_declaration(self.glsl_version, self.shader_type)?)?;
         }
-        let mut output: Vec<(String, String)> = self
-            .output_properties
-            .iter()
-            .map(|(k, v)| (k.clone(), v.glsl_declaration()))
-            .collect();
-        output.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
-        for (_, value) in output {
-            property_declarations = format!("{}{}\n", property_declarations, value);
+        let mut output: Vec<_> = self.output_properties.iter().collect();
+        output.sort_by_key(|(k, _)| *k);
+        let count = output.len();
+        for (index, (_, property)) in output.into_iter().enumerate() {
+            writer.line(property.glsl_declaration(
+                self.glsl_version,
+                self.shader_type,
+                index,
+                count,
+            )?)?;
         }
-        property_declarations
+        Ok(())
     }
 
-    fn output_property_generation(&self) -> String {
-        let mut res = String::new();
-        let mut output: Vec<(String, String)> = self
-            .output_properties
-            .iter()
-            .map(|(k, v)| (k.clone(), v.to_glsl()))
-            .collect();
-        output.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
-        for (_, value) in output {
-            res = format!("{}{}\n    ", res, value);
+    fn check_glsl_type(&self, native_type: NativeType) -> Result<(), ShadyError> {
+        if self.glsl_version.supports(native_type) {
+            Ok(())
+        } else {
+            Err(ShadyError::UnsupportedNativeType {
+                native_type,
+                target: self.glsl_version.to_string(),
+            })
         }
-        res
     }
 
-    fn nodes_generation(
+    /// Checks the `glsl_version` against the `library`, every type used by the properties and
+    /// constants of the shader and its `sub_graphs`, the `nodes` and `sub_graphs` nodes, and the
+    /// native functions of the nodes
+    fn check_glsl_target(
         &self,
-        mut nodes_to_handle: Vec<String>,
-    ) -> Result<NodeGeneration, ShadyError> {
-        let mut res = NodeGeneration::default();
-        let mut required_nodes = Vec::new();
-
-        for depth in 0..=self.max_processing_depth {
-            log::debug!(
-                "Depth: {}, {} nodes to declare",
-                depth,
-                nodes_to_handle.len()
-            );
-            nodes_to_handle.sort_unstable();
-            nodes_to_handle.dedup();
-            let tmp_nodes = nodes_to_handle
-                .iter()
-                .try_fold(Vec::new(), |mut acc, node_id| {
-                    log::trace!("Processing node {}", node_id);
-                    let node = self.get_node(node_id)?;
-                    let connections = node.node_connections();
-                    if !res.node_data.contains_key(node_id) {
-                        res.node_data.insert(node_id.clone(), node.to_glsl());
-                    }
-                    acc.extend(connections);
-                    Result::<_, ShadyError>::Ok(acc)
-                })?;
-            // TODO: Check if this is enough to detect loops
-            if required_nodes.contains(&nodes_to_handle) {
-                return Err(ShadyError::NodeLoopDetected(nodes_to_handle));
+        nodes: &NodeGeneration,
+        sub_graphs: &SubGraphGeneration,
+    ) -> Result<(), ShadyError> {
+        if self.glsl_version.library() != self.library {
+            return Err(ShadyError::IncompatibleGlslVersion {
+                version: self.glsl_version,
+                library: self.library,
+            });
+        }
+        for graph in std::iter::once(self).chain(sub_graphs.graphs(self)) {
+            for constant in graph.constants.values() {
+                self.check_glsl_type(constant.native_type())?;
             }
-            required_nodes.push(nodes_to_handle);
-            if tmp_nodes.is_empty() {
-                log::info!("Finished processing nodes at depth {}", depth);
-                break;
-            } else if depth == self.max_processing_depth {
-                return Err(ShadyError::MaxDepthReached(self.max_processing_depth));
+            for property in graph.input_properties.values() {
+                self.check_glsl_type(property.native_type)?;
+            }
+            for property in graph.output_properties.values() {
+                self.check_glsl_type(property.native_type)?;
             }
-            nodes_to_handle = tmp_nodes;
         }
-        required_nodes.reverse();
-        for node_id in required_nodes.into_iter().flatten() {
-            if !res.ordered_nodes.contains(&node_id) {
-                res.ordered_nodes.push(node_id);
+        for node in nodes.nodes(self).chain(sub_graphs.nodes(self)) {
+            for native_type in node.native_types() {
+                self.check_glsl_type(native_type)?;
+            }
+            if let Some(function) = node.native_function() {
+                if !self.glsl_version.supports_function(function) {
+                    return Err(ShadyError::UnsupportedNativeFunction {
+                        function: function.function_name().to_string(),
+                        target: self.glsl_version.to_string(),
+                    });
+                }
+            }
+            if let Some(operation) = node.native_operation() {
+                if !self.glsl_version.supports_operation(operation) {
+                    return Err(ShadyError::UnsupportedNativeOperation {
+                        operation: operation.descriptive_name().to_string(),
+                        target: self.glsl_version.to_string(),
+                    });
+                }
             }
         }
-        log::trace!("Node Generation: {:#?}", res);
-        Ok(res)
+        Ok(())
     }
 
-    pub fn to_glsl(&self) -> Result<String, ShadyError> {
-        let constants_declarations = self.get_constants_declarations();
-        let property_declarations = self.get_property_declarations();
-
-        let nodes_to_handle = self
-            .output_properties
+    fn uses_high_precision(&self, nodes: &NodeGeneration, sub_graphs: &SubGraphGeneration) -> bool {
+        let high = Some(ShaderPrecision::High);
+        if self
+            .default_precisions
             .values()
-            .filter_map(|p| {
-                if let Some(
-                    Connection::ComplexOutputNode { id, .. } | Connection::SingleOutputNode { id },
-                ) = &p.connection
-                {
-                    Some(id.clone())
-                } else {
-                    None
+            .any(|p| *p == ShaderPrecision::High)
+            || self.input_properties.values().any(|p| p.precision == high)
+            || self.output_properties.values().any(|p| p.precision == high)
+        {
+            return true;
+        }
+        nodes
+            .nodes(self)
+            .chain(sub_graphs.nodes(self))
+            .any(|node| node.precision() == high)
+    }
+
+    /// Writes the default precision statements of ES targets. Fragment shaders have no default
+    /// `float` precision, so `ShaderPrecision::default()` is used if none is set. It is also used
+    /// for sampler properties without predefined default precision nor precision qualifier
+    fn write_precision_declarations<W: Write>(
+        &self,
+        writer: &mut CodeWriter<W>,
+        nodes: &NodeGeneration,
+        sub_graphs: &SubGraphGeneration,
+    ) -> Result<(), ShadyError> {
+        if !self.glsl_version.is_es() {
+            return Ok(());
+        }
+        let mut precisions: Vec<(NativeType, ShaderPrecision)> = self
+            .default_precisions
+            .iter()
+            .filter(|(native_type, _)| {
+                let valid = ShaderPrecision::has_default_precision(**native_type);
+                if !valid {
+                    log::warn!("Type {} can't have a default precision", native_type);
                 }
+                valid
             })
+            .map(|(native_type, precision)| (*native_type, *precision))
             .collect();
-
-        // Output properties code
-        let output_properties = self.output_property_generation();
-        let main_content = self.nodes_generation(nodes_to_handle)?;
-
-        let mut struct_declarations = Vec::new();
-        let mut function_declarations = Vec::new();
-        for node_id in main_content.node_data.keys() {
-            let node = self.get_node(node_id)?;
-            if let Some(declaration) = node.function_declaration()? {
-                function_declarations.push(declaration);
+        if self.shader_type == ShaderType::Fragment
+            && !self.default_precisions.contains_key(&NativeType::Float)
+        {
+            precisions.push((NativeType::Float, ShaderPrecision::default()));
+        }
+        for property in self.input_properties.values() {
+            let native_type = property.native_type;
+            if property.precision.is_none()
+                && !ShaderPrecision::has_predefined_precision(native_type)
+                && !precisions.iter().any(|(t, _)| *t == native_type)
+            {
+                precisions.push((native_type, ShaderPrecision::default()));
             }
-            if let Some(declaration) = node.struct_declaration() {
-                struct_declarations.push(declaration);
+        }
+        precisions.sort_by_key(|(native_type, _)| native_type.get_glsl_type());
+        let high_precision =
+            self.shader_type == ShaderType::Fragment && self.uses_high_precision(nodes, sub_graphs);
+        if precisions.is_empty() && !high_precision {
+            return Ok(());
+        }
+        writer.section("Precisions")?;
+        if high_precision {
+            writer.line(ShaderPrecision::high_fragment_precision_definition())?;
+        }
+        for (native_type, precision) in precisions {
+            writer.line(precision.to_glsl(native_type, self.shader_type))?;
+        }
+        writer.empty_line()?;
+        Ok(())
+    }
+
+    /// Writes the `struct` and function declarations required by the `nodes` and `sub_graphs`,
+    /// each declared once
+    fn write_node_declarations<W: Write>(
+        &self,
+        writer: &mut CodeWriter<W>,
+        nodes: &NodeGeneration,
+        sub_graphs: &SubGraphGeneration,
+        functions: &FunctionLibrary,
+    ) -> Result<(), ShadyError> {
+        let mut struct_names = HashSet::new();
+        writer.section("Struct Declarations")?;
+        for node in nodes.nodes(self).chain(sub_graphs.nodes(self)) {
+            if let Some(custom) = node.output().custom_type() {
+                if struct_names.insert(&custom.struct_name) {
+                    if struct_names.len() > 1 {
+                        writer.empty_line()?;
+                    }
+                    custom.write_glsl_struct_declaration(writer)?;
+                }
             }
         }
+        writer.empty_line()?;
+
+        writer.section("Function declarations")?;
+        self.write_function_declarations(writer, nodes, sub_graphs, functions, self.library)?;
+        Ok(())
+    }
+
+    fn write_output_properties<W: Write>(&self, writer: &mut CodeWriter<W>) -> std::fmt::Result {
+        let mut output: Vec<_> = self.output_properties.iter().collect();
+        output.sort_by_key(|(k, _)| *k);
+        for (_, property) in output {
+            writer.line(property.to_glsl(self.glsl_version))?;
+        }
+        Ok(())
+    }
+
+    /// Generates the GLSL code of the shader, with variables named through the `naming_strategy`
+    /// and custom functions declared from `functions`
+    pub fn to_glsl(&self, functions: &FunctionLibrary) -> Result<String, ShadyError> {
+        let mut code = String::new();
+        self.write_glsl(&mut code, functions)?;
+        Ok(code)
+    }
+
+    /// Streams the GLSL code of the shader into `output`, see `to_glsl`.
+    ///
+    /// The code is written while generated, on error `output` may hold partial code
+    pub fn write_glsl(
+        &self,
+        output: &mut impl Write,
+        functions: &FunctionLibrary,
+    ) -> Result<(), ShadyError> {
+        let mut writer = CodeWriter::new(output);
+        match self.naming_strategy {
+            NamingStrategy::Id => self.generate_glsl(&mut writer, functions),
+            NamingStrategy::Readable => self
+                .renamed(&self.readable_names(functions, self.library)?)
+                .generate_glsl(&mut writer, functions),
+        }
+    }
+
+    fn generate_glsl<W: Write>(
+        &self,
+        writer: &mut CodeWriter<W>,
+        functions: &FunctionLibrary,
+    ) -> Result<(), ShadyError> {
+        self.check_identifiers()?;
+        let nodes = self.nodes_generation()?;
+        let sub_graphs = self.sub_graphs_generation(&nodes)?;
+        self.check_glsl_target(&nodes, &sub_graphs)?;
+
+        writer.line(self.glsl_version.header())?;
+        self.write_precision_declarations(writer, &nodes, &sub_graphs)?;
+        writer.section("Constants")?;
+        self.write_constants_declarations(writer)?;
+        writer.empty_line()?;
+        writer.section("Properties")?;
+        self.write_property_declarations(writer)?;
+        writer.empty_line()?;
+        self.write_node_declarations(writer, &nodes, &sub_graphs, functions)?;
 
-        struct_declarations.dedup();
-        function_declarations.dedup();
-        let struct_declarations = struct_declarations.join("\n\n");
-        let function_declarations = function_declarations.join("\n\n");
-
-        Ok(formatdoc! {"
-            // Constants
-            {constants}
-            // Properties
-            {properties}
-            // Struct Declarations
-            {structs}
-            // Function declarations
-            {functions}
-            // Main Function
-            void main() {{
-                {main}
-                // Output properties
-                {output}
-            }}
-        ", 
-            constants = constants_declarations,
-            properties = property_declarations,
-            structs = struct_declarations,
-            functions = function_declarations,
-            main = main_content.to_glsl(),
-            output = output_properties
-        })
+        writer.section("Main Function")?;
+        writer.line("void main() {")?;
+        writer.indent();
+        nodes.write_glsl(self, writer)?;
+        writer.empty_line()?;
+        writer.section("Output properties")?;
+        self.write_output_properties(writer)?;
+        writer.empty_line()?;
+        writer.dedent();
+        writer.line("}")?;
+        Ok(())
     }
 }
 
 #[cfg(test)]
-mod tests {
+pub(super) mod tests {
     #![allow(clippy::too_many_lines)]
 
     use super::*;
     use crate::node_operation::NodeOperation;
     use crate::shader::{InputProperty, OutputProperty};
-    use crate::{ConnectionAttempt, ConnectionTo, Input, InputField, NativeType, Node, Output};
+    use crate::tests::test_library;
+    use crate::{
+        Connection, ConnectionAttempt, ConnectionTo, GlslVersion, GraphicLibrary, Input,
+        InputField, Node, Output,
+    };
 
     fn init_base_operation() -> NodeOperation {
         NodeOperation::CustomOperation {
@@ -212,7 +296,7 @@ mod tests {
         }
     }
 
-    fn init_basic_shader() -> Shader {
+    pub(in crate::shader) fn init_basic_shader() -> Shader {
         let mut shader = Shader::new("Basic Shader".to_string());
 
         shader
@@ -221,6 +305,7 @@ mod tests {
                 reference: "Gl_Pos123".to_string(),
                 native_type: NativeType::Vec3,
                 uniform: false,
+                precision: None,
             })
             .unwrap();
         shader
@@ -228,6 +313,7 @@ mod tests {
                 name: "Out_Pos".to_string(),
                 reference: "Out_Pos456".to_string(),
                 native_type: NativeType::Vec3,
+                precision: None,
                 connection: None,
             })
             .unwrap();
@@ -245,7 +331,7 @@ mod tests {
         shader
     }
 
-    fn init_simple_shader() -> Shader {
+    pub(in crate::shader) fn init_simple_shader() -> Shader {
         let mut shader = Shader::new("Simple Shader".to_string());
 
         shader
@@ -254,6 +340,7 @@ mod tests {
                 reference: "Gl_Pos123".to_string(),
                 native_type: NativeType::Vec3,
                 uniform: false,
+                precision: None,
             })
             .unwrap();
         shader
@@ -261,6 +348,7 @@ mod tests {
                 name: "Out_Pos".to_string(),
                 reference: "Out_Pos456".to_string(),
                 native_type: NativeType::Vec2,
+                precision: None,
                 connection: None,
             })
             .unwrap();
@@ -303,7 +391,7 @@ mod tests {
         shader
     }
 
-    fn init_example_shader_1() -> Shader {
+    pub(in crate::shader) fn init_example_shader_1() -> Shader {
         let mut shader = Shader::new("Shader Example 1".to_string());
 
         shader
@@ -312,6 +400,7 @@ mod tests {
                 reference: "i".to_string(),
                 native_type: NativeType::Float,
                 uniform: false,
+                precision: None,
             })
             .unwrap();
         shader
@@ -319,6 +408,7 @@ mod tests {
                 name: "O_1".to_string(),
                 reference: "o_1".to_string(),
                 native_type: NativeType::Float,
+                precision: None,
                 connection: None,
             })
             .unwrap();
@@ -327,6 +417,7 @@ mod tests {
                 name: "O_2".to_string(),
                 reference: "o_2".to_string(),
                 native_type: NativeType::Float,
+                precision: None,
                 connection: None,
             })
             .unwrap();
@@ -335,6 +426,7 @@ mod tests {
                 name: "O_3".to_string(),
                 reference: "o_3".to_string(),
                 native_type: NativeType::Float,
+                precision: None,
                 connection: None,
             })
             .unwrap();
@@ -461,7 +553,7 @@ mod tests {
         shader
     }
 
-    fn init_example_shader_2() -> Shader {
+    pub(in crate::shader) fn init_example_shader_2() -> Shader {
         let mut shader = Shader::new("Shader Example 2".to_string());
 
         shader
@@ -470,6 +562,7 @@ mod tests {
                 reference: "i1".to_string(),
                 native_type: NativeType::Float,
                 uniform: false,
+                precision: None,
             })
             .unwrap();
         shader
@@ -478,6 +571,7 @@ mod tests {
                 reference: "i2".to_string(),
                 native_type: NativeType::Float,
                 uniform: false,
+                precision: None,
             })
             .unwrap();
         shader
@@ -485,6 +579,7 @@ mod tests {
                 name: "O_1".to_string(),
                 reference: "o_1".to_string(),
                 native_type: NativeType::Float,
+                precision: None,
                 connection: None,
             })
             .unwrap();
@@ -493,6 +588,7 @@ mod tests {
                 name: "O_2".to_string(),
                 reference: "o_2".to_string(),
                 native_type: NativeType::Float,
+                precision: None,
                 connection: None,
             })
             .unwrap();
@@ -501,6 +597,7 @@ mod tests {
                 name: "O_3".to_string(),
                 reference: "o_3".to_string(),
                 native_type: NativeType::Float,
+                precision: None,
                 connection: None,
             })
             .unwrap();
@@ -680,7 +777,7 @@ mod tests {
         shader
     }
 
-    fn init_looping_shader_1() -> Shader {
+    pub(in crate::shader) fn init_looping_shader_1() -> Shader {
         let mut shader = Shader::new("Looping Shader 1".to_string());
 
         shader
@@ -689,6 +786,7 @@ mod tests {
                 reference: "i".to_string(),
                 native_type: NativeType::Float,
                 uniform: false,
+                precision: None,
             })
             .unwrap();
         shader
@@ -696,6 +794,7 @@ mod tests {
                 name: "O_1".to_string(),
                 reference: "o_1".to_string(),
                 native_type: NativeType::Float,
+                precision: None,
                 connection: None,
             })
             .unwrap();
@@ -704,6 +803,7 @@ mod tests {
                 name: "O_2".to_string(),
                 reference: "o_2".to_string(),
                 native_type: NativeType::Float,
+                precision: None,
                 connection: None,
             })
             .unwrap();
@@ -712,6 +812,7 @@ mod tests {
                 name: "O_3".to_string(),
                 reference: "o_3".to_string(),
                 native_type: NativeType::Float,
+                precision: None,
                 connection: None,
             })
             .unwrap();
@@ -807,8 +908,9 @@ mod tests {
                 },
             })
             .unwrap();
+        // Loops are refused by `connect` but can still be loaded from a save file
         shader
-            .connect(ConnectionAttempt {
+            .apply_connection(ConnectionAttempt {
                 connection_from: Connection::SingleOutputNode {
                     id: "e".to_string(),
                 },
@@ -852,7 +954,7 @@ mod tests {
         shader
     }
 
-    fn init_looping_shader_2() -> Shader {
+    pub(in crate::shader) fn init_looping_shader_2() -> Shader {
         let mut shader = Shader::new("Looping Shader 2".to_string());
 
         shader
@@ -861,6 +963,7 @@ mod tests {
                 reference: "i".to_string(),
                 native_type: NativeType::Float,
                 uniform: false,
+                precision: None,
             })
             .unwrap();
         shader
@@ -868,6 +971,7 @@ mod tests {
                 name: "O".to_string(),
                 reference: "o".to_string(),
                 native_type: NativeType::Float,
+                precision: None,
                 connection: None,
             })
             .unwrap();
@@ -913,8 +1017,9 @@ mod tests {
                 },
             })
             .unwrap();
+        // Loops are refused by `connect` but can still be loaded from a save file
         shader
-            .connect(ConnectionAttempt {
+            .apply_connection(ConnectionAttempt {
                 connection_from: Connection::SingleOutputNode {
                     id: "c".to_string(),
                 },
@@ -942,12 +1047,20 @@ mod tests {
     mod declarations {
         use super::*;
 
+        fn property_declarations(shader: &Shader) -> String {
+            let mut declarations = String::new();
+            shader
+                .write_property_declarations(&mut CodeWriter::new(&mut declarations))
+                .unwrap();
+            declarations
+        }
+
         #[test]
         fn works_with_simple_shader() {
             let shader = init_simple_shader();
 
             assert_eq!(
-                shader.get_property_declarations().as_str(),
+                property_declarations(&shader),
                 "in vec3 Gl_Pos123; // Gl_Position\n\
                 out vec2 Out_Pos456; // Out_Pos\n"
             );
@@ -958,7 +1071,7 @@ mod tests {
             let shader = init_basic_shader();
 
             assert_eq!(
-                shader.get_property_declarations().as_str(),
+                property_declarations(&shader),
                 "in vec3 Gl_Pos123; // Gl_Position\n\
                 out vec3 Out_Pos456; // Out_Pos\n"
             );
@@ -969,7 +1082,7 @@ mod tests {
             let shader = init_example_shader_1();
 
             assert_eq!(
-                shader.get_property_declarations(),
+                property_declarations(&shader),
                 formatdoc! {"
                     in float i; // I
                     out float o_1; // O_1
@@ -984,7 +1097,7 @@ mod tests {
             let shader = init_example_shader_2();
 
             assert_eq!(
-                shader.get_property_declarations(),
+                property_declarations(&shader),
                 formatdoc! {"
                     in float i1; // I_1
                     in float i2; // I_2
@@ -1003,8 +1116,9 @@ mod tests {
         fn works_with_empty_shader() {
             let shader = Shader::default();
             assert_eq!(
-                shader.to_glsl().unwrap(),
+                shader.to_glsl(&test_library()).unwrap(),
                 formatdoc! {"
+                #version 450 core
                 // Constants
 
                 // Properties
@@ -1027,8 +1141,9 @@ mod tests {
         fn works_with_basic_shader() {
             let shader = init_basic_shader();
             assert_eq!(
-                shader.to_glsl().unwrap(),
+                shader.to_glsl(&test_library()).unwrap(),
                 formatdoc! {"
+                #version 450 core
                 // Constants
 
                 // Properties
@@ -1054,8 +1169,9 @@ mod tests {
         fn works_with_simple_shader() {
             let shader = init_simple_shader();
             assert_eq!(
-                shader.to_glsl().unwrap(),
+                shader.to_glsl(&test_library()).unwrap(),
                 formatdoc! {"
+                #version 450 core
                 // Constants
 
                 // Properties
@@ -1082,12 +1198,47 @@ mod tests {
             );
         }
 
+        #[test]
+        fn works_with_readable_names() {
+            let mut shader = init_simple_shader();
+            shader.naming_strategy = NamingStrategy::Readable;
+            assert_eq!(
+                shader.to_glsl(&test_library()).unwrap(),
+                formatdoc! {"
+                #version 450 core
+                // Constants
+
+                // Properties
+                in vec3 v_gl_position; // Gl_Position
+                out vec2 out_pos; // Out_Pos
+
+                // Struct Declarations
+
+                // Function declarations
+                // Test function
+                float test_func(float a, float b) {{
+                    // This function does nothing
+                    return a;
+                }}
+                // Main Function
+                void main() {{
+                    vec2 my_node = test_func(v_gl_position); // MyNode Node
+                    
+                    // Output properties
+                    out_pos = my_node; // Out_Pos
+                    
+                }}
+                "}
+            );
+        }
+
         #[test]
         fn works_with_complex_shader() {
             let shader = init_example_shader_1();
             assert_eq!(
-                shader.to_glsl().unwrap(),
+                shader.to_glsl(&test_library()).unwrap(),
                 formatdoc! {"
+                #version 450 core
                 // Constants
 
                 // Properties
@@ -1125,6 +1276,7 @@ mod tests {
         fn works_with_example_shader_2() {
             let shader = init_example_shader_2();
             let expected = formatdoc! {"
+                #version 450 core
                 // Constants
 
                 // Properties
@@ -1159,21 +1311,561 @@ mod tests {
                     
                 }}
             "};
-            assert_eq!(shader.to_glsl().unwrap(), expected);
+            assert_eq!(shader.to_glsl(&test_library()).unwrap(), expected);
         }
 
-        #[should_panic = "NodeLoopDetected"]
         #[test]
         fn fails_with_looping_shader_1() {
             let shader = init_looping_shader_1();
-            shader.to_glsl().unwrap();
+            match shader.to_glsl(&test_library()) {
+                Err(ShadyError::NodeLoopDetected(cycle)) => {
+                    assert_eq!(cycle, vec!["c", "b", "e", "c"]);
+                }
+                res => panic!("Expected NodeLoopDetected, got {:?}", res),
+            }
         }
 
-        #[should_panic = "NodeLoopDetected"]
         #[test]
         fn fails_with_looping_shader_2() {
             let shader = init_looping_shader_2();
-            shader.to_glsl().unwrap();
+            match shader.to_glsl(&test_library()) {
+                Err(ShadyError::NodeLoopDetected(cycle)) => {
+                    assert_eq!(cycle, vec!["a", "c", "b", "a"]);
+                }
+                res => panic!("Expected NodeLoopDetected, got {:?}", res),
+            }
+        }
+
+        #[test]
+        fn works_with_legacy_es_shader() {
+            let mut shader = init_basic_shader();
+            shader.library = GraphicLibrary::OpenGlEs;
+            shader.glsl_version = GlslVersion::GlslEs100;
+            assert_eq!(
+                shader.to_glsl(&test_library()).unwrap(),
+                formatdoc! {"
+                #version 100
+                // Constants
+
+                // Properties
+                attribute vec3 Gl_Pos123; // Gl_Position
+                varying vec3 Out_Pos456; // Out_Pos
+
+                // Struct Declarations
+
+                // Function declarations
+
+                // Main Function
+                void main() {{
+                    
+                    // Output properties
+                    Out_Pos456 = Gl_Pos123; // Out_Pos
+                    
+                }}
+                "}
+            );
         }
+
+        #[test]
+        fn works_with_es_fragment_shader() {
+            let mut shader = init_basic_shader();
+            shader.library = GraphicLibrary::OpenGlEs;
+            shader.glsl_version = GlslVersion::GlslEs300;
+            shader.shader_type = ShaderType::Fragment;
+            assert_eq!(
+                shader.to_glsl(&test_library()).unwrap(),
+                formatdoc! {"
+                #version 300 es
+                // Precisions
+                precision mediump float;
+
+                // Constants
+
+                // Properties
+                in vec3 Gl_Pos123; // Gl_Position
+                out vec3 Out_Pos456; // Out_Pos
+
+                // Struct Declarations
+
+                // Function declarations
+
+                // Main Function
+                void main() {{
+                    
+                    // Output properties
+                    Out_Pos456 = Gl_Pos123; // Out_Pos
+                    
+                }}
+                "}
+            );
+        }
+
+        #[test]
+        fn works_with_high_precision() {
+            let mut shader = init_basic_shader();
+            shader.library = GraphicLibrary::OpenGlEs;
+            shader.glsl_version = GlslVersion::GlslEs300;
+            shader.shader_type = ShaderType::Fragment;
+            shader
+                .default_precisions
+                .insert(NativeType::Int, ShaderPrecision::Low);
+            shader
+                .add_input_property(InputProperty {
+                    name: "Color".to_string(),
+                    reference: "color".to_string(),
+                    native_type: NativeType::Vec4,
+                    uniform: true,
+                    precision: Some(ShaderPrecision::High),
+                })
+                .unwrap();
+            assert_eq!(
+                shader.to_glsl(&test_library()).unwrap(),
+                formatdoc! {"
+                #version 300 es
+                // Precisions
+                #ifdef GL_FRAGMENT_PRECISION_HIGH
+                #define SHADY_HIGHP highp
+                #else
+                #define SHADY_HIGHP mediump
+                #endif
+                precision mediump float;
+                precision lowp int;
+
+                // Constants
+
+                // Properties
+                in vec3 Gl_Pos123; // Gl_Position
+                uniform SHADY_HIGHP vec4 color; // Color
+                out vec3 Out_Pos456; // Out_Pos
+
+                // Struct Declarations
+
+                // Function declarations
+
+                // Main Function
+                void main() {{
+                    
+                    // Output properties
+                    Out_Pos456 = Gl_Pos123; // Out_Pos
+                    
+                }}
+                "}
+            );
+        }
+
+        #[test]
+        fn ignores_precision_on_desktop() {
+            let mut shader = init_basic_shader();
+            shader
+                .default_precisions
+                .insert(NativeType::Float, ShaderPrecision::High);
+            assert!(!shader
+                .to_glsl(&test_library())
+                .unwrap()
+                .contains("precision"));
+        }
+
+        #[test]
+        fn fails_with_unsupported_type() {
+            let mut shader = init_basic_shader();
+            shader.library = GraphicLibrary::OpenGlEs;
+            shader.glsl_version = GlslVersion::GlslEs100;
+            shader
+                .add_input_property(InputProperty {
+                    name: "Count".to_string(),
+                    reference: "count".to_string(),
+                    native_type: NativeType::UInt,
+                    uniform: true,
+                    precision: None,
+                })
+                .unwrap();
+            assert!(matches!(
+                shader.to_glsl(&test_library()),
+                Err(ShadyError::UnsupportedNativeType {
+                    native_type: NativeType::UInt,
+                    ..
+                })
+            ));
+        }
+
+        #[test]
+        fn fails_with_incompatible_version() {
+            let mut shader = init_basic_shader();
+            shader.glsl_version = GlslVersion::GlslEs300;
+            assert!(matches!(
+                shader.to_glsl(&test_library()),
+                Err(ShadyError::IncompatibleGlslVersion { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn generates_matrix_operations() {
+        use crate::node_operation::{NativeFunction, NativeOperation};
+        use crate::{MatrixNativeType, NonScalarNativeType};
+
+        let mut shader = Shader::new("Matrix Shader".to_string());
+        for (reference, native_type) in [("model", NativeType::Mat4), ("pos", NativeType::Vec4)] {
+            shader
+                .add_input_property(InputProperty {
+                    name: reference.to_string(),
+                    reference: reference.to_string(),
+                    native_type,
+                    uniform: true,
+                    precision: None,
+                })
+                .unwrap();
+        }
+        for (reference, native_type) in [
+            ("o_pos", NativeType::Vec4),
+            ("o_axis", NativeType::Vec4),
+            ("o_det", NativeType::Float),
+        ] {
+            shader
+                .add_output_property(OutputProperty {
+                    name: reference.to_string(),
+                    reference: reference.to_string(),
+                    native_type,
+                    precision: None,
+                    connection: None,
+                })
+                .unwrap();
+        }
+        for (id, operation) in [
+            (
+                "world",
+                NodeOperation::NativeOperation(NativeOperation::MatrixVectorMul(
+                    MatrixNativeType::Mat4,
+                )),
+            ),
+            (
+                "columns",
+                NodeOperation::TypeSplit(NonScalarNativeType::Mat4),
+            ),
+            (
+                "det",
+                NodeOperation::NativeFunction(NativeFunction::Determinant(MatrixNativeType::Mat4)),
+            ),
+        ] {
+            shader
+                .create_node(Node::new_with_custom_id(id, id, operation))
+                .unwrap();
+        }
+        let model = || Connection::InputProperty {
+            id: "model".to_string(),
+        };
+        let node_field = |id: &str, field_name: &str| ConnectionTo::Node {
+            id: id.to_string(),
+            field_name: field_name.to_string(),
+        };
+        let output = |id: &str| ConnectionTo::OutputProperty { id: id.to_string() };
+        for (connection_from, connection_to) in [
+            (model(), node_field("world", "m")),
+            (
+                Connection::InputProperty {
+                    id: "pos".to_string(),
+                },
+                node_field("world", "v"),
+            ),
+            (model(), node_field("columns", "in")),
+            (model(), node_field("det", "m")),
+            (
+                Connection::SingleOutputNode {
+                    id: "world".to_string(),
+                },
+                output("o_pos"),
+            ),
+            (
+                Connection::ComplexOutputNode {
+                    id: "columns".to_string(),
+                    field_name: "3".to_string(),
+                },
+                output("o_axis"),
+            ),
+            (
+                Connection::SingleOutputNode {
+                    id: "det".to_string(),
+                },
+                output("o_det"),
+            ),
+        ] {
+            shader
+                .connect(ConnectionAttempt {
+                    connection_from,
+                    connection_to,
+                })
+                .unwrap();
+        }
+        let code = shader.to_glsl(&test_library()).unwrap();
+        for line in [
+            "uniform mat4 model;",
+            "vec4 world = model * pos; // world Node",
+            "mat4 columns = mat4(model); // columns Node",
+            "float det = determinant(model); // det Node",
+            "o_axis = columns[3]; // o_axis",
+        ] {
+            assert!(code.contains(line), "Missing `{}` in:\n{}", line, code);
+        }
+        let code = shader.to_wgsl(&test_library()).unwrap();
+        assert!(code.contains("let det: f32 = determinant(model); // det Node"));
+
+        shader.library = GraphicLibrary::OpenGlEs;
+        shader.glsl_version = GlslVersion::GlslEs100;
+        assert!(matches!(
+            shader.to_glsl(&test_library()),
+            Err(ShadyError::UnsupportedNativeFunction { function, .. }) if function == "determinant"
+        ));
+        shader.glsl_version = GlslVersion::GlslEs300;
+        assert!(shader.to_glsl(&test_library()).is_ok());
+    }
+
+    #[test]
+    fn generates_typed_swizzles() {
+        use crate::node_operation::{NonScalarSwizzle, Vec3Field};
+        use crate::ScalarNativeType;
+
+        let mut shader = Shader::new("Swizzle Shader".to_string());
+        shader
+            .add_input_property(InputProperty {
+                name: "flags".to_string(),
+                reference: "flags".to_string(),
+                native_type: NativeType::UVec3,
+                uniform: true,
+                precision: None,
+            })
+            .unwrap();
+        shader
+            .add_output_property(OutputProperty {
+                name: "o".to_string(),
+                reference: "o".to_string(),
+                native_type: NativeType::UVec2,
+                precision: None,
+                connection: None,
+            })
+            .unwrap();
+        shader
+            .create_node(Node::new_with_custom_id(
+                "s",
+                "s",
+                NodeOperation::NonScalarSwizzle(NonScalarSwizzle::Vec3ToVec2(
+                    ScalarNativeType::UInt,
+                    [Vec3Field::Z, Vec3Field::X],
+                )),
+            ))
+            .unwrap();
+        for (connection_from, connection_to) in [
+            (
+                Connection::InputProperty {
+                    id: "flags".to_string(),
+                },
+                ConnectionTo::Node {
+                    id: "s".to_string(),
+                    field_name: "i".to_string(),
+                },
+            ),
+            (
+                Connection::SingleOutputNode {
+                    id: "s".to_string(),
+                },
+                ConnectionTo::OutputProperty {
+                    id: "o".to_string(),
+                },
+            ),
+        ] {
+            shader
+                .connect(ConnectionAttempt {
+                    connection_from,
+                    connection_to,
+                })
+                .unwrap();
+        }
+        let code = shader.to_glsl(&test_library()).unwrap();
+        assert!(code.contains("uvec2 s = flags.zx; // s Node"), "{}", code);
+        let code = shader.to_wgsl(&test_library()).unwrap();
+        assert!(
+            code.contains("let s: vec2<u32> = flags.zx; // s Node"),
+            "{}",
+            code
+        );
+
+        shader.library = GraphicLibrary::OpenGlEs;
+        shader.glsl_version = GlslVersion::GlslEs100;
+        assert!(matches!(
+            shader.to_glsl(&test_library()),
+            Err(ShadyError::UnsupportedNativeType {
+                native_type: NativeType::UVec3,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn generates_texture_lookups() {
+        use crate::node_operation::NativeFunction;
+        use crate::SamplerNativeType;
+
+        let mut shader = Shader::new("Texture Shader".to_string());
+        shader
+            .add_input_property(InputProperty {
+                name: "volume".to_string(),
+                reference: "volume".to_string(),
+                native_type: NativeType::Sampler3d,
+                uniform: true,
+                precision: None,
+            })
+            .unwrap();
+        shader
+            .add_output_property(OutputProperty {
+                name: "o".to_string(),
+                reference: "o".to_string(),
+                native_type: NativeType::IVec3,
+                precision: None,
+                connection: None,
+            })
+            .unwrap();
+        shader
+            .create_node(Node::new_with_custom_id(
+                "size",
+                "size",
+                NodeOperation::NativeFunction(NativeFunction::TextureSize(
+                    SamplerNativeType::Sampler3d,
+                )),
+            ))
+            .unwrap();
+        for (connection_from, connection_to) in [
+            (
+                Connection::InputProperty {
+                    id: "volume".to_string(),
+                },
+                ConnectionTo::Node {
+                    id: "size".to_string(),
+                    field_name: "sampler".to_string(),
+                },
+            ),
+            (
+                Connection::SingleOutputNode {
+                    id: "size".to_string(),
+                },
+                ConnectionTo::OutputProperty {
+                    id: "o".to_string(),
+                },
+            ),
+        ] {
+            shader
+                .connect(ConnectionAttempt {
+                    connection_from,
+                    connection_to,
+                })
+                .unwrap();
+        }
+        let code = shader.to_glsl(&test_library()).unwrap();
+        assert!(
+            code.contains("ivec3 size = textureSize(volume, 0); // size Node"),
+            "{}",
+            code
+        );
+        let code = shader.to_wgsl(&test_library()).unwrap();
+        assert!(
+            code.contains(
+                "let size: vec3<i32> = vec3<i32>(textureDimensions(volume, 0)); // size Node"
+            ),
+            "{}",
+            code
+        );
+
+        // `sampler3D` has no predefined precision in GLSL ES
+        shader.library = GraphicLibrary::OpenGlEs;
+        shader.glsl_version = GlslVersion::GlslEs300;
+        let code = shader.to_glsl(&test_library()).unwrap();
+        assert!(code.contains("precision mediump sampler3D;"), "{}", code);
+
+        shader.glsl_version = GlslVersion::GlslEs100;
+        assert!(matches!(
+            shader.to_glsl(&test_library()),
+            Err(ShadyError::UnsupportedNativeType {
+                native_type: NativeType::Sampler3d,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn generates_out_parameter_functions() {
+        use crate::node_operation::NativeFunction;
+        use crate::FloatingNativeType;
+
+        let mut shader = Shader::new("Modf Shader".to_string());
+        shader
+            .add_input_property(InputProperty {
+                name: "v".to_string(),
+                reference: "v".to_string(),
+                native_type: NativeType::Vec3,
+                uniform: true,
+                precision: None,
+            })
+            .unwrap();
+        for reference in ["o_fract", "o_whole"] {
+            shader
+                .add_output_property(OutputProperty {
+                    name: reference.to_string(),
+                    reference: reference.to_string(),
+                    native_type: NativeType::Vec3,
+                    precision: None,
+                    connection: None,
+                })
+                .unwrap();
+        }
+        shader
+            .create_node(Node::new_with_custom_id(
+                "m",
+                "m",
+                NodeOperation::NativeFunction(NativeFunction::FractionalIntegralParts(
+                    FloatingNativeType::Vec3,
+                )),
+            ))
+            .unwrap();
+        let mut attempts = vec![ConnectionAttempt {
+            connection_from: Connection::InputProperty {
+                id: "v".to_string(),
+            },
+            connection_to: ConnectionTo::Node {
+                id: "m".to_string(),
+                field_name: "v".to_string(),
+            },
+        }];
+        for (output, field_name) in [("o_fract", "fract"), ("o_whole", "whole")] {
+            attempts.push(ConnectionAttempt {
+                connection_from: Connection::ComplexOutputNode {
+                    id: "m".to_string(),
+                    field_name: field_name.to_string(),
+                },
+                connection_to: ConnectionTo::OutputProperty {
+                    id: output.to_string(),
+                },
+            });
+        }
+        for attempt in attempts {
+            shader.connect(attempt).unwrap();
+        }
+        let code = shader.to_glsl(&test_library()).unwrap();
+        for line in [
+            "struct modf_result_vec3 {",
+            "modf_result_vec3 m; m.fract = modf(v, m.whole); // m Node",
+            "o_whole = m.whole; // o_whole",
+        ] {
+            assert!(code.contains(line), "Missing `{}` in:\n{}", line, code);
+        }
+        let code = shader.to_wgsl(&test_library()).unwrap();
+        assert!(code.contains(
+            "var m: modf_result_vec3; { let result = modf(v); \
+             m = modf_result_vec3(result.fract, result.whole); } // m Node"
+        ));
+
+        shader.library = GraphicLibrary::OpenGlEs;
+        shader.glsl_version = GlslVersion::GlslEs100;
+        assert!(matches!(
+            shader.to_glsl(&test_library()),
+            Err(ShadyError::UnsupportedNativeFunction { function, .. }) if function == "modf"
+        ));
     }
 }
@@ -0,0 +1,662 @@
+use super::node_generation::NodeGeneration;
+use super::sub_graph::SubGraphGeneration;
+use crate::error::ShadyError;
+use crate::node_operation::{FunctionLibrary, NativeOperation};
+use crate::{
+    CodeWriter, GraphicLibrary, NamingStrategy, NativeType, OutputProperty, Shader, ShaderType,
+};
+use std::collections::HashSet;
+use std::fmt::Write;
+
+const WGSL_UNIFORM_GROUP: u32 = 0;
+const WGSL_OUTPUT_STRUCT: &str = "ShaderOutput";
+/// Default vertex position member, the `gl_` prefix is reserved and can't collide with a property
+const WGSL_POSITION_FIELD: &str = "gl_Position";
+
+impl Shader {
+    fn write_wgsl_constants_declarations<W: Write>(
+        &self,
+        writer: &mut CodeWriter<W>,
+    ) -> Result<(), ShadyError> {
+        let mut constants: Vec<(&String, _)> = self.constants.iter().collect();
+        constants.sort_by_key(|(k, _)| *k);
+        for (_, constant) in constants {
+            writer.line(constant.wgsl_declaration()?)?;
+        }
+        Ok(())
+    }
+
+    fn write_wgsl_uniform_declarations<W: Write>(
+        &self,
+        writer: &mut CodeWriter<W>,
+    ) -> Result<(), ShadyError> {
+        let mut uniforms: Vec<_> = self
+            .input_properties
+            .iter()
+            .filter(|(_, p)| p.uniform || p.native_type.is_sampler())
+            .collect();
+        uniforms.sort_by_key(|(k, _)| *k);
+        let mut binding = 0;
+        for (_, property) in uniforms {
+            writer.line(property.wgsl_uniform_declaration(WGSL_UNIFORM_GROUP, binding)?)?;
+            binding += if property.native_type.is_sampler() {
+                2
+            } else {
+                1
+            };
+        }
+        Ok(())
+    }
+
+    fn get_wgsl_parameter_declarations(&self) -> Result<Vec<String>, ShadyError> {
+        let mut input: Vec<_> = self
+            .input_properties
+            .iter()
+            .filter(|(_, p)| !p.uniform && !p.native_type.is_sampler())
+            .collect();
+        input.sort_by_key(|(k, _)| *k);
+        (0..)
+            .zip(input)
+            .map(|(location, (_, property))| property.wgsl_parameter_declaration(location))
+            .collect()
+    }
+
+    /// Vertex shaders must output a `@builtin(position)`
+    fn has_wgsl_output(&self) -> bool {
+        self.shader_type == ShaderType::Vertex || !self.output_properties.is_empty()
+    }
+
+    /// Output property declared as the vertex position of vertex shaders
+    fn wgsl_vertex_position(&self) -> Result<Option<(&str, &OutputProperty)>, ShadyError> {
+        if self.shader_type != ShaderType::Vertex {
+            return Ok(None);
+        }
+        self.vertex_position
+            .as_ref()
+            .map(|id| {
+                let property = self
+                    .output_properties
+                    .get_by_name(id)
+                    .ok_or_else(|| ShadyError::MissingOutputProperty(id.clone()))?;
+                if property.native_type != NativeType::Vec4 {
+                    return Err(ShadyError::UnsupportedNativeType {
+                        native_type: property.native_type,
+                        target: format!("{} vertex position", GraphicLibrary::WebGPU),
+                    });
+                }
+                Ok((id.as_str(), property))
+            })
+            .transpose()
+    }
+
+    fn write_wgsl_output_declaration<W: Write>(
+        &self,
+        writer: &mut CodeWriter<W>,
+    ) -> Result<(), ShadyError> {
+        if !self.has_wgsl_output() {
+            return Ok(());
+        }
+        writer.line(format_args!("struct {} {{", WGSL_OUTPUT_STRUCT))?;
+        writer.indent();
+        let position = self.wgsl_vertex_position()?;
+        match position {
+            Some((_, property)) => writer.line(property.wgsl_position_declaration()?)?,
+            None if self.shader_type == ShaderType::Vertex => {
+                log::warn!("No vertex position output property set. Using default value");
+                writer.line(format_args!(
+                    "@builtin(position) {}: vec4<f32>,",
+                    WGSL_POSITION_FIELD
+                ))?;
+            }
+            None => (),
+        }
+        let mut output: Vec<_> = self
+            .output_properties
+            .iter()
+            .filter(|(id, _)| position.map(|(position, _)| position) != Some(id.as_str()))
+            .collect();
+        output.sort_by_key(|(k, _)| *k);
+        for (location, (_, property)) in (0..).zip(output) {
+            writer.line(property.wgsl_field_declaration(location)?)?;
+        }
+        writer.dedent();
+        writer.line("};")?;
+        Ok(())
+    }
+
+    fn write_wgsl_struct_declarations<W: Write>(
+        &self,
+        writer: &mut CodeWriter<W>,
+        nodes: &NodeGeneration,
+        sub_graphs: &SubGraphGeneration,
+    ) -> Result<(), ShadyError> {
+        let mut struct_names = HashSet::new();
+        for node in nodes.nodes(self).chain(sub_graphs.nodes(self)) {
+            if let Some(custom) = node.output().custom_type() {
+                if struct_names.insert(&custom.struct_name) {
+                    if struct_names.len() > 1 {
+                        writer.empty_line()?;
+                    }
+                    custom.write_wgsl_struct_declaration(writer)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_wgsl_output_properties<W: Write>(
+        &self,
+        writer: &mut CodeWriter<W>,
+    ) -> std::fmt::Result {
+        let mut output: Vec<_> = self.output_properties.iter().collect();
+        output.sort_by_key(|(k, _)| *k);
+        for (_, property) in output {
+            writer.line(property.to_wgsl())?;
+        }
+        Ok(())
+    }
+
+    /// Generates the WGSL code of the shader, with a `main` entry point, variables named through
+    /// the `naming_strategy` and custom functions declared from `functions`
+    pub fn to_wgsl(&self, functions: &FunctionLibrary) -> Result<String, ShadyError> {
+        let mut code = String::new();
+        self.write_wgsl(&mut code, functions)?;
+        Ok(code)
+    }
+
+    /// Streams the WGSL code of the shader into `output`, see `to_wgsl`.
+    ///
+    /// The code is written while generated, on error `output` may hold partial code
+    pub fn write_wgsl(
+        &self,
+        output: &mut impl Write,
+        functions: &FunctionLibrary,
+    ) -> Result<(), ShadyError> {
+        let mut writer = CodeWriter::new(output);
+        match self.naming_strategy {
+            NamingStrategy::Id => self.generate_wgsl(&mut writer, functions),
+            NamingStrategy::Readable => self
+                .renamed(&self.readable_names(functions, GraphicLibrary::WebGPU)?)
+                .generate_wgsl(&mut writer, functions),
+        }
+    }
+
+    /// Checks the operations of the `nodes` and `sub_graphs` nodes, WGSL has no addition,
+    /// subtraction or division of a matrix by a scalar
+    fn check_wgsl_operations(
+        &self,
+        nodes: &NodeGeneration,
+        sub_graphs: &SubGraphGeneration,
+    ) -> Result<(), ShadyError> {
+        for (graph, node) in nodes
+            .nodes(self)
+            .map(|node| (self, node))
+            .chain(sub_graphs.graph_nodes(self))
+        {
+            let (native_type, operator) = match node.native_operation() {
+                Some(NativeOperation::Add(t)) => (t, "+"),
+                Some(NativeOperation::Sub(t)) => (t, "-"),
+                Some(NativeOperation::Div(t)) => (t, "/"),
+                _ => continue,
+            };
+            if !native_type.is_matrix() {
+                continue;
+            }
+            let operand = node
+                .connections()
+                .into_iter()
+                .find(|(field, _)| *field == "b")
+                .map(|(_, connection)| graph.connection_type(connection))
+                .transpose()?;
+            if let Some(operand) = operand.filter(|operand| !operand.is_matrix()) {
+                return Err(ShadyError::UnsupportedNativeOperation {
+                    operation: format!("{} {} {}", native_type, operator, operand),
+                    target: GraphicLibrary::WebGPU.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn generate_wgsl<W: Write>(
+        &self,
+        writer: &mut CodeWriter<W>,
+        functions: &FunctionLibrary,
+    ) -> Result<(), ShadyError> {
+        self.check_identifiers()?;
+        let nodes = self.nodes_generation()?;
+        let sub_graphs = self.sub_graphs_generation(&nodes)?;
+        self.check_wgsl_operations(&nodes, &sub_graphs)?;
+
+        writer.section("Constants")?;
+        self.write_wgsl_constants_declarations(writer)?;
+        writer.empty_line()?;
+        writer.section("Uniforms")?;
+        self.write_wgsl_uniform_declarations(writer)?;
+        writer.empty_line()?;
+        writer.section("Struct Declarations")?;
+        self.write_wgsl_struct_declarations(writer, &nodes, &sub_graphs)?;
+        writer.empty_line()?;
+        writer.section("Function declarations")?;
+        self.write_function_declarations(
+            writer,
+            &nodes,
+            &sub_graphs,
+            functions,
+            GraphicLibrary::WebGPU,
+        )?;
+        writer.section("Output properties")?;
+        self.write_wgsl_output_declaration(writer)?;
+        writer.empty_line()?;
+
+        writer.section("Main Function")?;
+        writer.line(match self.shader_type {
+            ShaderType::Vertex => "@vertex",
+            ShaderType::Fragment => "@fragment",
+        })?;
+        write!(writer, "fn main(")?;
+        let parameters = self.get_wgsl_parameter_declarations()?;
+        if !parameters.is_empty() {
+            writer.empty_line()?;
+            writer.indent();
+            for parameter in parameters {
+                writer.line(parameter)?;
+            }
+            writer.dedent();
+        }
+        let has_output = self.has_wgsl_output();
+        if has_output {
+            writer.line(format_args!(") -> {} {{", WGSL_OUTPUT_STRUCT))?;
+        } else {
+            writer.line(") {")?;
+        }
+        writer.indent();
+        if has_output {
+            writer.line(format_args!("var output: {};", WGSL_OUTPUT_STRUCT))?;
+        }
+        nodes.write_wgsl(self, writer)?;
+        writer.empty_line()?;
+        writer.section("Output properties")?;
+        self.write_wgsl_output_properties(writer)?;
+        writer.empty_line()?;
+        if has_output {
+            writer.line("return output;")?;
+        } else {
+            writer.empty_line()?;
+        }
+        writer.dedent();
+        writer.line("}")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shader::to_glsl::tests::{
+        init_basic_shader, init_example_shader_1, init_looping_shader_1,
+    };
+    use crate::tests::test_library;
+    use crate::{Constant, ConstantValue, InputProperty, NativeType};
+
+    #[test]
+    fn works_with_empty_shader() {
+        let shader = Shader::default();
+        assert_eq!(
+            shader.to_wgsl(&test_library()).unwrap(),
+            formatdoc! {"
+            // Constants
+
+            // Uniforms
+
+            // Struct Declarations
+
+            // Function declarations
+
+            // Output properties
+            struct ShaderOutput {{
+                @builtin(position) gl_Position: vec4<f32>,
+            }};
+
+            // Main Function
+            @vertex
+            fn main() -> ShaderOutput {{
+                var output: ShaderOutput;
+                
+                // Output properties
+                
+                return output;
+            }}
+            "}
+        );
+    }
+
+    #[test]
+    fn works_with_basic_shader() {
+        let mut shader = init_basic_shader();
+        shader
+            .add_input_property(InputProperty {
+                name: "Color".to_string(),
+                reference: "color".to_string(),
+                native_type: NativeType::Vec4,
+                uniform: true,
+                precision: None,
+            })
+            .unwrap();
+        shader
+            .add_constant(Constant {
+                name: "Offset".to_string(),
+                value: ConstantValue::Vec2([1.0, 0.5]),
+            })
+            .unwrap();
+        assert_eq!(
+            shader.to_wgsl(&test_library()).unwrap(),
+            formatdoc! {"
+            // Constants
+            const OFFSET: vec2<f32> = vec2<f32>(1.0, 0.5);
+
+            // Uniforms
+            @group(0) @binding(0) var<uniform> color: vec4<f32>; // Color
+
+            // Struct Declarations
+
+            // Function declarations
+
+            // Output properties
+            struct ShaderOutput {{
+                @builtin(position) gl_Position: vec4<f32>,
+                @location(0) Out_Pos456: vec3<f32>, // Out_Pos
+            }};
+
+            // Main Function
+            @vertex
+            fn main(
+                @location(0) Gl_Pos123: vec3<f32>, // Gl_Position
+            ) -> ShaderOutput {{
+                var output: ShaderOutput;
+                
+                // Output properties
+                output.Out_Pos456 = Gl_Pos123; // Out_Pos
+                
+                return output;
+            }}
+            "}
+        );
+    }
+
+    #[test]
+    fn works_with_complex_shader() {
+        let mut shader = init_example_shader_1();
+        shader.shader_type = ShaderType::Fragment;
+        assert_eq!(
+            shader.to_wgsl(&test_library()).unwrap(),
+            formatdoc! {"
+            // Constants
+
+            // Uniforms
+
+            // Struct Declarations
+
+            // Function declarations
+            // Test function
+            fn test_func(a: f32, b: f32) -> f32 {{
+                // This function does nothing
+                return a;
+            }}
+            // Output properties
+            struct ShaderOutput {{
+                @location(0) o_1: f32, // O_1
+                @location(1) o_2: f32, // O_2
+                @location(2) o_3: f32, // O_3
+            }};
+
+            // Main Function
+            @fragment
+            fn main(
+                @location(0) i: f32, // I
+            ) -> ShaderOutput {{
+                var output: ShaderOutput;
+                let a: f32 = test_func(i, 0.0); // A Node
+                let b: f32 = test_func(i, a); // B Node
+                let c: f32 = test_func(a, b); // C Node
+                let d: f32 = test_func(b, c); // D Node
+                
+                // Output properties
+                output.o_1 = a; // O_1
+                output.o_2 = c; // O_2
+                output.o_3 = d; // O_3
+                
+                return output;
+            }}
+            "}
+        );
+    }
+
+    #[test]
+    fn declares_vertex_position() {
+        let mut shader = init_basic_shader();
+        assert!(matches!(
+            shader.set_vertex_position(Some("Out_Pos456".to_string())),
+            Err(ShadyError::WrongNativeType { .. })
+        ));
+        let mut position = shader.new_output_property(NativeType::Vec4);
+        position.name = "Position".to_string();
+        let id = shader
+            .add_output_property(position)
+            .unwrap()
+            .reference
+            .clone();
+        shader.set_vertex_position(Some(id.clone())).unwrap();
+        let code = shader.to_wgsl(&test_library()).unwrap();
+        assert!(code.contains(&formatdoc! {"
+            struct ShaderOutput {{
+                @builtin(position) {0}: vec4<f32>, // Position
+                @location(0) Out_Pos456: vec3<f32>, // Out_Pos
+            }};",
+            id
+        }));
+        assert!(code.contains(&format!(
+            "output.{} = {}; // Position",
+            id,
+            NativeType::Vec4.default_wgsl_value()
+        )));
+        shader.shader_type = ShaderType::Fragment;
+        let code = shader.to_wgsl(&test_library()).unwrap();
+        assert!(code.contains(&format!("@location(1) {}: vec4<f32>, // Position", id)));
+        shader.remove_output_property(&id);
+        assert_eq!(shader.vertex_position(), None);
+    }
+
+    #[test]
+    fn generates_shadow_offset_lookups() {
+        use crate::node_operation::{NativeFunction, NodeOperation};
+        use crate::{
+            Connection, ConnectionAttempt, ConnectionTo, Node, OutputProperty, SamplerNativeType,
+        };
+
+        let mut shader = Shader::new("Shadow Shader".to_string());
+        shader.shader_type = ShaderType::Fragment;
+        for (reference, native_type, uniform) in [
+            ("shadow", NativeType::Sampler2dShadow, true),
+            ("uv", NativeType::Vec3, false),
+            ("delta", NativeType::IVec2, false),
+        ] {
+            shader
+                .add_input_property(InputProperty {
+                    name: reference.to_string(),
+                    reference: reference.to_string(),
+                    native_type,
+                    uniform,
+                    precision: None,
+                })
+                .unwrap();
+        }
+        shader
+            .add_constant(Constant {
+                name: "Offset".to_string(),
+                value: ConstantValue::IVec2([1, -1]),
+            })
+            .unwrap();
+        shader
+            .add_output_property(OutputProperty {
+                name: "o".to_string(),
+                reference: "o".to_string(),
+                native_type: NativeType::Float,
+                precision: None,
+                connection: None,
+            })
+            .unwrap();
+        shader
+            .create_node(Node::new_with_custom_id(
+                "lookup",
+                "lookup",
+                NodeOperation::NativeFunction(NativeFunction::TextureOffset(
+                    SamplerNativeType::Sampler2dShadow,
+                )),
+            ))
+            .unwrap();
+        let input = |id: &str| Connection::InputProperty { id: id.to_string() };
+        let node_field = |field_name: &str| ConnectionTo::Node {
+            id: "lookup".to_string(),
+            field_name: field_name.to_string(),
+        };
+        for (connection_from, connection_to) in [
+            (input("shadow"), node_field("sampler")),
+            (input("uv"), node_field("coords")),
+            (
+                Connection::Constant {
+                    id: "OFFSET".to_string(),
+                },
+                node_field("offset"),
+            ),
+            (
+                Connection::SingleOutputNode {
+                    id: "lookup".to_string(),
+                },
+                ConnectionTo::OutputProperty {
+                    id: "o".to_string(),
+                },
+            ),
+        ] {
+            shader
+                .connect(ConnectionAttempt {
+                    connection_from,
+                    connection_to,
+                })
+                .unwrap();
+        }
+        let code = shader.to_wgsl(&test_library()).unwrap();
+        for line in [
+            "@group(0) @binding(0) var shadow: texture_depth_2d; // shadow",
+            "@group(0) @binding(1) var shadow_sampler: sampler_comparison; // shadow",
+            "let lookup: f32 = textureSampleCompare(shadow, shadow_sampler, uv.xy, uv.z, OFFSET);",
+        ] {
+            assert!(code.contains(line), "Missing `{}` in:\n{}", line, code);
+        }
+
+        // WGSL texture offsets must be constant expressions
+        shader
+            .connect(ConnectionAttempt {
+                connection_from: input("delta"),
+                connection_to: node_field("offset"),
+            })
+            .unwrap();
+        assert!(matches!(
+            shader.to_wgsl(&test_library()),
+            Err(ShadyError::NonConstantInput { node, field }) if node == "lookup" && field == "offset"
+        ));
+    }
+
+    #[test]
+    fn rejects_scalar_matrix_operands() {
+        use crate::node_operation::{NativeOperation, NodeOperation};
+        use crate::shader::test_helpers::{connect, input_property, node_field, node_output};
+        use crate::{ConnectionTo, MatrixNativeType, Node, OutputProperty};
+
+        let mut shader = Shader::new("Matrix Shader".to_string());
+        shader.shader_type = ShaderType::Fragment;
+        for (reference, native_type) in [
+            ("m", NativeType::Mat3),
+            ("f", NativeType::Float),
+            ("v", NativeType::Vec3),
+        ] {
+            shader
+                .add_input_property(InputProperty {
+                    name: reference.to_string(),
+                    reference: reference.to_string(),
+                    native_type,
+                    uniform: true,
+                    precision: None,
+                })
+                .unwrap();
+        }
+        shader
+            .add_output_property(OutputProperty::new(NativeType::Vec3, "o"))
+            .unwrap();
+        for (id, operation) in [
+            ("sum", NativeOperation::Add(NativeType::Mat3)),
+            (
+                "product",
+                NativeOperation::MatrixVectorMul(MatrixNativeType::Mat3),
+            ),
+        ] {
+            shader
+                .create_node(Node::new_with_custom_id(
+                    id,
+                    id,
+                    NodeOperation::NativeOperation(operation),
+                ))
+                .unwrap();
+        }
+        for (from, to) in [
+            (input_property("m"), node_field("sum", "a")),
+            (input_property("f"), node_field("sum", "b")),
+            (node_output("sum"), node_field("product", "m")),
+            (input_property("v"), node_field("product", "v")),
+            (
+                node_output("product"),
+                ConnectionTo::OutputProperty {
+                    id: "vec3_o".to_string(),
+                },
+            ),
+        ] {
+            connect(&mut shader, from, to);
+        }
+        assert!(matches!(
+            shader.to_wgsl(&test_library()),
+            Err(ShadyError::UnsupportedNativeOperation { operation, .. }) if operation == "mat3 + float"
+        ));
+        connect(&mut shader, input_property("m"), node_field("sum", "b"));
+        let code = shader.to_wgsl(&test_library()).unwrap();
+        assert!(
+            code.contains("let sum: mat3x3<f32> = m + m; // sum Node"),
+            "{}",
+            code
+        );
+    }
+
+    #[test]
+    fn rejects_double_properties() {
+        let mut shader = init_basic_shader();
+        shader
+            .add_input_property(InputProperty {
+                name: "Long".to_string(),
+                reference: "long_value".to_string(),
+                native_type: NativeType::Double,
+                uniform: true,
+                precision: None,
+            })
+            .unwrap();
+        assert!(matches!(
+            shader.to_wgsl(&test_library()),
+            Err(ShadyError::UnsupportedNativeType { .. })
+        ));
+    }
+
+    #[should_panic = "NodeLoopDetected"]
+    #[test]
+    fn fails_with_looping_shader() {
+        let shader = init_looping_shader_1();
+        shader.to_wgsl(&test_library()).unwrap();
+    }
+}
@@ -0,0 +1,571 @@
+use crate::error::ShadyError;
+use crate::node_operation::FunctionLibrary;
+use crate::{Connection, GraphicLibrary, Handle, NativeType, Node, Output, Shader};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::fmt::{Display, Formatter};
+
+/// Severity of a shader `Diagnostic`
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub enum DiagnosticLevel {
+    /// The shader can't be exported or the generated code is broken
+    Error,
+    /// The shader can be exported but probably doesn't behave as expected
+    Warning,
+}
+
+/// Shader element targeted by a `Diagnostic`
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub enum ShaderElement {
+    Node(String),
+    Constant(String),
+    InputProperty(String),
+    OutputProperty(String),
+    /// Element of the graph of a sub-graph
+    SubGraph {
+        /// Sub-graph name
+        name: String,
+        /// Element of the sub-graph graph
+        element: Box<ShaderElement>,
+    },
+}
+
+/// Issue detected by `Shader::validate`
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub enum DiagnosticKind {
+    /// The input field has no connection and uses the default value of its type
+    UnconnectedInput,
+    /// The connection targets a missing element or field
+    DanglingConnection(Connection),
+    /// The connection can't be used as an input
+    InvalidConnection {
+        connection: Connection,
+        reason: String,
+    },
+    /// The connected element type doesn't match the input
+    TypeMismatch {
+        connection: Connection,
+        input_type: NativeType,
+        expected_types: Vec<NativeType>,
+    },
+    /// The constant or input property isn't connected to anything
+    Unused,
+    /// The output property has no connection and uses the default value of its type
+    UnsetOutputProperty,
+    /// The input field doesn't exist for the node operation
+    UnknownField(Connection),
+    /// The custom function of the node is missing from the function library
+    MissingFunction(String),
+    /// The custom function signature of the node can't be parsed or used by a node
+    InvalidFunctionSignature(String),
+    /// The node input or output doesn't match the custom function signature
+    FunctionSignatureMismatch(String),
+    /// The node isn't used by any output property and won't be generated
+    UnreachableNode,
+    /// The saved node output doesn't match its operation and was replaced by `Shader::repair`
+    ReconciledOutput { saved: Output, output: Output },
+}
+
+/// Shader graph diagnostic
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Diagnostic {
+    pub level: DiagnosticLevel,
+    pub element: ShaderElement,
+    /// Targeted input field of a node
+    pub field: Option<String>,
+    pub kind: DiagnosticKind,
+}
+
+impl DiagnosticKind {
+    /// The severity of the issue
+    pub const fn level(&self) -> DiagnosticLevel {
+        match self {
+            Self::DanglingConnection(_)
+            | Self::InvalidConnection { .. }
+            | Self::TypeMismatch { .. }
+            | Self::UnknownField(_)
+            | Self::MissingFunction(_)
+            | Self::InvalidFunctionSignature(_)
+            | Self::FunctionSignatureMismatch(_) => DiagnosticLevel::Error,
+            Self::UnconnectedInput
+            | Self::Unused
+            | Self::UnsetOutputProperty
+            | Self::UnreachableNode
+            | Self::ReconciledOutput { .. } => DiagnosticLevel::Warning,
+        }
+    }
+}
+
+impl Diagnostic {
+    pub const fn new(element: ShaderElement, field: Option<String>, kind: DiagnosticKind) -> Self {
+        Self {
+            level: kind.level(),
+            element,
+            field,
+            kind,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.level == DiagnosticLevel::Error
+    }
+
+    /// The diagnostic of an element of the graph of the sub-graph `name`
+    pub(super) fn in_sub_graph(self, name: &str) -> Self {
+        Self {
+            element: ShaderElement::SubGraph {
+                name: name.to_string(),
+                element: Box::new(self.element),
+            },
+            ..self
+        }
+    }
+}
+
+impl Shader {
+    /// Checks the shader graph, returning every detected issue. Custom functions are looked up
+    /// in `functions`.
+    ///
+    /// Diagnostics are sorted by element: nodes, output properties, constants, input properties and
+    /// sub-graphs, whose graphs are checked the same way
+    pub fn validate(&self, functions: &FunctionLibrary) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let mut used_connections = HashSet::new();
+
+        let mut nodes: Vec<_> = self.nodes.entries().collect();
+        nodes.sort_by_key(|(_, k, _)| *k);
+        let reachable = self.reachable_nodes();
+        for (handle, id, node) in nodes {
+            let element = ShaderElement::Node(id.clone());
+            for (field_name, field) in node.input_fields() {
+                let kind = field.connection.as_ref().map_or(
+                    Some(DiagnosticKind::UnconnectedInput),
+                    |connection| {
+                        used_connections.insert(connection.clone());
+                        self.check_connection(connection, &field.accepted_types())
+                    },
+                );
+                if let Some(kind) = kind {
+                    diagnostics.push(Diagnostic::new(element.clone(), Some(field_name), kind));
+                }
+            }
+            if let Some(function_name) = node.custom_function_name() {
+                if matches!(functions.contains(function_name, self.library), Ok(false)) {
+                    diagnostics.push(Diagnostic::new(
+                        element.clone(),
+                        None,
+                        DiagnosticKind::MissingFunction(function_name.clone()),
+                    ));
+                }
+            }
+            if let Some(kind) = self.check_function_signature(node, functions) {
+                diagnostics.push(Diagnostic::new(element.clone(), None, kind));
+            }
+            if !reachable.contains(&handle) {
+                diagnostics.push(Diagnostic::new(
+                    element,
+                    None,
+                    DiagnosticKind::UnreachableNode,
+                ));
+            }
+        }
+
+        let mut output: Vec<_> = self.output_properties.iter().collect();
+        output.sort_by_key(|(k, _)| *k);
+        for (id, property) in output {
+            let kind = property.connection.as_ref().map_or(
+                Some(DiagnosticKind::UnsetOutputProperty),
+                |connection| {
+                    used_connections.insert(connection.clone());
+                    self.check_connection(connection, &[property.native_type])
+                },
+            );
+            if let Some(kind) = kind {
+                diagnostics.push(Diagnostic::new(
+                    ShaderElement::OutputProperty(id.clone()),
+                    None,
+                    kind,
+                ));
+            }
+        }
+
+        let mut constants: Vec<_> = self.constants.keys().collect();
+        constants.sort();
+        for id in constants {
+            if !used_connections.contains(&Connection::Constant { id: id.clone() }) {
+                diagnostics.push(Diagnostic::new(
+                    ShaderElement::Constant(id.clone()),
+                    None,
+                    DiagnosticKind::Unused,
+                ));
+            }
+        }
+        let mut input: Vec<_> = self.input_properties.keys().collect();
+        input.sort();
+        for id in input {
+            if !used_connections.contains(&Connection::InputProperty { id: id.clone() }) {
+                diagnostics.push(Diagnostic::new(
+                    ShaderElement::InputProperty(id.clone()),
+                    None,
+                    DiagnosticKind::Unused,
+                ));
+            }
+        }
+        let mut sub_graphs: Vec<_> = self.sub_graphs.iter().collect();
+        sub_graphs.sort_by_key(|(k, _)| *k);
+        for (name, sub_graph) in sub_graphs {
+            diagnostics.extend(
+                sub_graph
+                    .graph()
+                    .validate(functions)
+                    .into_iter()
+                    .map(|d| d.in_sub_graph(name)),
+            );
+        }
+        diagnostics
+    }
+
+    /// Checks the element `connection` comes from exists and has one of the `expected_types`
+    pub(super) fn check_connection(
+        &self,
+        connection: &Connection,
+        expected_types: &[NativeType],
+    ) -> Option<DiagnosticKind> {
+        match self.connection_type(connection) {
+            Ok(input_type) if expected_types.contains(&input_type) => None,
+            Ok(input_type) => Some(DiagnosticKind::TypeMismatch {
+                connection: connection.clone(),
+                input_type,
+                expected_types: expected_types.to_vec(),
+            }),
+            Err(
+                ShadyError::MissingNode(_)
+                | ShadyError::MissingConstant(_)
+                | ShadyError::MissingInputProperty(_)
+                | ShadyError::WrongFieldKey(_),
+            ) => Some(DiagnosticKind::DanglingConnection(connection.clone())),
+            Err(e) => Some(DiagnosticKind::InvalidConnection {
+                connection: connection.clone(),
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    /// Checks the input and output of a custom function `node` against the signature of its GLSL
+    /// code in `functions`. Missing functions are reported separately
+    fn check_function_signature(
+        &self,
+        node: &Node,
+        functions: &FunctionLibrary,
+    ) -> Option<DiagnosticKind> {
+        let function_name = node.custom_function_name()?;
+        if self.library == GraphicLibrary::WebGPU {
+            return None;
+        }
+        let signature = match functions.signature(function_name) {
+            Ok(signature) => signature,
+            Err(ShadyError::MissingFunctionSource { .. }) => return None,
+            Err(e) => return Some(DiagnosticKind::InvalidFunctionSignature(e.to_string())),
+        };
+        let expected = match signature.node_operation() {
+            Ok(operation) => Node::new_with_custom_id(node.name(), node.unique_id(), operation),
+            Err(e) => return Some(DiagnosticKind::InvalidFunctionSignature(e.to_string())),
+        };
+        let input_types = |node: &Node| -> Vec<NativeType> {
+            node.input_fields()
+                .into_iter()
+                .map(|(_, f)| f.glsl_type())
+                .collect()
+        };
+        if input_types(node) == input_types(&expected)
+            && node.output().glsl_type() == expected.output().glsl_type()
+        {
+            None
+        } else {
+            Some(DiagnosticKind::FunctionSignatureMismatch(
+                signature.to_string(),
+            ))
+        }
+    }
+
+    /// Handles of the nodes used, directly or not, by an output property
+    fn reachable_nodes(&self) -> HashSet<Handle<Node>> {
+        let graph = self.dependency_graph();
+        let mut reachable = HashSet::new();
+        let mut queue: VecDeque<Handle<Node>> = self
+            .output_node_ids()
+            .into_iter()
+            .filter_map(|id| self.nodes.handle(id))
+            .collect();
+        while let Some(handle) = queue.pop_front() {
+            if reachable.insert(handle) {
+                queue.extend(graph.dependencies(handle));
+            }
+        }
+        reachable
+    }
+}
+
+impl Display for ShaderElement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Node(id) => write!(f, "Node `{}`", id),
+            Self::Constant(id) => write!(f, "Constant `{}`", id),
+            Self::InputProperty(id) => write!(f, "Input property `{}`", id),
+            Self::OutputProperty(id) => write!(f, "Output property `{}`", id),
+            Self::SubGraph { name, element } => write!(f, "Sub-graph `{}` {}", name, element),
+        }
+    }
+}
+
+impl Display for DiagnosticKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnconnectedInput | Self::UnsetOutputProperty => {
+                write!(f, "No connection set, using default value")
+            }
+            Self::DanglingConnection(connection) => {
+                write!(f, "Connection to missing element {:?}", connection)
+            }
+            Self::InvalidConnection { connection, reason } => {
+                write!(f, "Invalid connection {:?}: {}", connection, reason)
+            }
+            Self::TypeMismatch {
+                connection,
+                input_type,
+                expected_types,
+            } => write!(
+                f,
+                "Connection {:?} has type {}, expected one of {:?}",
+                connection, input_type, expected_types
+            ),
+            Self::Unused => write!(f, "Not connected to any node or output property"),
+            Self::UnknownField(connection) => {
+                write!(f, "Unknown field for connection {:?}", connection)
+            }
+            Self::MissingFunction(name) => {
+                write!(f, "Function `{}` not found in the function library", name)
+            }
+            Self::InvalidFunctionSignature(reason) => write!(f, "{}", reason),
+            Self::FunctionSignatureMismatch(signature) => {
+                write!(f, "Input or output doesn't match function `{}`", signature)
+            }
+            Self::UnreachableNode => write!(f, "Not used by any output property"),
+            Self::ReconciledOutput { saved, output } => write!(
+                f,
+                "Saved output {} replaced by the operation output {}",
+                saved.glsl_type(),
+                output.glsl_type()
+            ),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let level = match self.level {
+            DiagnosticLevel::Error => "error",
+            DiagnosticLevel::Warning => "warning",
+        };
+        match &self.field {
+            None => write!(f, "[{}] {}: {}", level, self.element, self.kind),
+            Some(field) => write!(
+                f,
+                "[{}] {} field `{}`: {}",
+                level, self.element, field, self.kind
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_operation::NodeOperation;
+    use crate::shader::to_glsl::tests::{init_basic_shader, init_example_shader_1};
+    use crate::tests::test_library;
+    use crate::{ConnectionTo, Constant, ConstantValue, Input, Node, NonScalarNativeType};
+
+    #[test]
+    fn valid_shader_has_no_diagnostics() {
+        let shader = init_basic_shader();
+        assert!(shader.validate(&test_library()).is_empty());
+    }
+
+    #[test]
+    fn detects_unconnected_and_unused_elements() {
+        let mut shader = init_basic_shader();
+        shader
+            .add_constant(Constant {
+                name: "Unused".to_string(),
+                value: ConstantValue::Float(1.0),
+            })
+            .unwrap();
+        let id = shader
+            .create_node(Node::new_with_custom_id(
+                "Lonely",
+                "lonely",
+                NodeOperation::TypeConstruction(NonScalarNativeType::Vec2),
+            ))
+            .unwrap()
+            .unique_id()
+            .clone();
+        shader
+            .disconnect(ConnectionTo::OutputProperty {
+                id: "Out_Pos456".to_string(),
+            })
+            .unwrap();
+        let diagnostics = shader.validate(&test_library());
+        let node = ShaderElement::Node(id);
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic::new(
+                    node.clone(),
+                    Some("x".to_string()),
+                    DiagnosticKind::UnconnectedInput
+                ),
+                Diagnostic::new(
+                    node.clone(),
+                    Some("y".to_string()),
+                    DiagnosticKind::UnconnectedInput
+                ),
+                Diagnostic::new(node, None, DiagnosticKind::UnreachableNode),
+                Diagnostic::new(
+                    ShaderElement::OutputProperty("Out_Pos456".to_string()),
+                    None,
+                    DiagnosticKind::UnsetOutputProperty
+                ),
+                Diagnostic::new(
+                    ShaderElement::Constant("UNUSED".to_string()),
+                    None,
+                    DiagnosticKind::Unused
+                ),
+                Diagnostic::new(
+                    ShaderElement::InputProperty("Gl_Pos123".to_string()),
+                    None,
+                    DiagnosticKind::Unused
+                ),
+            ]
+        );
+        assert!(diagnostics.iter().all(|d| !d.is_error()));
+    }
+
+    #[test]
+    fn detects_dangling_connections_and_type_mismatch() {
+        let mut shader = init_example_shader_1();
+        // Simulates a broken save file
+        shader.nodes.remove_by_name("a");
+        shader
+            .input_properties
+            .get_mut_by_name("i")
+            .unwrap()
+            .native_type = NativeType::Vec2;
+        let errors: Vec<_> = shader
+            .validate(&test_library())
+            .into_iter()
+            .filter(Diagnostic::is_error)
+            .collect();
+        let a = Connection::SingleOutputNode {
+            id: "a".to_string(),
+        };
+        assert_eq!(
+            errors,
+            vec![
+                Diagnostic::new(
+                    ShaderElement::Node("b".to_string()),
+                    Some("x".to_string()),
+                    DiagnosticKind::TypeMismatch {
+                        connection: Connection::InputProperty {
+                            id: "i".to_string()
+                        },
+                        input_type: NativeType::Vec2,
+                        expected_types: vec![NativeType::Float],
+                    }
+                ),
+                Diagnostic::new(
+                    ShaderElement::Node("b".to_string()),
+                    Some("y".to_string()),
+                    DiagnosticKind::DanglingConnection(a.clone())
+                ),
+                Diagnostic::new(
+                    ShaderElement::Node("c".to_string()),
+                    Some("x".to_string()),
+                    DiagnosticKind::DanglingConnection(a.clone())
+                ),
+                Diagnostic::new(
+                    ShaderElement::OutputProperty("o_1".to_string()),
+                    None,
+                    DiagnosticKind::DanglingConnection(a)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn detects_missing_function_file() {
+        let mut shader = init_basic_shader();
+        shader
+            .create_node(Node::new_with_custom_id(
+                "Missing",
+                "missing",
+                NodeOperation::CustomOperation {
+                    function_name: "missing_func".to_string(),
+                    input: Input::none(),
+                    output: Output::NativeType(NativeType::Float),
+                },
+            ))
+            .unwrap();
+        let errors: Vec<_> = shader
+            .validate(&test_library())
+            .into_iter()
+            .filter(Diagnostic::is_error)
+            .collect();
+        assert_eq!(
+            errors,
+            vec![Diagnostic::new(
+                ShaderElement::Node("missing".to_string()),
+                None,
+                DiagnosticKind::MissingFunction("missing_func".to_string())
+            )]
+        );
+    }
+
+    #[test]
+    fn detects_function_signature_mismatch() {
+        let mut shader = init_basic_shader();
+        shader
+            .create_node(Node::new_with_custom_id(
+                "Matching",
+                "matching",
+                NodeOperation::custom_operation("test_func", &test_library()).unwrap(),
+            ))
+            .unwrap();
+        shader
+            .create_node(Node::new_with_custom_id(
+                "Mismatch",
+                "mismatch",
+                NodeOperation::CustomOperation {
+                    function_name: "test_func".to_string(),
+                    input: Input::none(),
+                    output: Output::NativeType(NativeType::Vec2),
+                },
+            ))
+            .unwrap();
+        let errors: Vec<_> = shader
+            .validate(&test_library())
+            .into_iter()
+            .filter(Diagnostic::is_error)
+            .collect();
+        assert_eq!(
+            errors,
+            vec![Diagnostic::new(
+                ShaderElement::Node("mismatch".to_string()),
+                None,
+                DiagnosticKind::FunctionSignatureMismatch(
+                    "float test_func(in float a, in float b)".to_string()
+                )
+            )]
+        );
+    }
+}
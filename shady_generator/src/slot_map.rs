@@ -0,0 +1,391 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::{Index, IndexMut};
+
+/// Copyable typed key of an element stored in a `SlotMap<T>`.
+///
+/// A handle is invalidated by the removal of its element, and never matches an element inserted
+/// afterwards in the same slot
+pub struct Handle<T> {
+    index: u32,
+    generation: u32,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    const fn new(index: u32, generation: u32) -> Self {
+        Self {
+            index,
+            generation,
+            marker: PhantomData,
+        }
+    }
+
+    /// Slot index of the handle, lower than the `SlotMap::capacity` and suited to index dense
+    /// side tables
+    pub const fn index(&self) -> usize {
+        self.index as usize
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> PartialOrd for Handle<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Handle<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.index, self.generation).cmp(&(other.index, other.generation))
+    }
+}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T> Debug for Handle<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Handle({}v{})", self.index, self.generation)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Slot<T> {
+    generation: u32,
+    entry: Option<(String, T)>,
+}
+
+/// Elements stored in reusable slots, keyed by copyable typed `Handle<T>` and by unique names.
+///
+/// Names are the identifiers of the elements in the saves, the generated code and the
+/// connections, which are resolved to handles through `SlotMap::handle`. Handle access doesn't
+/// hash or allocate. Iteration follows the slot order, and the map is serialized as a name
+/// ordered map
+#[derive(Debug, Clone)]
+pub struct SlotMap<T> {
+    slots: Vec<Slot<T>>,
+    free_slots: Vec<u32>,
+    names: HashMap<String, Handle<T>>,
+}
+
+impl<T> Default for SlotMap<T> {
+    fn default() -> Self {
+        Self {
+            slots: Vec::new(),
+            free_slots: Vec::new(),
+            names: HashMap::new(),
+        }
+    }
+}
+
+impl<T> SlotMap<T> {
+    /// Number of stored elements
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Number of slots, every handle `index` being lower
+    pub const fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Inserts `value` with the unique `name`, replacing and returning the element previously
+    /// stored with that name, whose handle stays valid
+    pub fn insert(&mut self, name: String, value: T) -> (Handle<T>, Option<T>) {
+        if let Some(handle) = self.handle(&name) {
+            let previous = self.get_mut(handle).map(|v| std::mem::replace(v, value));
+            return (handle, previous);
+        }
+        let handle = if let Some(index) = self.free_slots.pop() {
+            let slot = &mut self.slots[index as usize];
+            slot.entry = Some((name.clone(), value));
+            Handle::new(index, slot.generation)
+        } else {
+            let index = u32::try_from(self.slots.len()).expect("Slot map capacity overflow");
+            self.slots.push(Slot {
+                generation: 0,
+                entry: Some((name.clone(), value)),
+            });
+            Handle::new(index, 0)
+        };
+        self.names.insert(name, handle);
+        (handle, None)
+    }
+
+    /// Removes the element of `handle`, invalidating the handle
+    pub fn remove(&mut self, handle: Handle<T>) -> Option<(String, T)> {
+        let slot = self.slots.get_mut(handle.index())?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        let (name, value) = slot.entry.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free_slots.push(handle.index);
+        self.names.remove(&name);
+        Some((name, value))
+    }
+
+    /// Removes the element named `name`
+    pub fn remove_by_name(&mut self, name: &str) -> Option<T> {
+        let handle = self.handle(name)?;
+        self.remove(handle).map(|(_, value)| value)
+    }
+
+    /// Handle of the element named `name`
+    pub fn handle(&self, name: &str) -> Option<Handle<T>> {
+        self.names.get(name).copied()
+    }
+
+    pub fn contains(&self, handle: Handle<T>) -> bool {
+        self.entry(handle).is_some()
+    }
+
+    pub fn contains_name(&self, name: &str) -> bool {
+        self.names.contains_key(name)
+    }
+
+    fn entry(&self, handle: Handle<T>) -> Option<&(String, T)> {
+        self.slots
+            .get(handle.index())
+            .filter(|slot| slot.generation == handle.generation)?
+            .entry
+            .as_ref()
+    }
+
+    pub fn get(&self, handle: Handle<T>) -> Option<&T> {
+        self.entry(handle).map(|(_, value)| value)
+    }
+
+    pub fn get_mut(&mut self, handle: Handle<T>) -> Option<&mut T> {
+        self.slots
+            .get_mut(handle.index())
+            .filter(|slot| slot.generation == handle.generation)?
+            .entry
+            .as_mut()
+            .map(|(_, value)| value)
+    }
+
+    /// Name of the element of `handle`
+    pub fn name(&self, handle: Handle<T>) -> Option<&String> {
+        self.entry(handle).map(|(name, _)| name)
+    }
+
+    pub fn get_by_name(&self, name: &str) -> Option<&T> {
+        self.get(self.handle(name)?)
+    }
+
+    pub fn get_mut_by_name(&mut self, name: &str) -> Option<&mut T> {
+        self.get_mut(self.handle(name)?)
+    }
+
+    /// Iterates the handles, names and values of the elements in slot order
+    pub fn entries(&self) -> impl Iterator<Item = (Handle<T>, &String, &T)> {
+        self.slots.iter().zip(0..).filter_map(|(slot, index)| {
+            slot.entry
+                .as_ref()
+                .map(|(name, value)| (Handle::new(index, slot.generation), name, value))
+        })
+    }
+
+    pub fn handles(&self) -> impl Iterator<Item = Handle<T>> + '_ {
+        self.entries().map(|(handle, _, _)| handle)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter(self.slots.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut(self.slots.iter_mut())
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.iter().map(|(name, _)| name)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.iter().map(|(_, value)| value)
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.iter_mut().map(|(_, value)| value)
+    }
+}
+
+impl<T> Index<Handle<T>> for SlotMap<T> {
+    type Output = T;
+
+    fn index(&self, handle: Handle<T>) -> &Self::Output {
+        self.get(handle).expect("Invalid slot map handle")
+    }
+}
+
+impl<T> IndexMut<Handle<T>> for SlotMap<T> {
+    fn index_mut(&mut self, handle: Handle<T>) -> &mut Self::Output {
+        self.get_mut(handle).expect("Invalid slot map handle")
+    }
+}
+
+impl<T> FromIterator<(String, T)> for SlotMap<T> {
+    fn from_iter<I: IntoIterator<Item = (String, T)>>(iter: I) -> Self {
+        let mut res = Self::default();
+        for (name, value) in iter {
+            res.insert(name, value);
+        }
+        res
+    }
+}
+
+/// Iterator over the names and values of a `SlotMap`, in slot order
+pub struct Iter<'a, T>(std::slice::Iter<'a, Slot<T>>);
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (&'a String, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0
+            .find_map(|slot| slot.entry.as_ref().map(|(name, value)| (name, value)))
+    }
+}
+
+/// Iterator over the names and mutable values of a `SlotMap`, in slot order
+pub struct IterMut<'a, T>(std::slice::IterMut<'a, Slot<T>>);
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = (&'a String, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0
+            .find_map(|slot| slot.entry.as_mut().map(|(name, value)| (&*name, value)))
+    }
+}
+
+/// Owning iterator over the names and values of a `SlotMap`, in slot order
+pub struct IntoIter<T>(std::vec::IntoIter<Slot<T>>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = (String, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.find_map(|slot| slot.entry)
+    }
+}
+
+impl<T> IntoIterator for SlotMap<T> {
+    type Item = (String, T);
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.slots.into_iter())
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SlotMap<T> {
+    type Item = (&'a String, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut SlotMap<T> {
+    type Item = (&'a String, &'a mut T);
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T: Serialize> Serialize for SlotMap<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let ordered: BTreeMap<_, _> = self.iter().collect();
+        ordered.serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for SlotMap<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let ordered = BTreeMap::<String, T>::deserialize(deserializer)?;
+        Ok(ordered.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handles_are_invalidated() {
+        let mut map = SlotMap::default();
+        let (a, _) = map.insert("a".to_string(), 1);
+        let (b, _) = map.insert("b".to_string(), 2);
+        assert_eq!(map[a], 1);
+        assert_eq!(map.remove(a), Some(("a".to_string(), 1)));
+        assert!(!map.contains(a));
+        assert_eq!(map.get(a), None);
+        let (c, _) = map.insert("c".to_string(), 3);
+        assert_eq!(c.index(), a.index());
+        assert_ne!(c, a);
+        assert_eq!(map.get(a), None);
+        assert_eq!(map[c], 3);
+        assert_eq!(map.handle("b"), Some(b));
+        assert_eq!(map.name(c), Some(&"c".to_string()));
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.capacity(), 2);
+    }
+
+    #[test]
+    fn names_are_unique() {
+        let mut map = SlotMap::default();
+        let (a, _) = map.insert("a".to_string(), 1);
+        let (same, previous) = map.insert("a".to_string(), 2);
+        assert_eq!(a, same);
+        assert_eq!(previous, Some(1));
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get_by_name("a"), Some(&2));
+        assert_eq!(map.remove_by_name("a"), Some(2));
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn serializes_as_ordered_map() {
+        let map: SlotMap<u32> = [("b".to_string(), 2), ("a".to_string(), 1)]
+            .into_iter()
+            .collect();
+        let data = serde_yaml::to_string(&map).unwrap();
+        assert!(data.find("a:").unwrap() < data.find("b:").unwrap());
+        let loaded: SlotMap<u32> = serde_yaml::from_str(&data).unwrap();
+        assert_eq!(loaded.get_by_name("a"), Some(&1));
+        assert_eq!(loaded.get_by_name("b"), Some(&2));
+    }
+}
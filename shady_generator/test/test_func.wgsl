@@ -0,0 +1,5 @@
+// Test function
+fn test_func(a: f32, b: f32) -> f32 {
+    // This function does nothing
+    return a;
+}
\ No newline at end of file
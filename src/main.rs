@@ -33,6 +33,7 @@ use bevy_egui::EguiPlugin;
 #[cfg(feature = "debug")]
 use bevy_inspector_egui::WorldInspectorPlugin;
 use bevy_prototype_lyon::prelude::ShapePlugin;
+use shady_generator::node_operation::FunctionLibrary;
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -89,6 +90,9 @@ fn main() {
         .add_event::<ShaderEvent>()
         .add_event::<IOEvent>()
         .insert_resource(CurrentShader::default())
+        .insert_resource(FunctionLibrary::new().with_search_path(
+            std::env::var("CUSTOM_FUNCTIONS_PATH").unwrap_or_else(|_| "functions".to_string()),
+        ))
         .insert_resource(UiState::default())
         .insert_resource(PreviewMaterial::default());
     // Debug hierarchy inspector
@@ -61,8 +61,33 @@ impl ShadyAssets {
             NativeType::IVec3 => Color::GOLD,
             NativeType::Vec4 => Color::ORANGE,
             NativeType::IVec4 => Color::ORANGE_RED,
+            NativeType::BVec2 => Color::AZURE,
+            NativeType::BVec3 => Color::ALICE_BLUE,
+            NativeType::BVec4 => Color::SILVER,
+            NativeType::UVec2 => Color::SEA_GREEN,
+            NativeType::UVec3 => Color::OLIVE,
+            NativeType::UVec4 => Color::MAROON,
+            NativeType::DVec2 => Color::NAVY,
+            NativeType::DVec3 => Color::BEIGE,
+            NativeType::DVec4 => Color::SALMON,
+            NativeType::Mat2 => Color::TEAL,
+            NativeType::Mat3 => Color::TURQUOISE,
+            NativeType::Mat4 => Color::AQUAMARINE,
             NativeType::Sampler2d => Color::PURPLE,
             NativeType::SamplerCube => Color::PINK,
+            NativeType::Sampler3d => Color::VIOLET,
+            NativeType::Sampler2dArray => Color::INDIGO,
+            NativeType::Sampler2dShadow => Color::FUCHSIA,
+            NativeType::SamplerCubeShadow => Color::BISQUE,
+            NativeType::Sampler2dArrayShadow => Color::ANTIQUE_WHITE,
+            NativeType::ISampler2d => Color::CRIMSON,
+            NativeType::ISampler3d => Color::RED,
+            NativeType::ISamplerCube => Color::TOMATO,
+            NativeType::ISampler2dArray => Color::DARK_GRAY,
+            NativeType::USampler2d => Color::rgb(0.6, 0.4, 0.2),
+            NativeType::USampler3d => Color::rgb(0.8, 0.6, 0.4),
+            NativeType::USamplerCube => Color::rgb(0.4, 0.2, 0.6),
+            NativeType::USampler2dArray => Color::rgb(0.6, 0.8, 0.4),
         }
     }
 }
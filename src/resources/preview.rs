@@ -69,8 +69,50 @@ impl PreviewValue {
                 Self::BuiltinValue(BuiltinValue::Color([1., 1., 1., 1.])),
             ],
             NativeType::IVec4 => vec![Self::ConstantValue(ConstantValue::IVec4([1, 1, 1, 1]))],
+            NativeType::BVec2 => vec![Self::ConstantValue(ConstantValue::BVec2([true, true]))],
+            NativeType::BVec3 => vec![Self::ConstantValue(ConstantValue::BVec3([
+                true, true, true,
+            ]))],
+            NativeType::BVec4 => vec![Self::ConstantValue(ConstantValue::BVec4([
+                true, true, true, true,
+            ]))],
+            NativeType::UVec2 => vec![Self::ConstantValue(ConstantValue::UVec2([1, 1]))],
+            NativeType::UVec3 => vec![Self::ConstantValue(ConstantValue::UVec3([1, 1, 1]))],
+            NativeType::UVec4 => vec![Self::ConstantValue(ConstantValue::UVec4([1, 1, 1, 1]))],
+            NativeType::DVec2 => vec![Self::ConstantValue(ConstantValue::DVec2([1., 1.]))],
+            NativeType::DVec3 => vec![Self::ConstantValue(ConstantValue::DVec3([1., 1., 1.]))],
+            NativeType::DVec4 => vec![Self::ConstantValue(ConstantValue::DVec4([1., 1., 1., 1.]))],
+            NativeType::Mat2 => vec![Self::ConstantValue(ConstantValue::Mat2([
+                [1., 0.],
+                [0., 1.],
+            ]))],
+            NativeType::Mat3 => vec![Self::ConstantValue(ConstantValue::Mat3([
+                [1., 0., 0.],
+                [0., 1., 0.],
+                [0., 0., 1.],
+            ]))],
+            NativeType::Mat4 => vec![Self::ConstantValue(ConstantValue::Mat4([
+                [1., 0., 0., 0.],
+                [0., 1., 0., 0.],
+                [0., 0., 1., 0.],
+                [0., 0., 0., 1.],
+            ]))],
             // TODO: Add handle for textures
-            NativeType::Sampler2d | NativeType::SamplerCube => vec![],
+            NativeType::Sampler2d
+            | NativeType::SamplerCube
+            | NativeType::Sampler3d
+            | NativeType::Sampler2dArray
+            | NativeType::Sampler2dShadow
+            | NativeType::SamplerCubeShadow
+            | NativeType::Sampler2dArrayShadow
+            | NativeType::ISampler2d
+            | NativeType::ISampler3d
+            | NativeType::ISamplerCube
+            | NativeType::ISampler2dArray
+            | NativeType::USampler2d
+            | NativeType::USampler3d
+            | NativeType::USamplerCube
+            | NativeType::USampler2dArray => vec![],
         }
     }
 
@@ -89,6 +131,18 @@ impl PreviewValue {
                 ConstantValue::IVec3(_) => "IVec3",
                 ConstantValue::Vec4(_) => "Vec4",
                 ConstantValue::IVec4(_) => "IVec4",
+                ConstantValue::BVec2(_) => "BVec2",
+                ConstantValue::BVec3(_) => "BVec3",
+                ConstantValue::BVec4(_) => "BVec4",
+                ConstantValue::UVec2(_) => "UVec2",
+                ConstantValue::UVec3(_) => "UVec3",
+                ConstantValue::UVec4(_) => "UVec4",
+                ConstantValue::DVec2(_) => "DVec2",
+                ConstantValue::DVec3(_) => "DVec3",
+                ConstantValue::DVec4(_) => "DVec4",
+                ConstantValue::Mat2(_) => "Mat2",
+                ConstantValue::Mat3(_) => "Mat3",
+                ConstantValue::Mat4(_) => "Mat4",
             },
             PreviewValue::BuiltinValue(b) => match b {
                 BuiltinValue::VertexPosition => "Vertex position",
@@ -5,7 +5,7 @@ use crate::PreviewMaterial;
 use bevy::log;
 use bevy::prelude::{Commands, DespawnRecursiveExt, Entity, Vec2};
 use bevy::utils::HashMap;
-use shady_generator::{Connection, ConnectionTo, Shader};
+use shady_generator::{Connection, ConnectionTo, SeveredConnections, Shader};
 use std::ops::{Deref, DerefMut};
 
 #[derive(Debug, Default)]
@@ -84,6 +84,16 @@ impl CurrentShader {
         }
     }
 
+    pub fn delete_connection_entities(
+        &mut self,
+        severed: &SeveredConnections,
+        commands: &mut Commands,
+    ) {
+        for (to, from) in severed {
+            self.delete_connection_entity(to, from, commands);
+        }
+    }
+
     pub fn unique_connector_id(to: &ConnectionTo, from: &Connection) -> String {
         format!(
             "{}_{}",
@@ -3,9 +3,7 @@ use crate::IOEvent;
 use shady_generator::node_operation::{
     NativeFunction, NativeOperation, NodeOperation, NonScalarSwizzle,
 };
-use shady_generator::{
-    Constant, ConstantValue, InputProperty, NativeType, NonScalarNativeType, OutputProperty,
-};
+use shady_generator::{Constant, ConstantValue, NativeType, NonScalarNativeType, Shader};
 use std::path::PathBuf;
 
 #[derive(Debug, Clone)]
@@ -68,16 +66,18 @@ impl OperationSelection {
 }
 
 impl TypeSelection {
-    pub fn creation_candidate(&self) -> CreationCandidate {
+    pub fn creation_candidate(&self, shader: &mut Shader) -> CreationCandidate {
         match self {
             Self::Constant(v) => CreationCandidate::Constant(Constant {
                 name: format!("My {}", v.native_type()),
                 value: *v,
             }),
             Self::InputProperty(t) => {
-                CreationCandidate::InputProperty(InputProperty::new(*t, false))
+                CreationCandidate::InputProperty(shader.new_input_property(*t, false))
+            }
+            Self::OutputProperty(t) => {
+                CreationCandidate::OutputProperty(shader.new_output_property(*t))
             }
-            Self::OutputProperty(t) => CreationCandidate::OutputProperty(OutputProperty::new(*t)),
             Self::TypeConstruction(t) => CreationCandidate::Node {
                 name: t.to_string(),
                 operation: NodeOperation::TypeConstruction(*t),
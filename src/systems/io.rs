@@ -6,7 +6,8 @@ use bevy::prelude::*;
 use bevy::tasks::{ComputeTaskPool, Task};
 use futures_lite::future;
 use rfd::{AsyncFileDialog, FileHandle};
-use shady_generator::Shader;
+use shady_generator::node_operation::FunctionLibrary;
+use shady_generator::{LoadMode, SaveFormat, Shader};
 
 pub fn handle_io_events(
     mut commands: Commands,
@@ -15,6 +16,7 @@ pub fn handle_io_events(
     mut preview: ResMut<PreviewMaterial>,
     camera_translation: Res<CameraTranslation>,
     assets: Res<ShadyAssets>,
+    functions: Res<FunctionLibrary>,
 ) {
     for event in io_evr.iter() {
         match event {
@@ -31,8 +33,17 @@ pub fn handle_io_events(
                 };
             }
             IOEvent::Load(path) => {
-                let new_shader = match Shader::load(path) {
-                    Ok(s) => s,
+                let new_shader = match Shader::load_with_mode(path, LoadMode::Lenient) {
+                    Ok((s, report)) => {
+                        for diagnostic in report {
+                            LogElement::new(
+                                LogLevel::Warn,
+                                format!("Dropped broken connection: {}", diagnostic),
+                            )
+                            .spawn(&mut commands);
+                        }
+                        s
+                    }
                     Err(e) => {
                         LogElement::new(LogLevel::Error, format!("Failed to load shader: {}", e))
                             .spawn(&mut commands);
@@ -48,7 +59,7 @@ pub fn handle_io_events(
                 );
             }
             IOEvent::Export(path) => {
-                match shader.export_glsl_to(&path) {
+                match shader.export_to(&path, &functions) {
                     Ok(()) => {
                         LogElement::new(LogLevel::Info, format!("Exported shader to {:?}", path))
                             .spawn(&mut commands);
@@ -90,7 +101,7 @@ pub fn handle_io_state(
                 IOState::Loading => {
                     AsyncFileDialog::new()
                         .set_directory(get_current_dir())
-                        .add_filter("Save File", &["yaml"])
+                        .add_filter("Save File", SaveFormat::EXTENSIONS)
                         .pick_file()
                         .await
                 }
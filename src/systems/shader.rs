@@ -5,7 +5,7 @@ use crate::systems::spawner::{spawn_element, SpawnType};
 use crate::CurrentShader;
 use bevy::log;
 use bevy::prelude::*;
-use shady_generator::{Connection, ConnectionTo, Node};
+use shady_generator::{Connection, ConnectionTo};
 
 #[allow(clippy::too_many_lines)]
 pub fn handle_shader_event(
@@ -22,8 +22,8 @@ pub fn handle_shader_event(
                 candidate,
             } => match candidate {
                 CreationCandidate::Node { name, operation } => {
-                    let node = match current_shader.create_node(Node::new(name, operation.clone()))
-                    {
+                    let node = current_shader.new_node(name, operation.clone());
+                    let node = match current_shader.create_node(node) {
                         Ok(e) => e,
                         Err(err) => {
                             LogElement::new(LogLevel::Error, err.to_string()).spawn(&mut commands);
@@ -118,36 +118,51 @@ pub fn handle_shader_event(
             ShaderEvent::DeleteNode { id } => {
                 LogElement::new(LogLevel::Info, format!("Deleting node {}", id))
                     .spawn(&mut commands);
-                if current_shader.remove_node(id).is_none() {
-                    LogElement::new(
-                        LogLevel::Error,
-                        format!("Shader did not have a Node with id {}", id),
-                    )
-                    .spawn(&mut commands);
+                match current_shader.remove_node(id) {
+                    None => {
+                        LogElement::new(
+                            LogLevel::Error,
+                            format!("Shader did not have a Node with id {}", id),
+                        )
+                        .spawn(&mut commands);
+                    }
+                    Some((_, severed)) => {
+                        current_shader.delete_connection_entities(&severed, &mut commands);
+                    }
                 }
                 current_shader.delete_node_entity(id, &mut commands);
             }
             ShaderEvent::DeleteConstant { id } => {
                 LogElement::new(LogLevel::Info, format!("Deleting constant {}", id))
                     .spawn(&mut commands);
-                if current_shader.remove_constant(id).is_none() {
-                    LogElement::new(
-                        LogLevel::Error,
-                        format!("Shader did not have a constant with id {}", id),
-                    )
-                    .spawn(&mut commands);
+                match current_shader.remove_constant(id) {
+                    None => {
+                        LogElement::new(
+                            LogLevel::Error,
+                            format!("Shader did not have a constant with id {}", id),
+                        )
+                        .spawn(&mut commands);
+                    }
+                    Some((_, severed)) => {
+                        current_shader.delete_connection_entities(&severed, &mut commands);
+                    }
                 }
                 current_shader.delete_constant_entity(id, &mut commands);
             }
             ShaderEvent::DeleteInputProperty { id } => {
                 LogElement::new(LogLevel::Info, format!("Deleting input property {}", id))
                     .spawn(&mut commands);
-                if current_shader.remove_input_property(id).is_none() {
-                    LogElement::new(
-                        LogLevel::Error,
-                        format!("Shader did not have an input with id {}", id),
-                    )
-                    .spawn(&mut commands);
+                match current_shader.remove_input_property(id) {
+                    None => {
+                        LogElement::new(
+                            LogLevel::Error,
+                            format!("Shader did not have an input with id {}", id),
+                        )
+                        .spawn(&mut commands);
+                    }
+                    Some((_, severed)) => {
+                        current_shader.delete_connection_entities(&severed, &mut commands);
+                    }
                 }
                 current_shader.delete_input_property_entity(id, &mut commands);
             }
@@ -198,6 +213,10 @@ pub fn handle_shader_event(
                             format!("Failed apply connection: `{}`", e),
                         )
                         .spawn(&mut commands);
+                        NodeConnectorCandidate::remove_candidate(
+                            &mut commands,
+                            connector_canditate.as_deref(),
+                        );
                     }
                 };
             }
@@ -1,6 +1,5 @@
 use bevy_egui::egui::{emath, DragValue, Ui};
-use shady_generator::{Constant, ConstantValue};
-use std::collections::HashMap;
+use shady_generator::{Constant, ConstantValue, IterMut};
 
 const ITER_VALUE_NAMES: [&str; 4] = ["x", "y", "z", "w"];
 
@@ -21,7 +20,23 @@ fn show_iter_range<T: emath::Numeric>(ui: &mut Ui, values: &mut [T]) {
     }
 }
 
-pub fn handle_constants(ui: &mut Ui, constants: &mut HashMap<String, Constant>) {
+fn show_iter_checkbox(ui: &mut Ui, values: &mut [bool]) {
+    for (i, value) in values.iter_mut().enumerate() {
+        ui.checkbox(
+            value,
+            *ITER_VALUE_NAMES.get(i).unwrap_or(&i.to_string().as_str()),
+        );
+    }
+}
+
+fn show_matrix_range<const SIZE: usize>(ui: &mut Ui, columns: &mut [[f32; SIZE]; SIZE]) {
+    for (i, column) in columns.iter_mut().enumerate() {
+        ui.label(format!("Column {}", i));
+        show_iter_range(ui, column);
+    }
+}
+
+pub fn handle_constants(ui: &mut Ui, constants: IterMut<'_, Constant>) {
     for (key, constant) in constants {
         ui.collapsing(key, |ui| {
             constant_value_selection(ui, &mut constant.value);
@@ -64,5 +79,41 @@ pub fn constant_value_selection(ui: &mut Ui, constant: &mut ConstantValue) {
         ConstantValue::IVec4(v) => {
             show_iter_range(ui, v);
         }
+        ConstantValue::BVec2(v) => {
+            show_iter_checkbox(ui, v);
+        }
+        ConstantValue::BVec3(v) => {
+            show_iter_checkbox(ui, v);
+        }
+        ConstantValue::BVec4(v) => {
+            show_iter_checkbox(ui, v);
+        }
+        ConstantValue::UVec2(v) => {
+            show_iter_range(ui, v);
+        }
+        ConstantValue::UVec3(v) => {
+            show_iter_range(ui, v);
+        }
+        ConstantValue::UVec4(v) => {
+            show_iter_range(ui, v);
+        }
+        ConstantValue::DVec2(v) => {
+            show_iter_range(ui, v);
+        }
+        ConstantValue::DVec3(v) => {
+            show_iter_range(ui, v);
+        }
+        ConstantValue::DVec4(v) => {
+            show_iter_range(ui, v);
+        }
+        ConstantValue::Mat2(v) => {
+            show_matrix_range(ui, v);
+        }
+        ConstantValue::Mat3(v) => {
+            show_matrix_range(ui, v);
+        }
+        ConstantValue::Mat4(v) => {
+            show_matrix_range(ui, v);
+        }
     }
 }
@@ -1,5 +1,5 @@
 use crate::resources::{Candidate, CreationCandidate, OperationSelection, TypeSelection};
-use crate::UiState;
+use crate::{CurrentShader, UiState};
 use bevy::prelude::*;
 use bevy_egui::egui::{Button, Color32, Response, Rgba, RichText, Ui, Widget};
 use bevy_egui::{egui, EguiContext};
@@ -7,7 +7,9 @@ use shady_generator::node_operation::{
     FieldToGlsl, NativeFunction, NativeOperation, NonScalarSwizzle,
 };
 use shady_generator::{
-    ConstantValue, FloatingNativeType, NativeType, NonScalarNativeType, NumericScalarNativeType,
+    BooleanVectorNativeType, ConstantValue, FloatingNativeType, IntegerNativeType,
+    MatrixNativeType, NativeType, NonScalarNativeType, NumericScalarNativeType,
+    NumericVectorNativeType, SamplerNativeType, ScalarNativeType, VectorNativeType,
 };
 use std::fmt::Display;
 
@@ -33,6 +35,23 @@ fn type_selection<T: Copy + Display + PartialEq>(
     }
 }
 
+fn sampler_selection(ui: &mut Ui, function: &mut NativeFunction, picked: &mut bool) {
+    let accepted: Vec<SamplerNativeType> = SamplerNativeType::VARIANTS
+        .iter()
+        .copied()
+        .filter(|sampler| {
+            let mut candidate = function.clone();
+            if let Some(s) = candidate.sampler_type_mut() {
+                *s = *sampler;
+            }
+            candidate.accepts_sampler()
+        })
+        .collect();
+    if let Some(sampler) = function.sampler_type_mut() {
+        type_selection(ui, &accepted, sampler, picked);
+    }
+}
+
 fn swizzle_selection<T: FieldToGlsl, const SIZE: usize>(
     ui: &mut Ui,
     value: &mut [T; SIZE],
@@ -58,6 +77,7 @@ pub fn creation_menu(
     mut commands: Commands,
     egui_ctx: ResMut<EguiContext>,
     mut ui_state: ResMut<UiState>,
+    mut shader: ResMut<CurrentShader>,
 ) {
     let mut close = false;
     let mut new_candidate = None;
@@ -125,11 +145,14 @@ pub fn creation_menu(
                                 | NativeOperation::Mul(t)
                                 | NativeOperation::Div(t)
                                 | NativeOperation::Selection(t)
-                                | NativeOperation::Equals(t) => {
+                                | NativeOperation::Equals(t)
+                                | NativeOperation::NotEquals(t) => {
                                     type_selection(ui, NativeType::VARIANTS, t, &mut picked);
                                 }
                                 NativeOperation::GreaterThan(t)
-                                | NativeOperation::GreaterThanEqual(t) => {
+                                | NativeOperation::GreaterThanEqual(t)
+                                | NativeOperation::LessThan(t)
+                                | NativeOperation::LessThanEqual(t) => {
                                     type_selection(
                                         ui,
                                         NumericScalarNativeType::VARIANTS,
@@ -137,6 +160,44 @@ pub fn creation_menu(
                                         &mut picked,
                                     );
                                 }
+                                NativeOperation::MatrixVectorMul(t)
+                                | NativeOperation::VectorMatrixMul(t) => {
+                                    type_selection(ui, MatrixNativeType::VARIANTS, t, &mut picked);
+                                }
+                                NativeOperation::Modulo(t)
+                                | NativeOperation::BitAnd(t)
+                                | NativeOperation::BitOr(t)
+                                | NativeOperation::BitXor(t)
+                                | NativeOperation::BitNot(t)
+                                | NativeOperation::LeftShift(t)
+                                | NativeOperation::RightShift(t) => {
+                                    type_selection(ui, IntegerNativeType::VARIANTS, t, &mut picked);
+                                }
+                                NativeOperation::VectorEquals(t)
+                                | NativeOperation::VectorNotEquals(t) => {
+                                    type_selection(ui, VectorNativeType::VARIANTS, t, &mut picked);
+                                }
+                                NativeOperation::VectorLessThan(t)
+                                | NativeOperation::VectorLessThanEqual(t)
+                                | NativeOperation::VectorGreaterThan(t)
+                                | NativeOperation::VectorGreaterThanEqual(t) => {
+                                    type_selection(
+                                        ui,
+                                        NumericVectorNativeType::VARIANTS,
+                                        t,
+                                        &mut picked,
+                                    );
+                                }
+                                NativeOperation::Any(t)
+                                | NativeOperation::All(t)
+                                | NativeOperation::VectorNo(t) => {
+                                    type_selection(
+                                        ui,
+                                        BooleanVectorNativeType::VARIANTS,
+                                        t,
+                                        &mut picked,
+                                    );
+                                }
                                 _ => {
                                     picked = true;
                                 }
@@ -183,7 +244,26 @@ pub fn creation_menu(
                                 | NativeFunction::Normalize(t)
                                 | NativeFunction::FaceForward(t)
                                 | NativeFunction::Reflect(t)
-                                | NativeFunction::Refract(t) => {
+                                | NativeFunction::Refract(t)
+                                | NativeFunction::HyperbolicSine(t)
+                                | NativeFunction::HyperbolicCosine(t)
+                                | NativeFunction::HyperbolicTangent(t)
+                                | NativeFunction::HyperbolicArcSine(t)
+                                | NativeFunction::HyperbolicArcCosine(t)
+                                | NativeFunction::HyperbolicArcTangent(t)
+                                | NativeFunction::Truncate(t)
+                                | NativeFunction::Round(t)
+                                | NativeFunction::RoundEven(t)
+                                | NativeFunction::FractionalIntegralParts(t)
+                                | NativeFunction::IsNan(t)
+                                | NativeFunction::IsInfinite(t)
+                                | NativeFunction::FloatBitsToInt(t)
+                                | NativeFunction::FloatBitsToUInt(t)
+                                | NativeFunction::IntBitsToFloat(t)
+                                | NativeFunction::UIntBitsToFloat(t)
+                                | NativeFunction::FusedMultiplyAdd(t)
+                                | NativeFunction::SignificandExponent(t)
+                                | NativeFunction::LoadExponent(t) => {
                                     type_selection(
                                         ui,
                                         FloatingNativeType::VARIANTS,
@@ -191,37 +271,74 @@ pub fn creation_menu(
                                         &mut picked,
                                     );
                                 }
+                                NativeFunction::BitfieldExtract(t)
+                                | NativeFunction::BitfieldInsert(t)
+                                | NativeFunction::BitfieldReverse(t)
+                                | NativeFunction::BitCount(t)
+                                | NativeFunction::FindLsb(t)
+                                | NativeFunction::FindMsb(t) => {
+                                    type_selection(ui, IntegerNativeType::VARIANTS, t, &mut picked);
+                                }
+                                NativeFunction::Transpose(t)
+                                | NativeFunction::Inverse(t)
+                                | NativeFunction::Determinant(t)
+                                | NativeFunction::OuterProduct(t)
+                                | NativeFunction::MatrixCompMult(t) => {
+                                    type_selection(ui, MatrixNativeType::VARIANTS, t, &mut picked);
+                                }
+                                NativeFunction::Texture(_)
+                                | NativeFunction::TextureLod(_)
+                                | NativeFunction::TextureGrad(_)
+                                | NativeFunction::TextureOffset(_)
+                                | NativeFunction::TexelFetch(_)
+                                | NativeFunction::TextureSize(_)
+                                | NativeFunction::TextureGather(_)
+                                | NativeFunction::TextureProj(_)
+                                | NativeFunction::TextureProjLod(_) => {
+                                    sampler_selection(ui, f, &mut picked);
+                                }
                                 _ => {
                                     picked = true;
                                 }
                             },
                             TypeSelection::TypeSwizzle(swizzle) => {
+                                ui.horizontal_wrapped(|ui| {
+                                    let component_type = swizzle.component_type_mut();
+                                    for variant in ScalarNativeType::VARIANTS {
+                                        ui.selectable_value(
+                                            component_type,
+                                            *variant,
+                                            variant.to_string(),
+                                        );
+                                    }
+                                });
+                                ui.separator();
                                 match swizzle {
-                                    NonScalarSwizzle::Vec2ToVec2(v) => {
+                                    NonScalarSwizzle::Vec2ToVec2(_, v) => {
                                         swizzle_selection(ui, v, &mut picked);
                                     }
-                                    NonScalarSwizzle::Vec2ToVec3(v) => {
+                                    NonScalarSwizzle::Vec2ToVec3(_, v) => {
                                         swizzle_selection(ui, v, &mut picked);
                                     }
-                                    NonScalarSwizzle::Vec2ToVec4(v) => {
+                                    NonScalarSwizzle::Vec2ToVec4(_, v) => {
                                         swizzle_selection(ui, v, &mut picked);
                                     }
-                                    NonScalarSwizzle::Vec3ToVec2(v) => {
+                                    NonScalarSwizzle::Vec3ToVec2(_, v) => {
                                         swizzle_selection(ui, v, &mut picked);
                                     }
-                                    NonScalarSwizzle::Vec3ToVec3(v) => {
+                                    NonScalarSwizzle::Vec3ToVec3(_, v) => {
                                         swizzle_selection(ui, v, &mut picked);
                                     }
-                                    NonScalarSwizzle::Vec3ToVec4(v) => {
+                                    NonScalarSwizzle::Vec3ToVec4(_, v) => {
                                         swizzle_selection(ui, v, &mut picked);
                                     }
-                                    NonScalarSwizzle::Vec4ToVec2(v) => {
+                                    NonScalarSwizzle::Vec4ToVec2(_, v) => {
                                         swizzle_selection(ui, v, &mut picked);
                                     }
-                                    NonScalarSwizzle::Vec4ToVec3(v) => {
+                                    NonScalarSwizzle::Vec4ToVec3(_, v) => {
                                         swizzle_selection(ui, v, &mut picked);
                                     }
-                                    NonScalarSwizzle::Vec4ToVec4(v) => {
+                                    NonScalarSwizzle::Vec4ToVec4(_, v) => {
                                         swizzle_selection(ui, v, &mut picked);
                                     }
                                 };
@@ -229,7 +346,7 @@ pub fn creation_menu(
                         }
                         if picked {
                             new_candidate = Some(Candidate::Creation(
-                                intermediate_candidate.creation_candidate(),
+                                intermediate_candidate.creation_candidate(&mut shader.shader),
                             ));
                         }
                     }
@@ -8,10 +8,13 @@ use crate::systems::ui::constants::handle_constants;
 use crate::systems::ui::preview_material::handle_preview;
 use crate::{CurrentShader, PreviewMaterial, UiState, VERSION};
 use bevy::prelude::*;
-use bevy_egui::egui::{Color32, Frame, RichText};
+use bevy_egui::egui::{Color32, ComboBox, Frame, RichText};
 use bevy_egui::{egui, EguiContext};
 use shady_generator::node_operation::{NativeFunction, NativeOperation, NonScalarSwizzle};
-use shady_generator::{ConstantValue, FloatingNativeType, NativeType, NonScalarNativeType};
+use shady_generator::{
+    ConstantValue, FloatingNativeType, GlslVersion, GraphicLibrary, NamingStrategy, NativeType,
+    NonScalarNativeType,
+};
 
 pub fn setup(egui_ctx: ResMut<EguiContext>) {
     egui_ctx.ctx().set_visuals(egui::Visuals {
@@ -43,20 +46,49 @@ pub fn menu(
             ui.label("Shader name:");
             ui.text_edit_singleline(&mut shader.name);
 
-            // ComboBox::from_label("Type")
-            //     .selected_text(shader.shader_type.to_string())
-            //     .show_ui(ui, |ui| {
-            //         ui.selectable_value(&mut shader.shader_type, ShaderType::Vertex, "Vertex");
-            //         ui.selectable_value(&mut shader.shader_type, ShaderType::Fragment, "Fragment");
-            //     });
-
-            // ComboBox::from_label("Target Lib")
-            //     .selected_text(shader.library.to_string())
-            //     .show_ui(ui, |ui| {
-            //         ui.selectable_value(&mut shader.library, GraphicLibrary::OpenGl, "OpenGl");
-            //         ui.selectable_value(&mut shader.library, GraphicLibrary::OpenGlEs, "OpenGlEs");
-            //         ui.selectable_value(&mut shader.library, GraphicLibrary::WebGPU, "WebGPU");
-            //     });
+            ComboBox::from_label("Target Lib")
+                .selected_text(shader.library.to_string())
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut shader.library, GraphicLibrary::OpenGl, "OpenGl");
+                    ui.selectable_value(&mut shader.library, GraphicLibrary::OpenGlEs, "OpenGlEs");
+                    ui.selectable_value(&mut shader.library, GraphicLibrary::WebGPU, "WebGPU");
+                });
+            if shader.library != GraphicLibrary::WebGPU {
+                if shader.glsl_version.library() != shader.library {
+                    shader.glsl_version = GlslVersion::VARIANTS
+                        .iter()
+                        .rev()
+                        .find(|v| v.library() == shader.library)
+                        .copied()
+                        .unwrap_or_default();
+                }
+                ComboBox::from_label("GLSL Version")
+                    .selected_text(shader.glsl_version.to_string())
+                    .show_ui(ui, |ui| {
+                        let library = shader.library;
+                        for version in GlslVersion::VARIANTS
+                            .iter()
+                            .filter(|v| v.library() == library)
+                        {
+                            ui.selectable_value(
+                                &mut shader.glsl_version,
+                                *version,
+                                version.to_string(),
+                            );
+                        }
+                    });
+            }
+            ComboBox::from_label("Variable Names")
+                .selected_text(shader.naming_strategy.to_string())
+                .show_ui(ui, |ui| {
+                    for strategy in NamingStrategy::VARIANTS {
+                        ui.selectable_value(
+                            &mut shader.naming_strategy,
+                            *strategy,
+                            strategy.to_string(),
+                        );
+                    }
+                });
 
             ui.separator();
             ui.label("Properties");